@@ -0,0 +1,114 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use elsa::FrozenIndexSet;
+use fxhash::FxBuildHasher;
+use indexmap::IndexSet;
+use interner::{FrozenInterner, Interner as Inter};
+use std::hash::BuildHasher;
+use std::thread;
+
+type Interner<T> = Inter<T, FxBuildHasher>;
+
+const ITER: u32 = 32 * 1024;
+
+// the read phase of insert-then-read workloads, all values are interned before the lookups
+// start. compares the concurrent interner, the frozen copy of it, an `IndexSet` that is only
+// read after it is built and an elsa `FrozenIndexSet`. The `FrozenIndexSet` is not `Sync`, so it
+// is only read from the benchmark thread, without the spawn of the threads of the other lookups.
+
+fn build(values: &'static [u32]) -> Interner<&'static u32> {
+    let interner = Interner::with_capacity_and_hasher(0, FxBuildHasher::default());
+    for value in values.iter() {
+        interner.intern_ref(value, || value);
+    }
+    interner
+}
+
+fn lookup_all<F: Fn(&u32) -> bool + Sync>(values: &[u32], threads: usize, get: F) {
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                for value in values.iter() {
+                    assert!(get(value));
+                }
+            });
+        }
+    });
+}
+
+fn frozen_read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Frozen/read");
+    let values: Vec<u32> = (0..ITER).collect();
+    let values: &'static [u32] = values.leak();
+    let interner = build(values);
+    let frozen: FrozenInterner<&u32, FxBuildHasher> = build(values).freeze();
+    let index_set: IndexSet<&u32, FxBuildHasher> = values.iter().collect();
+    let elsa_set: FrozenIndexSet<&u32, FxBuildHasher> = values.iter().collect();
+    let max = usize::from(thread::available_parallelism().unwrap());
+
+    for threads in (1..=max).filter(|thread| *thread == 1 || *thread % 4 == 0) {
+        group.throughput(Throughput::Elements((ITER * threads as u32) as u64));
+        group.bench_with_input(
+            BenchmarkId::new("interner", threads),
+            &threads,
+            |bencher, &threads| {
+                bencher.iter(|| {
+                    lookup_all(values, threads, |value| {
+                        let hash = interner.hasher().hash_one(value);
+                        interner.get_from_hash(hash, |result| *result == value).is_some()
+                    })
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("frozen", threads),
+            &threads,
+            |bencher, &threads| {
+                bencher.iter(|| lookup_all(values, threads, |value| frozen.get(value).is_some()))
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("indexmap", threads),
+            &threads,
+            |bencher, &threads| {
+                bencher.iter(|| lookup_all(values, threads, |value| index_set.get(value).is_some()))
+            },
+        );
+        if threads == 1 {
+            group.bench_with_input(BenchmarkId::new("elsa", threads), &threads, |bencher, _| {
+                bencher.iter(|| {
+                    for value in values.iter() {
+                        assert!(elsa_set.get(&value).is_some());
+                    }
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn freeze(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Frozen/freeze");
+    let values: Vec<u32> = (0..ITER).collect();
+    let values: &'static [u32] = values.leak();
+
+    group.throughput(Throughput::Elements(ITER as u64));
+    group.bench_function("interner", |bencher| {
+        bencher.iter_batched(|| build(values), |interner| interner.freeze(), BatchSize::LargeInput)
+    });
+    group.bench_function("indexmap", |bencher| {
+        bencher.iter(|| values.iter().collect::<IndexSet<&u32, FxBuildHasher>>())
+    });
+    group.bench_function("elsa", |bencher| {
+        bencher.iter(|| {
+            let set: FrozenIndexSet<&u32, FxBuildHasher> = FrozenIndexSet::default();
+            for value in values.iter() {
+                set.insert(value);
+            }
+            set
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, frozen_read, freeze);
+criterion_main!(benches);
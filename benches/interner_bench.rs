@@ -16,7 +16,6 @@ struct MultithreadedBench<T> {
     start: Arc<Barrier>,
     end: Arc<Barrier>,
     interner: T,
-    num_threads: usize,
 }
 
 impl<T: Send + Sync + Clone + 'static> MultithreadedBench<T> {
@@ -25,7 +24,6 @@ impl<T: Send + Sync + Clone + 'static> MultithreadedBench<T> {
             start: Arc::new(Barrier::new(num_threads + 1)),
             end: Arc::new(Barrier::new(num_threads + 1)),
             interner,
-            num_threads,
         }
     }
 
@@ -34,7 +32,7 @@ impl<T: Send + Sync + Clone + 'static> MultithreadedBench<T> {
         let end = self.end.clone();
         let mut interner = self.interner.clone();
         thread::spawn(move || {
-            f(&*start, &*end, &mut interner);
+            f(&start, &end, &mut interner);
         });
         self
     }
@@ -43,8 +41,7 @@ impl<T: Send + Sync + Clone + 'static> MultithreadedBench<T> {
         self.start.wait();
         let t0 = Instant::now();
         self.end.wait();
-        let time = t0.elapsed();
-        time
+        t0.elapsed()
     }
 }
 
@@ -213,8 +210,40 @@ fn get_already_interned_u32refs(c: &mut Criterion) {
     group.finish();
 }
 
+fn drop_large_tables(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Interner/drop_large_tables");
+    let values: Vec<u32> = (0..16 * ITER).collect();
+    let values: &'static [u32] = values.leak();
+
+    for capacity in [ITER as usize, 16 * ITER as usize] {
+        group.throughput(Throughput::Elements(capacity as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(capacity),
+            &capacity,
+            |bencher, &capacity| {
+                bencher.iter_batched(
+                    || {
+                        // starts small so the chain of old tables is dropped too
+                        let interner =
+                            Interner::with_capacity_and_hasher(0, FxBuildHasher::default());
+                        for value in values[..capacity].iter() {
+                            interner.intern_ref(value, || value);
+                        }
+                        interner
+                    },
+                    drop,
+                    criterion::BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
+    drop_large_tables,
     get_already_interned_u32refs,
     intern_same_u32refs_in_all_threads,
     intern_diffrent_u32refs_in_all_threads,
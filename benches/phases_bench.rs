@@ -0,0 +1,75 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use fxhash::FxBuildHasher;
+use interner::Interner as Inter;
+use std::hash::BuildHasher;
+
+type Interner<T> = Inter<T, FxBuildHasher>;
+
+const ITER: u32 = 32 * 1024;
+
+// single threaded benchmarks of the phases of an insert, build with the `no-inline-hot` feature
+// to see the phases as separate symbols when profiling them.
+
+fn probe_hit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Phases/probe_hit");
+    let values: Vec<u32> = (0..ITER).collect();
+    let interner = Interner::with_capacity_and_hasher(ITER as usize, FxBuildHasher::default());
+    for value in values.iter() {
+        interner.intern_ref(value, || value);
+    }
+    let hashes: Vec<u64> = values.iter().map(|value| interner.hasher().hash_one(value)).collect();
+
+    group.throughput(Throughput::Elements(ITER as u64));
+    group.bench_function("get_from_hash", |bencher| {
+        bencher.iter(|| {
+            for (value, hash) in values.iter().zip(hashes.iter()) {
+                criterion::black_box(interner.get_from_hash(*hash, |val| *val == value));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn probe_miss(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Phases/probe_miss");
+    let values: Vec<u32> = (0..ITER).collect();
+    let interner = Interner::with_capacity_and_hasher(ITER as usize, FxBuildHasher::default());
+    for value in values.iter() {
+        interner.intern_ref(value, || value);
+    }
+    let missing: Vec<u32> = (ITER..2 * ITER).collect();
+    let hashes: Vec<u64> = missing.iter().map(|value| interner.hasher().hash_one(value)).collect();
+
+    group.throughput(Throughput::Elements(ITER as u64));
+    group.bench_function("get_from_hash", |bencher| {
+        bencher.iter(|| {
+            for (value, hash) in missing.iter().zip(hashes.iter()) {
+                criterion::black_box(interner.get_from_hash(*hash, |val| *val == value));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn reserve_and_unlock(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Phases/reserve_and_unlock");
+    let values: Vec<u32> = (0..ITER).collect();
+
+    group.throughput(Throughput::Elements(ITER as u64));
+    group.bench_function("intern_ref", |bencher| {
+        bencher.iter_batched(
+            || Interner::with_capacity_and_hasher(ITER as usize, FxBuildHasher::default()),
+            |interner| {
+                for value in values.iter() {
+                    interner.intern_ref(value, || value);
+                }
+                interner
+            },
+            criterion::BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, probe_hit, probe_miss, reserve_and_unlock);
+criterion_main!(benches);
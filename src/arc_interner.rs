@@ -0,0 +1,198 @@
+use crate::interner::{DefaultHashBuilder, Interner};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+// a strong count of an `Arc` that is owned by the `ArcInterner`, hashed and compared by the value
+struct ArcRef<T: ?Sized>(NonNull<T>);
+
+impl<T: ?Sized> ArcRef<T> {
+    #[inline]
+    fn new(arc: Arc<T>) -> Self {
+        // SAFTY: the pointer of an `Arc` is never null
+        Self(unsafe { NonNull::new_unchecked(Arc::into_raw(arc) as *mut T) })
+    }
+
+    #[inline]
+    fn get(&self) -> &T {
+        // SAFTY: the `ArcInterner` holds a strong count until it is dropped
+        unsafe { self.0.as_ref() }
+    }
+
+    // returns a new `Arc` of the value, the count of the interner is kept
+    #[inline]
+    fn to_arc(self) -> Arc<T> {
+        // SAFTY: the pointer is from `Arc::into_raw` and the interner holds a strong count
+        unsafe {
+            Arc::increment_strong_count(self.0.as_ptr());
+            Arc::from_raw(self.0.as_ptr())
+        }
+    }
+}
+
+unsafe impl<T: ?Sized + Send + Sync> Send for ArcRef<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for ArcRef<T> {}
+
+impl<T: ?Sized> Clone for ArcRef<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for ArcRef<T> {}
+
+impl<T: ?Sized + PartialEq> PartialEq for ArcRef<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for ArcRef<T> {}
+
+impl<T: ?Sized + Hash> Hash for ArcRef<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.get().hash(state)
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for ArcRef<T> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self.get()
+    }
+}
+
+/// An interner of values that are not `Copy`, the values are stored in [`Arc`]s and the
+/// interner returns clones of the `Arc`s.
+///
+/// The interner holds one strong count of every interned value until it is dropped, so the
+/// returned `Arc`s of equal values point to the same allocation.
+///
+/// # Examples
+///
+/// ```
+/// use interner::ArcInterner;
+/// use std::sync::Arc;
+///
+/// let interner: ArcInterner<String> = ArcInterner::new();
+/// let a = interner.intern("a".to_string());
+/// let b = interner.intern("a".to_string());
+/// assert!(Arc::ptr_eq(&a, &b));
+/// drop(interner);
+/// assert_eq!(2, Arc::strong_count(&a));
+/// ```
+///
+/// Unsized values like `str` are interned with [`ArcInterner::intern_ref`]:
+///
+/// ```
+/// use interner::ArcInterner;
+/// use std::sync::Arc;
+///
+/// let interner: ArcInterner<str> = ArcInterner::new();
+/// let a = interner.intern_ref("a", |value| Arc::from(value));
+/// assert!(Arc::ptr_eq(&a, &interner.intern_ref("a", |_| unreachable!())));
+/// ```
+pub struct ArcInterner<T: ?Sized, S = DefaultHashBuilder> {
+    interner: Interner<ArcRef<T>, S>,
+}
+
+impl<T: ?Sized> ArcInterner<T, DefaultHashBuilder> {
+    /// Creates an empty `ArcInterner`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl<T: ?Sized> Default for ArcInterner<T, DefaultHashBuilder> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ?Sized, S> ArcInterner<T, S> {
+    /// Creates an empty `ArcInterner` which will use the given hash builder to hash the values.
+    #[inline]
+    pub const fn with_hasher(hash_builder: S) -> Self {
+        Self { interner: Interner::with_hasher(hash_builder) }
+    }
+
+    /// Returns the number of interned values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.interner.len()
+    }
+
+    /// Returns `true` if no values are interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.interner.is_empty()
+    }
+}
+
+impl<T, S> ArcInterner<T, S>
+where
+    T: ?Sized + Hash + Eq,
+    S: BuildHasher,
+{
+    /// Interns the value and returns an `Arc` of the interned value, `make` is only called to
+    /// create the `Arc` if the value was not already interned, see [`Interner::intern_ref`].
+    pub fn intern_ref(&self, value: &T, make: impl FnOnce(&T) -> Arc<T>) -> Arc<T> {
+        self.interner.intern_ref(value, || ArcRef::new(make(value))).to_arc()
+    }
+
+    /// Interns the `Arc` and returns it, or an `Arc` of the already interned value equal to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::ArcInterner;
+    /// use std::sync::Arc;
+    ///
+    /// let interner: ArcInterner<[u8]> = ArcInterner::new();
+    /// let arc: Arc<[u8]> = Arc::from(&b"abc"[..]);
+    /// assert!(Arc::ptr_eq(&arc, &interner.intern_arc(arc.clone())));
+    /// assert!(Arc::ptr_eq(&arc, &interner.intern_arc(Arc::from(&b"abc"[..]))));
+    /// ```
+    pub fn intern_arc(&self, arc: Arc<T>) -> Arc<T> {
+        self.interner.intern_owned::<_, T>(arc, ArcRef::new).to_arc()
+    }
+
+    /// Returns an `Arc` of the interned value equal to `value`, or `None` if it is not interned.
+    pub fn get<Q>(&self, value: &Q) -> Option<Arc<T>>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.interner.hasher().hash_one(value);
+        self.interner
+            .get_from_hash(hash, |result| result.get().borrow() == value)
+            .map(|result| result.to_arc())
+    }
+}
+
+impl<T, S> ArcInterner<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Interns the value and returns an `Arc` of the interned value, the value is moved in to a
+    /// new `Arc` if it was not already interned and dropped otherwise.
+    pub fn intern(&self, value: T) -> Arc<T> {
+        self.interner.intern_owned::<_, T>(value, |value| ArcRef::new(Arc::new(value))).to_arc()
+    }
+}
+
+impl<T: ?Sized, S> Drop for ArcInterner<T, S> {
+    fn drop(&mut self) {
+        self.interner.for_each_value_mut(|value| {
+            // SAFTY: releases the strong count of the interner, each value is visited once
+            drop(unsafe { Arc::from_raw(value.0.as_ptr()) });
+        });
+    }
+}
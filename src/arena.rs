@@ -0,0 +1,61 @@
+use std::ptr::NonNull;
+use std::sync::Mutex;
+
+/// Owns values that are referenced from an interner, they are only freed when the arena is
+/// dropped.
+///
+/// An arena can outlive the interners that reference it, see [`ArenaInterner`].
+///
+/// [`ArenaInterner`]: crate::ArenaInterner
+pub struct Arena<T: ?Sized> {
+    allocations: Mutex<Vec<*mut T>>,
+}
+
+impl<T: ?Sized> Arena<T> {
+    /// Creates an empty arena.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { allocations: Mutex::new(Vec::new()) }
+    }
+
+    /// Returns the number of values in the arena.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.allocations.lock().unwrap().len()
+    }
+
+    /// Returns true if the arena contains no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Moves `value` in to the arena.
+    ///
+    /// The returned pointer is valid until the arena is dropped, references to the value must
+    /// only be handed out with the lifetime of the owner of the arena.
+    pub(crate) fn alloc(&self, value: Box<T>) -> NonNull<T> {
+        let allocation = Box::into_raw(value);
+        self.allocations.lock().unwrap().push(allocation);
+        // SAFTY: a `Box` is never null
+        unsafe { NonNull::new_unchecked(allocation) }
+    }
+}
+
+impl<T: ?Sized> Default for Arena<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ?Sized> Drop for Arena<T> {
+    fn drop(&mut self) {
+        for allocation in self.allocations.get_mut().unwrap().drain(..) {
+            let _allocation = unsafe { Box::from_raw(allocation) };
+        }
+    }
+}
+
+unsafe impl<T: ?Sized + Send + Sync> Send for Arena<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for Arena<T> {}
@@ -0,0 +1,106 @@
+use crate::arena::Arena;
+use crate::error::InternError;
+use crate::interner::{DefaultHashBuilder, Interner};
+use std::hash::{BuildHasher, Hash};
+
+/// An interner of values that are stored in an external [`Arena`].
+///
+/// The interned values are borrowed from the arena with the lifetime `'arena`, so they stay
+/// valid after the interner is dropped and are freed when the arena is dropped, without leaking
+/// the values to get `'static` references. The interner is invariant in `'arena` and can not
+/// outlive the arena.
+///
+/// # Examples
+///
+/// ```
+/// use interner::{Arena, ArenaInterner};
+///
+/// let arena: Arena<str> = Arena::new();
+/// let hello = {
+///     let interner: ArenaInterner<'_, str> = ArenaInterner::new(&arena);
+///     let hello = interner.intern_ref("hello", |value| value.into());
+///     assert!(std::ptr::eq(hello, interner.intern_ref("hello", |_| unreachable!())));
+///     hello
+/// };
+/// assert_eq!("hello", hello);
+/// assert_eq!(1, arena.len());
+/// ```
+///
+/// The values can not outlive the arena:
+///
+/// ```compile_fail
+/// use interner::{Arena, ArenaInterner};
+///
+/// let hello = {
+///     let arena: Arena<str> = Arena::new();
+///     let interner: ArenaInterner<'_, str> = ArenaInterner::new(&arena);
+///     interner.intern_ref("hello", |value| value.into())
+/// };
+/// assert_eq!("hello", hello);
+/// ```
+pub struct ArenaInterner<'arena, T: ?Sized, S = DefaultHashBuilder> {
+    interner: Interner<&'arena T, S>,
+    arena: &'arena Arena<T>,
+}
+
+impl<'arena, T: ?Sized> ArenaInterner<'arena, T, DefaultHashBuilder> {
+    /// Creates an empty `ArenaInterner` that stores the values in `arena`.
+    #[inline]
+    pub fn new(arena: &'arena Arena<T>) -> Self {
+        Self::with_hasher(arena, DefaultHashBuilder::default())
+    }
+}
+
+impl<'arena, T: ?Sized, S> ArenaInterner<'arena, T, S> {
+    /// Creates an empty `ArenaInterner` that stores the values in `arena` and hash the values
+    /// with `hash_builder`.
+    #[inline]
+    pub const fn with_hasher(arena: &'arena Arena<T>, hash_builder: S) -> Self {
+        Self { interner: Interner::with_hasher(hash_builder), arena }
+    }
+
+    /// Returns the arena the values are stored in.
+    #[inline]
+    pub fn arena(&self) -> &'arena Arena<T> {
+        self.arena
+    }
+
+    /// Returns the underlying interner.
+    #[inline]
+    pub fn interner(&self) -> &Interner<&'arena T, S> {
+        &self.interner
+    }
+}
+
+impl<'arena, T, S> ArenaInterner<'arena, T, S>
+where
+    T: ?Sized + Hash + Eq,
+    S: BuildHasher,
+{
+    /// Interns the value and returns a reference to the interned value in the arena, `make` is
+    /// only called to create the value that is moved in to the arena if it was not already
+    /// interned, see [`Interner::intern_ref`].
+    pub fn intern_ref(&self, value: &T, make: impl FnOnce(&T) -> Box<T>) -> &'arena T {
+        self.try_intern_ref(value, make).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Interns the value and returns a reference to the interned value in the arena or an error
+    /// if the interner is full, see [`Interner::try_intern_ref`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::MemoryLimit`] if the value is not interned and a resize is needed
+    /// that would pass the limit set with
+    /// [`InternerBuilder::max_memory_bytes`](crate::InternerBuilder::max_memory_bytes).
+    /// Returns [`InternError::ResizeDenied`] if a resize is needed and resizes are denied with
+    /// [`InternerBuilder::deny_resize`](crate::InternerBuilder::deny_resize).
+    pub fn try_intern_ref(
+        &self,
+        value: &T,
+        make: impl FnOnce(&T) -> Box<T>,
+    ) -> Result<&'arena T, InternError> {
+        let arena = self.arena;
+        // SAFTY: the value lives as long as the arena
+        self.interner.try_intern_ref(value, || unsafe { arena.alloc(make(value)).as_ref() })
+    }
+}
@@ -1,11 +1,22 @@
 /// Iterator over the contents of a `u8`, returning the indicies of set bits.
-pub(crate) struct BitMaskIter {
+pub struct BitMaskIter {
     bit_mask: u8,
+    // the returned indexes are rotated by this amount modulo 7
+    rotation: u8,
 }
 
 impl BitMaskIter {
-    pub(crate) fn new(bit_mask: u8) -> Self {
-        Self { bit_mask }
+    pub fn new(bit_mask: u8) -> Self {
+        Self { bit_mask, rotation: 0 }
+    }
+
+    /// Returns an iterator over the set bits of the 7 bit `bit_mask` that starts at index
+    /// `start` and wraps around to the lower indexes.
+    pub fn new_rotated(bit_mask: u8, start: usize) -> Self {
+        debug_assert!(bit_mask < 0x80);
+        let rotation = (start % 7) as u8;
+        let bit_mask = ((bit_mask >> rotation) | (bit_mask << (7 - rotation))) & 0x7F;
+        Self { bit_mask, rotation }
     }
 }
 
@@ -16,6 +27,6 @@ impl Iterator for BitMaskIter {
     fn next(&mut self) -> Option<usize> {
         let bit_mask = std::num::NonZeroU8::new(self.bit_mask)?;
         self.bit_mask &= self.bit_mask - 1;
-        Some(bit_mask.trailing_zeros() as usize)
+        Some((bit_mask.trailing_zeros() as usize + self.rotation as usize) % 7)
     }
 }
@@ -1,10 +1,15 @@
+use crate::builder::Settings;
 use crate::meta_data::{MetaData, MetaDataHandling};
-use crate::raw_interner::RawInterner;
-use parking_lot_core::{self, DEFAULT_PARK_TOKEN, DEFAULT_UNPARK_TOKEN};
+use crate::raw_interner::{HashPair, RawInterner};
+use crate::sanitizer;
+#[cfg(feature = "checked")]
+use crate::slot_state::{self, SlotEvent};
+use crate::stats;
 use std::cell::UnsafeCell;
 use std::hash::{BuildHasher, Hash};
 use std::mem::MaybeUninit;
-use std::sync::atomic::{fence, AtomicU64, Ordering};
+use std::sync::atomic::{fence, AtomicU32, AtomicU64, Ordering};
+use std::time::Instant;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum ReserveResult {
@@ -13,62 +18,137 @@ pub enum ReserveResult {
     AlreadyReservedWithOtherH2,
     AlreadyReservedWithSameH2,
     SlotAvailableButGroupMoved,
+    Poisoned,
 }
 
+/// The number of slots in a bucket.
+pub(crate) const BUCKET_SLOTS: usize = 7;
+
+/// A group of slots that share one meta data word.
+///
+/// A slot is written once, while it is locked, and is never written again after the valid bit
+/// is set with release ordering. Readers only read slots that are valid in meta data read with
+/// acquire ordering, so a read can not race with a write to the same slot and values of any size
+/// are never read torn. This is why the buckets have no sequence counter for readers to retry
+/// on, the meta data also has no spare bits for one. In debug builds and with the `checked`
+/// feature every read is checked against the slots recorded as written in the
+/// [`WrittenSlots`](crate::written_slots::WrittenSlots) of the table.
+///
+/// Values of at most 8 bytes, e.g. `u32` ids or references, are stored in the same cache line as
+/// the meta data, so a lookup that ends in the home bucket matches and reads the value in one
+/// cache line.
 #[repr(align(64))]
 pub(crate) struct Bucket<T> {
     pub meta_data: AtomicU64,
-    pub refs: [MaybeUninit<UnsafeCell<T>>; 7],
+    pub refs: [MaybeUninit<UnsafeCell<T>>; BUCKET_SLOTS],
 }
 
+const _: () = {
+    assert!(std::mem::size_of::<Bucket<u32>>() == 64);
+    assert!(std::mem::size_of::<Bucket<u64>>() == 64);
+    assert!(std::mem::size_of::<Bucket<&str>>() == 128);
+};
+
 impl<T> Bucket<T> {
     #[inline]
     pub unsafe fn set_slot(&self, index: usize, value: T) {
+        #[cfg(feature = "checked")]
+        assert!(index < self.refs.len(), "interner slot index {index} out of bounds");
         UnsafeCell::raw_get(self.refs.get_unchecked(index).as_ptr()).write(value);
     }
 
     #[inline]
     pub fn get_ref_to_slot(&self, index: usize) -> &T {
+        #[cfg(feature = "checked")]
+        assert!(index < self.refs.len(), "interner slot index {index} out of bounds");
         unsafe { &*self.refs.get_unchecked(index).assume_init_ref().get() }
     }
 
-    // move all valid slots from this bucket to the next interner
+    // the slot must be marked valid in `group_meta_data` and `group_meta_data` must have been read
+    // with acquire ordering, that makes the write of the value in `set_slot` visible.
+    #[inline]
+    pub fn get_valid_ref_to_slot(&self, group_meta_data: &MetaData, index: usize) -> &T {
+        debug_assert!(
+            group_meta_data.test_valid_bit(index),
+            "interner read of slot {index} that is not valid"
+        );
+        self.get_ref_to_slot(index)
+    }
+
+    // move all valid slots from this bucket to the next interner, with the dense ids of the
+    // slots if the interner has dense ids. `assert_written` is called with each slot before it
+    // is read.
+    #[cold]
     pub fn transfer_bucket(
         &self,
         new_raw_interner: &RawInterner<T>,
+        dense_ids: &[AtomicU32],
+        assert_written: impl Fn(usize),
         hash_builder: &impl BuildHasher,
     ) -> isize
     where
         T: Copy + Hash,
     {
-        let group_meta_data = self.meta_data.store_moved_flag_to_meta_data(Ordering::Acquire);
+        let group_meta_data = self
+            .meta_data
+            .store_moved_flag_to_meta_data(new_raw_interner.settings().order(Ordering::Acquire));
 
         if group_meta_data.bucket_moved() {
             return 0; //already moved
         }
+        #[cfg(feature = "checked")]
+        slot_state::assert_step(
+            &group_meta_data,
+            0,
+            SlotEvent::MoveBucket,
+            &group_meta_data.moved(),
+            false,
+        );
         let iter = group_meta_data.valid_indexes_iter();
         for index in iter {
-            let value = self.get_ref_to_slot(index);
-            new_raw_interner.transfer_in_to(*value, hash_builder);
+            assert_written(index);
+            let value = self.get_valid_ref_to_slot(&group_meta_data, index);
+            // the id is published with the value, the acquire of the moved flag synchronizes
+            let dense_id = dense_ids.get(index).map_or(0, |id| id.load(Ordering::Relaxed));
+            new_raw_interner.transfer_in_to(
+                HashPair::new(hash_builder.hash_one(value)),
+                *value,
+                dense_id,
+                hash_builder,
+            );
         }
         group_meta_data.count_locked_slots() + 1 // add one to markbucket as done
     }
 
+    // drops the valid values in the bucket if it has not been moved to the next interner, then the
+    // values are owned by the next interner. the bucket must not be used after this.
+    pub unsafe fn drop_slots(&self) {
+        let group_meta_data = MetaData::new(self.meta_data.load(Ordering::Relaxed));
+        if group_meta_data.bucket_moved() {
+            return;
+        }
+        for index in group_meta_data.valid_indexes_iter() {
+            std::ptr::drop_in_place(UnsafeCell::raw_get(self.refs.get_unchecked(index).as_ptr()));
+        }
+    }
+
     #[inline]
-    pub fn get_metadata_acquire(&self) -> MetaData {
-        MetaData::new(self.meta_data.load(Ordering::Acquire))
+    pub fn get_metadata_acquire(&self, settings: &Settings) -> MetaData {
+        self.meta_data.load_meta_data(settings.order(Ordering::Acquire))
     }
 
     fn lock_addr(&self, index: usize) -> usize {
         (&self.meta_data as *const _ as usize) + index
     }
 
-    #[inline]
+    #[cfg_attr(not(feature = "no-inline-hot"), inline)]
+    #[cfg_attr(feature = "no-inline-hot", inline(never))]
     pub(crate) fn reserve(
         &self,
         group_meta_data: &mut MetaData,
         h2: u8,
         index: usize,
+        settings: &Settings,
     ) -> ReserveResult {
         loop {
             if group_meta_data.test_valid_bit(index) {
@@ -83,95 +163,188 @@ impl<T> Bucket<T> {
                 }
                 return ReserveResult::AlreadyReservedWithOtherH2;
             }
+            if group_meta_data.test_poisoned(index) {
+                return ReserveResult::Poisoned;
+            }
             if group_meta_data.bucket_moved() {
                 return ReserveResult::SlotAvailableButGroupMoved;
             }
             let new_group_meta_data = group_meta_data.lock(h2, index);
+            #[cfg(feature = "checked")]
+            let before = *group_meta_data;
             if self.meta_data.compare_exchange_weak_meta_data(
                 group_meta_data,
                 new_group_meta_data,
-                Ordering::AcqRel,
-                Ordering::Acquire,
+                settings.order(Ordering::AcqRel),
+                settings.order(Ordering::Acquire),
             ) {
+                #[cfg(feature = "checked")]
+                slot_state::assert_step(
+                    &before,
+                    index,
+                    SlotEvent::Lock { h2 },
+                    &new_group_meta_data,
+                    false,
+                );
                 return ReserveResult::Reserved;
             }
         }
     }
 
+    // returns false if the slot is still locked at `deadline`, `out_meta_data` has the slot
+    // valid or poisoned when true is returned
     #[cold]
-    pub(crate) fn wait_on_lock_release(&self, out_meta_data: &mut MetaData, index: usize) {
-        let mut group_meta_data = self.meta_data.load_meta_data(Ordering::Relaxed);
+    pub(crate) fn wait_on_lock_release(
+        &self,
+        out_meta_data: &mut MetaData,
+        index: usize,
+        settings: &Settings,
+        deadline: Option<Instant>,
+    ) -> bool {
+        let mut group_meta_data = self.meta_data.load_meta_data(settings.order(Ordering::Relaxed));
+        // most calls of `make` are short, spin a bounded exponential number of times before the
+        // park bit is set so the insert does not need to unpark this thread
+        for round in 0..settings.spin_rounds {
+            if is_released(&group_meta_data, index) {
+                break;
+            }
+            for _ in 0..1u32 << round {
+                std::hint::spin_loop();
+            }
+            group_meta_data = self.meta_data.load_meta_data(settings.order(Ordering::Relaxed));
+        }
         let addr = self.lock_addr(index);
-        let validate = || !self.meta_data.load_meta_data(Ordering::Relaxed).test_valid_bit(index);
-        let before_sleep = || {};
-        let timed_out = |_, _| {};
+        let validate = || {
+            !is_released(&self.meta_data.load_meta_data(settings.order(Ordering::Relaxed)), index)
+        };
 
         loop {
-            if group_meta_data.test_valid_bit(index) {
+            if is_released(&group_meta_data, index) {
                 *out_meta_data = group_meta_data;
-                fence(Ordering::Acquire);
-                return;
+                fence(settings.order(Ordering::Acquire));
+                sanitizer::acquire(&self.meta_data);
+                return true;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return false;
             }
 
             // Set the parked bit
             if !group_meta_data.test_park_bit(index) {
                 let new_group_meta_data = group_meta_data.park(index);
+                #[cfg(feature = "checked")]
+                let before = group_meta_data;
                 if !self.meta_data.compare_exchange_weak_meta_data(
                     &mut group_meta_data,
                     new_group_meta_data,
-                    Ordering::Relaxed,
-                    Ordering::Relaxed,
+                    settings.order(Ordering::Relaxed),
+                    settings.order(Ordering::Relaxed),
                 ) {
                     continue;
                 }
+                #[cfg(feature = "checked")]
+                slot_state::assert_step(
+                    &before,
+                    index,
+                    SlotEvent::Park,
+                    &new_group_meta_data,
+                    false,
+                );
             }
 
             // Park our thread until we are woken up by an unlock
-
-            // SAFETY:
-            //   * `addr` is an address we control.
-            //   * `validate`/`timed_out` does not panic or call into any function of `parking_lot`.
-            //   * `before_sleep` does not call `park`, nor does it panic.
-            unsafe {
-                parking_lot_core::park(
-                    addr,
-                    validate,
-                    before_sleep,
-                    timed_out,
-                    DEFAULT_PARK_TOKEN,
-                    None,
-                );
-            }
+            let h2 = group_meta_data.h2_from_meta(index);
+            stats::record_park(h2);
+            sanitizer::park(addr, validate, deadline);
 
             // Loop back and check if the valid bit was set
-            group_meta_data = self.meta_data.load_meta_data(Ordering::Relaxed);
+            group_meta_data = self.meta_data.load_meta_data(settings.order(Ordering::Relaxed));
+            // a wake up at the deadline is not spurious
+            if !is_released(&group_meta_data, index)
+                && deadline.is_none_or(|deadline| Instant::now() < deadline)
+            {
+                stats::record_spurious_wakeup(h2);
+            }
         }
     }
 
+    #[cfg_attr(not(feature = "no-inline-hot"), inline)]
+    #[cfg_attr(feature = "no-inline-hot", inline(never))]
     pub(crate) fn set_valid_and_unpark(
         &self,
         mut group_meta_data: MetaData,
         h2: u8,
         index: usize,
+        settings: &Settings,
     ) -> bool {
+        sanitizer::release(&self.meta_data);
         loop {
             let new_group_meta_data = group_meta_data.unlock(h2, index);
+            // the park bit is replaced by h2 in the new meta data, check it before the exchange
+            let parked = group_meta_data.test_park_bit(index);
+            #[cfg(feature = "checked")]
+            let before = group_meta_data;
             if self.meta_data.compare_exchange_weak_meta_data(
                 &mut group_meta_data,
                 new_group_meta_data,
-                Ordering::Release,
-                Ordering::Relaxed,
+                settings.order(Ordering::Release),
+                settings.order(Ordering::Relaxed),
             ) {
-                if group_meta_data.test_park_bit(index) {
-                    let addr = self.lock_addr(index);
-                    // SAFETY:
-                    //   * `addr` is an address we control.
-                    unsafe {
-                        parking_lot_core::unpark_all(addr, DEFAULT_UNPARK_TOKEN);
-                    }
+                #[cfg(feature = "checked")]
+                slot_state::assert_step(
+                    &before,
+                    index,
+                    SlotEvent::Publish { h2 },
+                    &new_group_meta_data,
+                    parked,
+                );
+                if parked {
+                    sanitizer::unpark_all(self.lock_addr(index));
                 }
                 return group_meta_data.bucket_moved();
             }
         }
     }
+
+    // poisons the slot locked by this thread and unparks the threads waiting on it, returns true
+    // if the bucket was moved while the slot was locked
+    #[cold]
+    pub(crate) fn poison_and_unpark(
+        &self,
+        mut group_meta_data: MetaData,
+        index: usize,
+        settings: &Settings,
+    ) -> bool {
+        loop {
+            let new_group_meta_data = group_meta_data.poison(index);
+            let parked = group_meta_data.test_park_bit(index);
+            #[cfg(feature = "checked")]
+            let before = group_meta_data;
+            if self.meta_data.compare_exchange_weak_meta_data(
+                &mut group_meta_data,
+                new_group_meta_data,
+                settings.order(Ordering::Release),
+                settings.order(Ordering::Relaxed),
+            ) {
+                #[cfg(feature = "checked")]
+                slot_state::assert_step(
+                    &before,
+                    index,
+                    SlotEvent::Poison,
+                    &new_group_meta_data,
+                    parked,
+                );
+                if parked {
+                    sanitizer::unpark_all(self.lock_addr(index));
+                }
+                return group_meta_data.bucket_moved();
+            }
+        }
+    }
+}
+
+// the insert that locked the slot is done with it, the slot is valid or poisoned
+#[inline]
+fn is_released(group_meta_data: &MetaData, index: usize) -> bool {
+    group_meta_data.test_valid_bit(index) || group_meta_data.test_poisoned(index)
 }
@@ -0,0 +1,622 @@
+use crate::cancellation::CancellationToken;
+use crate::capacity_policy::CapacityPolicy;
+use crate::collision::{CollisionDetector, CollisionReport, CollisionThresholds};
+use crate::interner::{DefaultHashBuilder, Interner};
+use crate::make_timer::MakeTimer;
+use crate::normalizing::{Normalize, NormalizingInterner};
+use crate::read_sampler::ReadSampler;
+use crate::stats::StuckTransfer;
+use crate::stuck_transfer::StuckTransferAlert;
+use std::hash::{BuildHasher, Hash};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+/// The default of `InternerBuilder::spin_before_park`.
+const DEFAULT_SPIN_ROUNDS: u32 = 6;
+
+/// The most rounds of `InternerBuilder::spin_before_park`, the last round spins 2^15 times.
+const MAX_SPIN_ROUNDS: u32 = 16;
+
+/// How the number of buckets of the next table is chosen on a resize, see
+/// [`InternerBuilder::resize_policy`].
+///
+/// The tables are never shrunk and are at least doubled, a resize triggered by a cluster of
+/// values that fills the probe sequence of their home bucket needs more buckets to spread the
+/// cluster. The old tables are kept as long as the interner, so skipping sizes that would soon be
+/// resized again saves the memory of those tables.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResizePolicy {
+    /// The next table has twice the buckets of the table that is resized.
+    #[default]
+    Double,
+    /// The next table is sized for twice the values in the table that is resized, so an almost
+    /// full table is quadrupled.
+    LiveCount,
+    /// The next table is sized for four times the values in the table that is resized and has at
+    /// least four times the buckets, for many threads inserting new values where each resize
+    /// makes the inserts wait on the moves.
+    Contended,
+}
+
+/// Settings that is shared by all tables of an `Interner`.
+#[derive(Clone, Debug)]
+pub(crate) struct Settings {
+    pub(crate) seq_cst: bool,
+    pub(crate) read_transfer_budget: usize,
+    pub(crate) max_memory_bytes: Option<usize>,
+    pub(crate) wide_h2: bool,
+    pub(crate) dense_ids: bool,
+    // set on the first use, so the settings can be created in a const context
+    pub(crate) cancellation_token: OnceLock<CancellationToken>,
+    pub(crate) collision_detector: Option<Arc<CollisionDetector>>,
+    pub(crate) strict_two_table: bool,
+    pub(crate) make_timer: Option<Arc<MakeTimer>>,
+    pub(crate) secure_wipe: bool,
+    pub(crate) read_sampler: Option<Arc<ReadSampler>>,
+    pub(crate) ptr_eq_fast_path: bool,
+    pub(crate) deny_resize: bool,
+    pub(crate) stuck_transfer_alert: Option<Arc<StuckTransferAlert>>,
+    pub(crate) spin_rounds: u32,
+    pub(crate) resize_policy: ResizePolicy,
+}
+
+impl Settings {
+    pub(crate) const fn new() -> Self {
+        Self {
+            seq_cst: false,
+            read_transfer_budget: 0,
+            max_memory_bytes: None,
+            wide_h2: false,
+            dense_ids: false,
+            cancellation_token: OnceLock::new(),
+            collision_detector: None,
+            strict_two_table: false,
+            make_timer: None,
+            secure_wipe: false,
+            read_sampler: None,
+            ptr_eq_fast_path: false,
+            deny_resize: false,
+            stuck_transfer_alert: None,
+            spin_rounds: DEFAULT_SPIN_ROUNDS,
+            resize_policy: ResizePolicy::Double,
+        }
+    }
+
+    /// Returns the cancellation token, all tables of an `Interner` share the same token as the
+    /// token is set before the settings are cloned to a new table.
+    #[inline]
+    pub(crate) fn cancellation_token(&self) -> &CancellationToken {
+        self.cancellation_token.get_or_init(CancellationToken::new)
+    }
+
+    /// Returns the ordering to use for an atomic operation that normally uses `order`.
+    #[inline]
+    pub(crate) fn order(&self, order: Ordering) -> Ordering {
+        if self.seq_cst { Ordering::SeqCst } else { order }
+    }
+}
+
+impl Default for Settings {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A builder for an [`Interner`] with non default settings.
+///
+/// # Examples
+///
+/// ```
+/// use interner::InternerBuilder;
+/// use interner::Interner;
+///
+/// let interner: Interner<&i32> = InternerBuilder::new().capacity(10).seq_cst(true).build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct InternerBuilder<S = DefaultHashBuilder> {
+    capacity: usize,
+    hash_builder: S,
+    settings: Settings,
+    capacity_policy: Option<Arc<CapacityPolicy>>,
+}
+
+impl InternerBuilder<DefaultHashBuilder> {
+    /// Creates a builder for an `Interner` with capacity 0 and the default hasher.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for InternerBuilder<DefaultHashBuilder> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            capacity: 0,
+            hash_builder: DefaultHashBuilder::default(),
+            settings: Settings::default(),
+            capacity_policy: None,
+        }
+    }
+}
+
+impl<S> InternerBuilder<S> {
+    /// Sets the number of elements the `Interner` can hold without reallocating.
+    #[inline]
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets the hash builder used to hash the values.
+    ///
+    /// Warning: `hash_builder` is normally randomly generated, and
+    /// is designed to allow HashMaps to be resistant to attacks that
+    /// cause many collisions and very poor performance. Setting it
+    /// manually using this function can expose a DoS attack vector.
+    #[inline]
+    pub fn hasher<S2>(self, hash_builder: S2) -> InternerBuilder<S2> {
+        InternerBuilder {
+            capacity: self.capacity,
+            hash_builder,
+            settings: self.settings,
+            capacity_policy: self.capacity_policy,
+        }
+    }
+
+    /// Use sequentially consistent ordering for all operations on the slot meta data instead of
+    /// the minimal acquire/release orderings.
+    ///
+    /// This is intended for ruling out ordering bugs on weakly ordered platforms like ARM and
+    /// POWER and for measuring the cost of the relaxed scheme. Expect interning to be noticeably
+    /// slower on those platforms as every meta data access gets a full barrier, on x86 mostly
+    /// the stores get more expensive.
+    #[inline]
+    pub fn seq_cst(mut self, seq_cst: bool) -> Self {
+        self.settings.seq_cst = seq_cst;
+        self
+    }
+
+    /// Sets the maximum number of buckets a lookup with [`Interner::get_from_hash`] moves to the
+    /// new table when it needs to check more than one table during a resize.
+    ///
+    /// When all buckets have been moved, lookups only need to check the new table. The default
+    /// is 0, lookups never move any buckets and the resize is only finished by inserts.
+    #[inline]
+    pub fn read_transfer_budget(mut self, budget: usize) -> Self {
+        self.settings.read_transfer_budget = budget;
+        self
+    }
+
+    /// Sets the maximum number of bytes the tables of the `Interner` are allowed to use.
+    ///
+    /// When an insert needs a resize that would make the tables use more memory than the limit,
+    /// the fallible intern methods like [`Interner::try_intern_ref`] returns
+    /// [`InternError::MemoryLimit`](crate::InternError::MemoryLimit) and the other intern methods
+    /// panics. The limit is not checked for the initial capacity or for resizes needed while
+    /// values are moved to a new table, so the limit can be passed with a few tables.
+    #[inline]
+    pub fn max_memory_bytes(mut self, max_memory_bytes: usize) -> Self {
+        self.settings.max_memory_bytes = Some(max_memory_bytes);
+        self
+    }
+
+    /// Fails inserts that needs a resize instead of resizing, for performance tests that must
+    /// not resize.
+    ///
+    /// When an insert needs a new table the fallible intern methods like
+    /// [`Interner::try_intern_ref`] returns
+    /// [`InternError::ResizeDenied`](crate::InternError::ResizeDenied) and the other intern
+    /// methods panics, so a capacity that is too small for the data is caught instead of showing
+    /// up as a slowdown. Lookups and inserts of values that fits in the table are not affected.
+    /// An interner with capacity 0 has no table, so its first insert fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{InternError, Interner, InternerBuilder};
+    ///
+    /// let values: Vec<i32> = (0..1000).collect();
+    /// let interner: Interner<&i32> = InternerBuilder::new().capacity(10).deny_resize(true).build();
+    /// let result: Result<Vec<&i32>, InternError> =
+    ///     values.iter().map(|value| interner.try_intern_ref(value, || value)).collect();
+    /// assert!(matches!(result, Err(InternError::ResizeDenied { .. })));
+    /// ```
+    #[inline]
+    pub fn deny_resize(mut self, deny_resize: bool) -> Self {
+        self.settings.deny_resize = deny_resize;
+        self
+    }
+
+    /// Use a 16 bit h2 fingerprint per slot instead of the 8 bits stored in the meta data.
+    ///
+    /// With 8 bits about 1 in 256 occupied slots that are checked during a lookup needs a call to
+    /// `Eq`, for large tables with keys that are expensive to compare the extra 8 bits makes that
+    /// 1 in 65536. The extra bits are stored in a separate array with one byte per slot, so the
+    /// tables use about 11% more memory.
+    #[inline]
+    pub fn wide_h2(mut self, wide_h2: bool) -> Self {
+        self.settings.wide_h2 = wide_h2;
+        self
+    }
+
+    /// Gives each value a dense id when it is inserted, see [`Interner::dense_id`].
+    ///
+    /// The ids are stored in a separate array with four bytes per slot and are moved with the
+    /// values when the table is resized.
+    #[inline]
+    pub fn dense_ids(mut self, dense_ids: bool) -> Self {
+        self.settings.dense_ids = dense_ids;
+        self
+    }
+
+    /// Makes sure that values are only moved from one table at a time.
+    ///
+    /// By default inserts continue in the new table while the values are moved from the old
+    /// table, so a new table can be needed before the previous resize is finished and a lookup
+    /// can need to check a chain of several tables. With `strict_two_table` an insert that needs
+    /// the new table first helps to move the values of the old table and waits for the moves of
+    /// other threads to finish, so at most two tables have values, see
+    /// [`Interner::max_chain_depth`]. The only exceptions are when the moved values alone need a
+    /// resize of the new table, which needs a very poor hash function, and when the
+    /// [`cancellation_token`](InternerBuilder::cancellation_token) is cancelled.
+    ///
+    /// Inserts blocks during resizes, and `make` of an insert must not block on another insert in
+    /// to the same interner.
+    #[inline]
+    pub fn strict_two_table(mut self, strict_two_table: bool) -> Self {
+        self.settings.strict_two_table = strict_two_table;
+        self
+    }
+
+    /// Zeroes the memory of the tables before it is freed, for interners of secrets like tokens
+    /// or keys.
+    ///
+    /// The slots, meta data and fingerprints of every table are overwritten with volatile writes
+    /// that the optimizer can not remove when the `Interner` is dropped. The old tables of a
+    /// resize are wiped together with the rest as lookups in progress and the references
+    /// returned by [`Interner::get_from_hash`] can still read the moved values until the
+    /// `Interner` is dropped. The values of a preset and the memory pointed to by the values,
+    /// e.g. the strings of `&str` values, are not wiped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interner, InternerBuilder};
+    ///
+    /// let interner: Interner<[u8; 32]> = InternerBuilder::new().secure_wipe(true).build();
+    /// let key = [7u8; 32];
+    /// assert_eq!(key, interner.intern_ref(&key, || key));
+    /// drop(interner);
+    /// ```
+    #[inline]
+    pub fn secure_wipe(mut self, secure_wipe: bool) -> Self {
+        self.settings.secure_wipe = secure_wipe;
+        self
+    }
+
+    /// Measures the calls of `make` and records the calls that take longer than `threshold`.
+    ///
+    /// The slot of a value is locked while `make` creates it and other threads interning the
+    /// same value are parked until it is done, so slow calls show up as spikes of parked
+    /// threads. The slow calls are returned by [`Interner::slow_make_report`] with the hash of
+    /// the value, and with the `tracing` feature each slow call emits a warning event.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interner, InternerBuilder};
+    /// use std::time::Duration;
+    ///
+    /// let value = 42;
+    /// let interner: Interner<&i32> =
+    ///     InternerBuilder::new().slow_make_threshold(Duration::from_millis(1)).build();
+    /// interner.intern_ref(&value, || {
+    ///     std::thread::sleep(Duration::from_millis(5));
+    ///     &value
+    /// });
+    /// let report = interner.slow_make_report().unwrap();
+    /// assert_eq!(1, report.count);
+    /// assert!(report.max >= Duration::from_millis(5));
+    /// ```
+    #[inline]
+    pub fn slow_make_threshold(mut self, threshold: Duration) -> Self {
+        self.settings.make_timer = Some(Arc::new(MakeTimer::new(threshold)));
+        self
+    }
+
+    /// Compares the addresses of the looked up value and a candidate before calling `Eq`.
+    ///
+    /// When the value given to [`Interner::intern_ref`] or [`Interner::intern_owned`] is borrowed
+    /// from a value returned by the interner, e.g. a `&BigAst` that is interned again, the
+    /// candidate with the same address is found without calling `Eq`, that can be expensive for
+    /// values like trees. For small values `Eq` is cheaper than the extra branch, so it is off by
+    /// default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interner, InternerBuilder};
+    ///
+    /// let tree = vec![vec![1, 2], vec![3]];
+    /// let interner: Interner<&Vec<Vec<i32>>> =
+    ///     InternerBuilder::new().ptr_eq_fast_path(true).build();
+    /// let interned = interner.intern_ref(&tree, || &tree);
+    /// assert!(std::ptr::eq(interned, interner.intern_ref(interned, || unreachable!())));
+    /// ```
+    #[inline]
+    pub fn ptr_eq_fast_path(mut self, ptr_eq_fast_path: bool) -> Self {
+        self.settings.ptr_eq_fast_path = ptr_eq_fast_path;
+        self
+    }
+
+    /// Calls `alert` when the values of a table are not moved to the next table within
+    /// `timeout` after the next table was created.
+    ///
+    /// A bucket is only done moving when the values of its locked slots are set, so a thread that
+    /// dies or blocks forever while it holds a locked slot leaves the interner checking more than
+    /// one table for every lookup. The check is done when an insert continues to the next table
+    /// and by [`Interner::to_be_moved`], and `alert` is called at most once per table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interner, InternerBuilder};
+    /// use std::time::Duration;
+    ///
+    /// let interner: Interner<&i32> = InternerBuilder::new()
+    ///     .stuck_transfer_alert(Duration::from_secs(10), |stuck| {
+    ///         eprintln!("{} buckets not moved after {:?}", stuck.to_be_moved, stuck.elapsed)
+    ///     })
+    ///     .build();
+    /// ```
+    #[inline]
+    pub fn stuck_transfer_alert(
+        mut self,
+        timeout: Duration,
+        alert: impl Fn(StuckTransfer) + Send + Sync + 'static,
+    ) -> Self {
+        self.settings.stuck_transfer_alert =
+            Some(Arc::new(StuckTransferAlert::new(timeout, alert)));
+        self
+    }
+
+    /// Sets how long a thread that needs the value of a slot that is locked by another insert
+    /// spins before it parks, defaults to 6 rounds.
+    ///
+    /// Round `n` spins `2^n` times before the slot is checked again, so the default spins at
+    /// most 63 times, well below a microsecond. Most calls of `make` are shorter than that, so
+    /// the waiting thread usually finds the value without parking and the insert does not need
+    /// to unpark it. Set more rounds when `make` is a bit slower and the threads have cores of
+    /// their own, or 0 to park at once. At most 16 rounds are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interner, InternerBuilder};
+    ///
+    /// let interner: Interner<&i32> = InternerBuilder::new().spin_before_park(10).build();
+    /// assert_eq!(&1, interner.intern_ref(&1, || &1));
+    /// ```
+    #[inline]
+    pub fn spin_before_park(mut self, rounds: u32) -> Self {
+        self.settings.spin_rounds = rounds.min(MAX_SPIN_ROUNDS);
+        self
+    }
+
+    /// Sets how the size of the next table is chosen on a resize, defaults to
+    /// [`ResizePolicy::Double`].
+    ///
+    /// The policies other than `Double` count the values of the table when it is resized, that
+    /// is about as slow as moving its values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interner, InternerBuilder, ResizePolicy};
+    ///
+    /// let values: Vec<i32> = (0..1000).collect();
+    /// let interner: Interner<&i32> =
+    ///     InternerBuilder::new().capacity(10).resize_policy(ResizePolicy::Contended).build();
+    /// for value in values.iter() {
+    ///     interner.intern_ref(value, || value);
+    /// }
+    /// let tables = interner.fragmentation_report().tables;
+    /// assert!(tables.windows(2).all(|tables| tables[1].buckets >= 4 * tables[0].buckets));
+    /// ```
+    #[inline]
+    pub fn resize_policy(mut self, resize_policy: ResizePolicy) -> Self {
+        self.settings.resize_policy = resize_policy;
+        self
+    }
+
+    /// Lets lookups start in the table after the current table when most lookups find their
+    /// value there.
+    ///
+    /// During a resize the lookups start in the oldest table with values that are not moved,
+    /// values that are inserted during the resize are only in the new table so looking them up
+    /// checks both tables. With this set a sample of the lookups records the table they found
+    /// their value in, and when most of them needed the new table the lookups check it first and
+    /// the older table only if the value was not found. The estimate is returned by
+    /// [`Interner::estimated_chain_read_penalty`]. Sampled lookups update shared counters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interner, InternerBuilder};
+    ///
+    /// let interner: Interner<&i32> = InternerBuilder::new().adaptive_get_start(true).build();
+    /// assert_eq!(Some(0.0), interner.estimated_chain_read_penalty());
+    /// ```
+    #[inline]
+    pub fn adaptive_get_start(mut self, adaptive_get_start: bool) -> Self {
+        self.settings.read_sampler = adaptive_get_start.then(|| Arc::new(ReadSampler::default()));
+        self
+    }
+
+    /// Sets the token that stops the moving of values to a new table after a resize when it is
+    /// cancelled.
+    ///
+    /// By default each `Interner` has its own token that is cancelled by
+    /// [`Interner::shutdown`], sharing a token lets one handle stop the work of several interners.
+    #[inline]
+    pub fn cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.settings.cancellation_token = OnceLock::from(cancellation_token);
+        self
+    }
+
+    /// Tracks the probe lengths and h2 collisions of the inserts and calls `on_detect` the first
+    /// time one of the `thresholds` is passed, e.g. to log it or to rebuild the interner with a
+    /// randomly seeded hasher.
+    ///
+    /// This makes it possible to use a fast unseeded hasher with a safety net against
+    /// adversarial keys. The hasher of the `Interner` is not changed. Every insert updates
+    /// shared counters, so inserts from many threads gets slower.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{CollisionThresholds, Interner, InternerBuilder};
+    /// use std::hash::{BuildHasher, Hasher};
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// // a hasher that maps every value to the same hash
+    /// #[derive(Default)]
+    /// struct ConstantHasher;
+    /// impl Hasher for ConstantHasher {
+    ///     fn finish(&self) -> u64 {
+    ///         0
+    ///     }
+    ///     fn write(&mut self, _bytes: &[u8]) {}
+    /// }
+    /// #[derive(Clone, Default)]
+    /// struct ConstantState;
+    /// impl BuildHasher for ConstantState {
+    ///     type Hasher = ConstantHasher;
+    ///     fn build_hasher(&self) -> ConstantHasher {
+    ///         ConstantHasher
+    ///     }
+    /// }
+    ///
+    /// let values: Vec<i32> = (0..100).collect();
+    /// let detected = Arc::new(AtomicBool::new(false));
+    /// let on_detect = Arc::clone(&detected);
+    /// let thresholds = CollisionThresholds { min_inserts: 64, ..CollisionThresholds::default() };
+    /// let interner: Interner<&i32, ConstantState> = InternerBuilder::new()
+    ///     .hasher(ConstantState)
+    ///     .collision_detection(thresholds, move |_report| {
+    ///         on_detect.store(true, Ordering::Relaxed);
+    ///     })
+    ///     .build();
+    /// for value in values.iter() {
+    ///     interner.intern_ref(value, || value);
+    /// }
+    /// assert!(detected.load(Ordering::Relaxed));
+    /// assert!(interner.collision_report().unwrap().h2_collisions > 100);
+    /// ```
+    #[inline]
+    pub fn collision_detection(
+        mut self,
+        thresholds: CollisionThresholds,
+        on_detect: impl Fn(&CollisionReport) + Send + Sync + 'static,
+    ) -> Self {
+        self.settings.collision_detector =
+            Some(Arc::new(CollisionDetector::new(thresholds, Box::new(on_detect))));
+        self
+    }
+
+    /// Sets the capacity to the capacity recommended by `capacity_policy` and records the size of
+    /// the `Interner` in the policy when it is dropped.
+    #[inline]
+    pub fn capacity_policy(mut self, capacity_policy: Arc<CapacityPolicy>) -> Self {
+        self.capacity = capacity_policy.recommended_capacity();
+        self.capacity_policy = Some(capacity_policy);
+        self
+    }
+
+    /// Creates the `Interner`.
+    #[inline]
+    pub fn build<T>(self) -> Interner<T, S> {
+        Interner::with_settings(
+            self.capacity,
+            self.hash_builder,
+            self.settings,
+            self.capacity_policy,
+        )
+    }
+
+    /// Creates the `Interner` with a preset of values that are known ahead of time, e.g. the
+    /// keywords of a compiler.
+    ///
+    /// The preset is an immutable table that is checked before the tables of the interner, so
+    /// interning a preset value does not use any atomic operations and the preset values does
+    /// not use any space in the tables of the interner. [`Interner::iter_chunk`] and
+    /// [`Interner::sample`] only returns the values that are not in the preset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interner, InternerBuilder};
+    ///
+    /// const KEYWORDS: [&str; 3] = ["fn", "let", "match"];
+    /// let interner: Interner<&str> = InternerBuilder::new().build_with_preset(KEYWORDS);
+    /// assert_eq!(3, interner.preset_len());
+    /// assert_eq!("fn", interner.intern_ref("fn", || unreachable!()));
+    /// assert_eq!("foo", interner.intern_ref("foo", || "foo"));
+    /// ```
+    #[inline]
+    pub fn build_with_preset<T>(self, values: impl IntoIterator<Item = T>) -> Interner<T, S>
+    where
+        T: Hash + Eq,
+        S: BuildHasher,
+    {
+        self.build().with_preset(values)
+    }
+
+    /// Creates the `Interner` with a hook that is called once with every distinct value that is
+    /// inserted, so an index of the values, e.g. a reverse map from an id to metadata, can be
+    /// maintained without wrapping every call site that interns values.
+    ///
+    /// The hook is called on the thread that inserted the value, after the value is visible to
+    /// lookups of all threads and before the call that inserted it returns. Values that are
+    /// moved to a new table during a resize are not reported again and the values of a preset
+    /// are not reported. The hooks of different values can run in parallel and in any order, so
+    /// another thread can find a value before the hook has been called for it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interner, InternerBuilder};
+    /// use std::sync::mpsc;
+    ///
+    /// let (sender, receiver) = mpsc::channel();
+    /// let interner: Interner<u64> = InternerBuilder::new().build_with_on_new_value(move |value| {
+    ///     let _ = sender.send(value);
+    /// });
+    /// interner.intern(1, |value| value);
+    /// interner.intern(1, |value| value);
+    /// interner.intern(2, |value| value);
+    /// drop(interner);
+    /// assert_eq!(vec![1, 2], receiver.iter().collect::<Vec<_>>());
+    /// ```
+    #[inline]
+    pub fn build_with_on_new_value<T>(
+        self,
+        on_new_value: impl Fn(T) + Send + Sync + 'static,
+    ) -> Interner<T, S> {
+        self.build().with_on_new_value(on_new_value)
+    }
+
+    /// Creates a [`NormalizingInterner`] that transforms every value with `normalize` before
+    /// it is interned or looked up.
+    #[inline]
+    pub fn build_normalizing<T, Q>(self, normalize: Normalize<Q>) -> NormalizingInterner<T, Q, S>
+    where
+        Q: ?Sized + ToOwned,
+    {
+        NormalizingInterner::new(self.build(), normalize)
+    }
+}
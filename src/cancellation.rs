@@ -0,0 +1,73 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// A handle for stopping the maintenance work of interners, like moving the values to a new
+/// table after a resize.
+///
+/// The token is checked for every bucket that is moved, when it is cancelled the moves in
+/// progress stop and no new are started. The interners are still fully usable but a resize is
+/// not finished, so lookups and inserts can need to check more than one table. A token can be
+/// shared by several interners with [`InternerBuilder::cancellation_token`], see also
+/// [`Interner::shutdown`].
+///
+/// [`InternerBuilder::cancellation_token`]: crate::InternerBuilder::cancellation_token
+/// [`Interner::shutdown`]: crate::Interner::shutdown
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    state: Arc<TokenState>,
+}
+
+#[derive(Debug, Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    // number of moves of buckets in progress
+    in_flight: AtomicUsize,
+}
+
+/// Marks a maintenance operation as in progress until dropped.
+pub(crate) struct MaintenanceGuard<'a> {
+    state: &'a TokenState,
+}
+
+impl Drop for MaintenanceGuard<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.state.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the maintenance work of all interners using this token.
+    #[inline]
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if the token has been cancelled.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Cancels the token and waits until the maintenance work in progress has stopped.
+    pub fn cancel_and_wait(&self) {
+        self.cancel();
+        while self.state.in_flight.load(Ordering::SeqCst) != 0 {
+            std::thread::yield_now();
+        }
+    }
+
+    // the maintenance must check `is_cancelled` after this is called, `cancel_and_wait` either
+    // sees the operation as in progress or the operation sees the token as cancelled.
+    #[inline]
+    pub(crate) fn enter_maintenance(&self) -> MaintenanceGuard<'_> {
+        self.state.in_flight.fetch_add(1, Ordering::SeqCst);
+        MaintenanceGuard { state: &self.state }
+    }
+}
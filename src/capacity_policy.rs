@@ -0,0 +1,74 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Learns the initial capacity for interners that are recreated with a similar content.
+///
+/// Interners built with
+/// [`InternerBuilder::capacity_policy`](crate::InternerBuilder::capacity_policy) start with the
+/// recommended capacity and record the size of their newest table and the number of resizes
+/// when dropped, so the next interner can start big enough to not need any resizes. The
+/// recommendation grows directly to the largest recorded size and shrinks slowly when smaller
+/// interners are recorded.
+///
+/// # Examples
+///
+/// ```
+/// use interner::{CapacityPolicy, Interner, InternerBuilder};
+/// use std::sync::Arc;
+///
+/// let values: Vec<i32> = (0..1000).collect();
+/// let policy = Arc::new(CapacityPolicy::new(0));
+/// for _ in 0..2 {
+///     let interner: Interner<&i32> =
+///         InternerBuilder::new().capacity_policy(Arc::clone(&policy)).build();
+///     for value in values.iter() {
+///         interner.intern_ref(value, || value);
+///     }
+/// }
+/// assert!(policy.recommended_capacity() >= 1000);
+/// ```
+#[derive(Debug, Default)]
+pub struct CapacityPolicy {
+    recommended_capacity: AtomicUsize,
+    recorded_interners: AtomicUsize,
+    recorded_resizes: AtomicUsize,
+}
+
+impl CapacityPolicy {
+    /// Creates a policy that recommends `initial_capacity` until an interner is recorded.
+    #[inline]
+    pub fn new(initial_capacity: usize) -> Self {
+        Self {
+            recommended_capacity: AtomicUsize::new(initial_capacity),
+            recorded_interners: AtomicUsize::new(0),
+            recorded_resizes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the capacity that the next interner is created with.
+    #[inline]
+    pub fn recommended_capacity(&self) -> usize {
+        self.recommended_capacity.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of interners that have been recorded.
+    #[inline]
+    pub fn recorded_interners(&self) -> usize {
+        self.recorded_interners.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total number of resizes done by the recorded interners.
+    #[inline]
+    pub fn recorded_resizes(&self) -> usize {
+        self.recorded_resizes.load(Ordering::Relaxed)
+    }
+
+    // records an interner that ended with `capacity` in the newest table after `resizes` resizes.
+    pub(crate) fn record(&self, capacity: usize, resizes: usize) {
+        self.recorded_interners.fetch_add(1, Ordering::Relaxed);
+        self.recorded_resizes.fetch_add(resizes, Ordering::Relaxed);
+        let _ =
+            self.recommended_capacity.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |old| {
+                if capacity >= old { Some(capacity) } else { Some(old - (old - capacity) / 4) }
+            });
+    }
+}
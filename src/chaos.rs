@@ -0,0 +1,140 @@
+//! Fault injection for stress tests, enabled with the `chaos` feature.
+//!
+//! The rare interleavings of the table operations, like threads parked on a slot of a bucket
+//! that is moved or resizes that are chained while a transfer is ongoing, seldom happen on real
+//! hardware. With [`set`] a thread gets probabilities to sleep between locking a slot and
+//! unlocking it, to fail the compare exchanges of the bucket meta data spuriously and to stall
+//! before transferring a bucket to the next table, so a stress test hits them much more often.
+//! The knobs are thread local, so the threads of a test can get different roles, and the random
+//! numbers are drawn from a seeded generator of the thread. Without the feature the injection
+//! points compile to nothing.
+
+#[cfg(feature = "chaos")]
+use std::cell::Cell;
+#[cfg(feature = "chaos")]
+use std::time::Duration;
+
+/// The probabilities of the faults injected in the operations of the current thread.
+///
+/// The probabilities are in `0.0..1.0`, a probability of `0.0` disables the fault.
+/// A `cas_failure` of `1.0` makes every compare exchange fail and the thread never progresses.
+#[cfg(feature = "chaos")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Chaos {
+    /// The probability to sleep for `delay` after a slot is locked and before it is unlocked.
+    pub unlock_delay: f64,
+    /// The probability that a weak compare exchange of the meta data of a bucket fails
+    /// spuriously.
+    pub cas_failure: f64,
+    /// The probability to sleep for `delay` before a bucket is transferred to the next table.
+    pub transfer_stall: f64,
+    /// How long the delays and stalls sleep.
+    pub delay: Duration,
+    /// The seed of the random numbers of the thread.
+    pub seed: u64,
+}
+
+#[cfg(feature = "chaos")]
+impl Default for Chaos {
+    fn default() -> Self {
+        Self {
+            unlock_delay: 0.0,
+            cas_failure: 0.0,
+            transfer_stall: 0.0,
+            delay: Duration::from_micros(50),
+            seed: 0,
+        }
+    }
+}
+
+#[cfg(feature = "chaos")]
+thread_local! {
+    static CHAOS: Cell<Chaos> = Cell::new(Chaos::default());
+    static STATE: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Sets the faults injected in the operations of the current thread and seeds its random
+/// numbers with `chaos.seed`.
+///
+/// # Examples
+///
+/// ```
+/// use interner::chaos::{self, Chaos};
+/// use interner::Interner;
+///
+/// chaos::set(Chaos { unlock_delay: 0.5, cas_failure: 0.25, ..Chaos::default() });
+/// let interner: Interner<i32> = Interner::new();
+/// assert_eq!(1, interner.intern(1, |value| value));
+/// chaos::clear();
+/// assert_eq!(Chaos::default(), chaos::get());
+/// ```
+#[cfg(feature = "chaos")]
+pub fn set(chaos: Chaos) {
+    CHAOS.with(|cell| cell.set(chaos));
+    STATE.with(|state| state.set(chaos.seed));
+}
+
+/// Returns the faults injected in the operations of the current thread.
+#[cfg(feature = "chaos")]
+pub fn get() -> Chaos {
+    CHAOS.with(Cell::get)
+}
+
+/// Stops injecting faults in the operations of the current thread.
+#[cfg(feature = "chaos")]
+pub fn clear() {
+    set(Chaos::default());
+}
+
+// draws a random number from the splitmix64 generator of the thread and returns `true` with
+// the given probability
+#[cfg(feature = "chaos")]
+fn roll(probability: f64) -> bool {
+    if probability <= 0.0 {
+        return false;
+    }
+    let bits = STATE.with(|state| {
+        let next = state.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state.set(next);
+        let mut bits = next;
+        bits = (bits ^ (bits >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        bits = (bits ^ (bits >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        bits ^ (bits >> 31)
+    });
+    ((bits >> 11) as f64 / (1u64 << 53) as f64) < probability
+}
+
+/// Sleeps between locking a slot and unlocking it, so other threads find it locked and park.
+#[inline]
+pub(crate) fn unlock_delay() {
+    #[cfg(feature = "chaos")]
+    {
+        let chaos = get();
+        if roll(chaos.unlock_delay) {
+            std::thread::sleep(chaos.delay);
+        }
+    }
+}
+
+/// Returns `true` if a weak compare exchange of bucket meta data shall fail spuriously.
+#[inline]
+pub(crate) fn cas_failure() -> bool {
+    #[cfg(feature = "chaos")]
+    {
+        roll(get().cas_failure)
+    }
+    #[cfg(not(feature = "chaos"))]
+    false
+}
+
+/// Sleeps before a bucket is transferred, so inserts race with a half done transfer.
+#[inline]
+pub(crate) fn transfer_stall() {
+    #[cfg(feature = "chaos")]
+    {
+        let chaos = get();
+        if roll(chaos.transfer_stall) {
+            std::thread::sleep(chaos.delay);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Limits for how much the inserted values are allowed to collide before
+/// [`InternerBuilder::collision_detection`] reports them.
+///
+/// With a good hash function the values are spread evenly over the table, many values on the
+/// same probe sequence or with the same h2 indicates a weak hash function or adversarial keys.
+///
+/// [`InternerBuilder::collision_detection`]: crate::InternerBuilder::collision_detection
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CollisionThresholds {
+    /// The maximum number of buckets an insert is allowed to probe past the home bucket of the
+    /// value.
+    pub max_probe_length: usize,
+    /// The maximum average number of values with the same h2 but not equal to the inserted value
+    /// that an insert is allowed to compare with.
+    pub max_h2_collision_rate: f64,
+    /// The number of inserts before `max_h2_collision_rate` is checked.
+    pub min_inserts: usize,
+}
+
+impl Default for CollisionThresholds {
+    #[inline]
+    fn default() -> Self {
+        Self { max_probe_length: 32, max_h2_collision_rate: 0.5, min_inserts: 1024 }
+    }
+}
+
+/// Collision statistics of the inserts into an `Interner`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CollisionReport {
+    /// The number of inserted values.
+    pub inserts: usize,
+    /// The number of values with the same h2 but not equal to the inserted value that inserts
+    /// compared with.
+    pub h2_collisions: usize,
+    /// The maximum number of buckets an insert probed past the home bucket of the value.
+    pub max_probe_length: usize,
+}
+
+/// Collects the collision statistics of an `Interner` and calls the callback the first time a
+/// threshold is passed.
+pub(crate) struct CollisionDetector {
+    thresholds: CollisionThresholds,
+    inserts: AtomicUsize,
+    h2_collisions: AtomicUsize,
+    max_probe_length: AtomicUsize,
+    detected: AtomicBool,
+    on_detect: Box<dyn Fn(&CollisionReport) + Send + Sync>,
+}
+
+impl CollisionDetector {
+    pub(crate) fn new(
+        thresholds: CollisionThresholds,
+        on_detect: Box<dyn Fn(&CollisionReport) + Send + Sync>,
+    ) -> Self {
+        Self {
+            thresholds,
+            inserts: AtomicUsize::new(0),
+            h2_collisions: AtomicUsize::new(0),
+            max_probe_length: AtomicUsize::new(0),
+            detected: AtomicBool::new(false),
+            on_detect,
+        }
+    }
+
+    pub(crate) fn report(&self) -> CollisionReport {
+        CollisionReport {
+            inserts: self.inserts.load(Ordering::Relaxed),
+            h2_collisions: self.h2_collisions.load(Ordering::Relaxed),
+            max_probe_length: self.max_probe_length.load(Ordering::Relaxed),
+        }
+    }
+
+    // records an insert that probed `probe_length` buckets past the home bucket and compared
+    // with `h2_collisions` values with the same h2.
+    pub(crate) fn record_insert(&self, probe_length: usize, h2_collisions: usize) {
+        let inserts = self.inserts.fetch_add(1, Ordering::Relaxed) + 1;
+        let h2_collisions = if h2_collisions != 0 {
+            self.h2_collisions.fetch_add(h2_collisions, Ordering::Relaxed) + h2_collisions
+        } else {
+            self.h2_collisions.load(Ordering::Relaxed)
+        };
+        if probe_length > self.max_probe_length.load(Ordering::Relaxed) {
+            self.max_probe_length.fetch_max(probe_length, Ordering::Relaxed);
+        }
+        if self.detected.load(Ordering::Relaxed) {
+            return;
+        }
+        let thresholds = &self.thresholds;
+        if probe_length > thresholds.max_probe_length
+            || (inserts >= thresholds.min_inserts
+                && h2_collisions as f64 > thresholds.max_h2_collision_rate * inserts as f64)
+        {
+            self.detect();
+        }
+    }
+
+    #[cold]
+    fn detect(&self) {
+        if self.detected.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let report = self.report();
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            inserts = report.inserts,
+            h2_collisions = report.h2_collisions,
+            max_probe_length = report.max_probe_length,
+            "interner collision thresholds passed"
+        );
+        (self.on_detect)(&report);
+    }
+}
+
+impl fmt::Debug for CollisionDetector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CollisionDetector")
+            .field("thresholds", &self.thresholds)
+            .field("report", &self.report())
+            .field("detected", &self.detected)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,173 @@
+use crate::error::InternError;
+use crate::interner::{DefaultHashBuilder, Interner};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+/// How a [`ContentInterner`] decides that two values with the same content hash are equal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Verification {
+    /// Values with the same content hash are also compared with `Eq`, so a hash collision never
+    /// makes different values interned as one.
+    #[default]
+    VerifyWithEq,
+    /// Values with the same content hash are treated as equal without comparing them, see
+    /// [`ContentInterner::collision_probability`] for the risk that different values are
+    /// interned as one.
+    TrustHash,
+}
+
+// the value with its content hash, hashed and compared by the content hash only, the values are
+// compared by the closures given to the interner
+struct ContentEntry<'a, T: ?Sized> {
+    content_hash: u128,
+    value: &'a T,
+}
+
+impl<T: ?Sized> Clone for ContentEntry<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for ContentEntry<'_, T> {}
+
+impl<T: ?Sized> PartialEq for ContentEntry<'_, T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.content_hash == other.content_hash
+    }
+}
+
+impl<T: ?Sized> Eq for ContentEntry<'_, T> {}
+
+impl<T: ?Sized> Hash for ContentEntry<'_, T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.content_hash.hash(state)
+    }
+}
+
+/// An interner of large values that identifies the values by a 128 bit hash of their content.
+///
+/// The content hash of a value is calculated once per intern call, lookups then only compare
+/// content hashes and, with [`Verification::VerifyWithEq`], the values that have the same
+/// content hash. With [`Verification::TrustHash`] the values are never compared, which makes
+/// interning of multi-KB blobs in content-addressed workloads much cheaper. The content hash is
+/// calculated with two randomly seeded SipHash hashers, the table of the interner hashes the
+/// content hash with `S`.
+///
+/// # Examples
+///
+/// ```
+/// use interner::{ContentInterner, Verification};
+///
+/// let blob1 = vec![7u8; 4096];
+/// let blob2 = vec![7u8; 4096];
+/// let interner: ContentInterner<'_, [u8]> = ContentInterner::new(Verification::VerifyWithEq);
+/// let result = interner.intern_ref(&blob1[..], || &blob1[..]);
+/// assert!(std::ptr::eq(result, &blob1[..]));
+/// let result = interner.intern_ref(&blob2[..], || &blob2[..]);
+/// assert!(std::ptr::eq(result, &blob1[..]));
+/// ```
+pub struct ContentInterner<'a, T: ?Sized, S = DefaultHashBuilder> {
+    interner: Interner<ContentEntry<'a, T>, S>,
+    content_hashers: [RandomState; 2],
+    verification: Verification,
+}
+
+impl<'a, T: ?Sized> ContentInterner<'a, T, DefaultHashBuilder> {
+    /// Creates an empty `ContentInterner` that compares values with the same content hash as
+    /// given by `verification`.
+    #[inline]
+    pub fn new(verification: Verification) -> Self {
+        Self::with_hasher(verification, DefaultHashBuilder::default())
+    }
+}
+
+impl<'a, T: ?Sized, S> ContentInterner<'a, T, S> {
+    /// Creates an empty `ContentInterner` that compares values with the same content hash as
+    /// given by `verification` and hash the content hashes with `hash_builder`.
+    #[inline]
+    pub fn with_hasher(verification: Verification, hash_builder: S) -> Self {
+        Self {
+            interner: Interner::with_hasher(hash_builder),
+            content_hashers: [RandomState::new(), RandomState::new()],
+            verification,
+        }
+    }
+
+    /// Returns how values with the same content hash are compared.
+    #[inline]
+    pub fn verification(&self) -> Verification {
+        self.verification
+    }
+
+    /// Returns the probability that two of `values` different values have the same content hash.
+    ///
+    /// With [`Verification::TrustHash`] this is the probability that different values are
+    /// interned as one, e.g. about 1.5e-21 for a billion values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::ContentInterner;
+    ///
+    /// let probability = ContentInterner::<'_, [u8]>::collision_probability(1_000_000_000);
+    /// assert!(probability < 1e-20);
+    /// ```
+    pub fn collision_probability(values: usize) -> f64 {
+        // the birthday bound for a 128 bit hash
+        let values = values as f64;
+        values * (values - 1.0).max(0.0) / 2.0 / 2f64.powi(128)
+    }
+}
+
+impl<'a, T, S> ContentInterner<'a, T, S>
+where
+    T: ?Sized + Hash + Eq,
+    S: BuildHasher,
+{
+    /// Returns the 128 bit content hash of `value`.
+    #[inline]
+    pub fn content_hash(&self, value: &T) -> u128 {
+        let [low, high] = &self.content_hashers;
+        (high.hash_one(value) as u128) << 64 | low.hash_one(value) as u128
+    }
+
+    /// Interns the value and returns a reference to the interned value, `make` is only called if
+    /// no value with the same content is interned, see [`Interner::intern_ref`].
+    pub fn intern_ref(&self, value: &T, make: impl FnOnce() -> &'a T) -> &'a T {
+        self.try_intern_ref(value, make).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Interns the value and returns a reference to the interned value or an error if the
+    /// interner is full, see [`Interner::try_intern_ref`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::MemoryLimit`] if the value is not interned and a resize is needed
+    /// that would pass the limit set with
+    /// [`InternerBuilder::max_memory_bytes`](crate::InternerBuilder::max_memory_bytes).
+    /// Returns [`InternError::ResizeDenied`] if a resize is needed and resizes are denied with
+    /// [`InternerBuilder::deny_resize`](crate::InternerBuilder::deny_resize).
+    pub fn try_intern_ref(
+        &self,
+        value: &T,
+        make: impl FnOnce() -> &'a T,
+    ) -> Result<&'a T, InternError> {
+        let content_hash = self.content_hash(value);
+        let hash = self.interner.hasher().hash_one(content_hash);
+        let verify = self.verification == Verification::VerifyWithEq;
+        self.interner
+            .intern_hashed(
+                hash,
+                value,
+                |value, entry| {
+                    entry.content_hash == content_hash && (!verify || entry.value.eq(*value))
+                },
+                |_| ContentEntry { content_hash, value: make() },
+            )
+            .map(|entry| entry.value)
+    }
+}
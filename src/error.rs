@@ -0,0 +1,148 @@
+use std::fmt;
+
+/// The error type for the fallible intern operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InternError {
+    /// The value could not be interned as the resize needed for it would make the tables of the
+    /// interner use more than `limit` bytes, see
+    /// [`InternerBuilder::max_memory_bytes`](crate::InternerBuilder::max_memory_bytes).
+    MemoryLimit {
+        /// The bytes used by the tables of the interner.
+        used: usize,
+        /// The memory limit of the interner.
+        limit: usize,
+    },
+    /// The value could not be interned as it needs a resize of the table with `buckets` buckets
+    /// and resizes are denied, see
+    /// [`InternerBuilder::deny_resize`](crate::InternerBuilder::deny_resize).
+    ResizeDenied {
+        /// The number of buckets of the full table.
+        buckets: usize,
+    },
+    /// The deadline passed while waiting on another thread to insert in to the slot of the
+    /// value, see [`Interner::intern_ref_with_deadline`](crate::Interner::intern_ref_with_deadline).
+    DeadlineExceeded,
+}
+
+impl fmt::Display for InternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InternError::MemoryLimit { used, limit } => write!(
+                f,
+                "interner memory limit of {limit} bytes reached, the tables use {used} bytes and \
+                 a resize is needed"
+            ),
+            InternError::ResizeDenied { buckets } => write!(
+                f,
+                "interner table with {buckets} buckets needs a resize but resizes are denied"
+            ),
+            InternError::DeadlineExceeded => {
+                f.write_str("the deadline passed before the value was interned")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InternError {}
+
+/// The error type for [`Interner::merge`](crate::Interner::merge) and the set operations like
+/// [`Interner::intersection`](crate::Interner::intersection).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MergeError {
+    /// The interners do not share the same hasher, so the hashes of the values are not
+    /// comparable between them, see [`Interner::child`](crate::Interner::child).
+    HasherMismatch,
+    /// A value could not be interned.
+    Intern(InternError),
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeError::HasherMismatch => {
+                f.write_str("the interners to merge do not share the same hasher")
+            }
+            MergeError::Intern(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for MergeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MergeError::HasherMismatch => None,
+            MergeError::Intern(error) => Some(error),
+        }
+    }
+}
+
+impl From<InternError> for MergeError {
+    #[inline]
+    fn from(error: InternError) -> Self {
+        MergeError::Intern(error)
+    }
+}
+
+/// The error returned by [`Interner::insert_new`](crate::Interner::insert_new) when an equal
+/// value is already interned, contains the interned value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AlreadyPresent<T>(pub T);
+
+impl<T> fmt::Display for AlreadyPresent<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an equal value is already interned")
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for AlreadyPresent<T> {}
+
+/// The error type for [`Interner::debug_validate`](crate::Interner::debug_validate), the first
+/// broken invariant found in the tables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// The meta data of slot `index` of bucket `bucket` in table `table` has bits that no
+    /// sequence of slot transitions reaches, e.g. a park bit without the lock bit.
+    UnreachableSlotState {
+        /// The table of the slot, the first table is 0.
+        table: usize,
+        /// The bucket of the slot.
+        bucket: usize,
+        /// The index of the slot in the bucket.
+        index: usize,
+        /// The meta data of the bucket.
+        meta_data: u64,
+    },
+    /// The valid slots `first` and `second` of bucket `bucket` in table `table` hold equal
+    /// values.
+    DuplicateValue {
+        /// The table of the slots, the first table is 0.
+        table: usize,
+        /// The bucket of the slots.
+        bucket: usize,
+        /// The index of the first slot in the bucket.
+        first: usize,
+        /// The index of the second slot in the bucket.
+        second: usize,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::UnreachableSlotState { table, bucket, index, meta_data } => write!(
+                f,
+                "slot {index} of bucket {bucket} in table {table} has an unreachable state, \
+                 meta data {meta_data:#018x}"
+            ),
+            ValidationError::DuplicateValue { table, bucket, first, second } => write!(
+                f,
+                "slots {first} and {second} of bucket {bucket} in table {table} hold equal values"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
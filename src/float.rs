@@ -0,0 +1,160 @@
+use crate::interner::Interner;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+/// A `f64` key with `Eq` and `Hash` that compares the bits of the canonical form of the value.
+///
+/// All NaN values are replaced with [`f64::NAN`] and `-0.0` with `0.0`, so numbers that are
+/// equal as literals are interned as one value, and NaN is equal to itself.
+///
+/// # Examples
+///
+/// ```
+/// use interner::CanonicalF64;
+///
+/// assert_eq!(CanonicalF64::new(0.0), CanonicalF64::new(-0.0));
+/// assert_eq!(CanonicalF64::new(f64::NAN), CanonicalF64::new(-f64::NAN));
+/// assert_ne!(CanonicalF64::new(1.0), CanonicalF64::new(1.0 + f64::EPSILON));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct CanonicalF64(f64);
+
+impl CanonicalF64 {
+    /// Creates the key of the canonical form of `value`.
+    #[inline]
+    pub fn new(value: f64) -> Self {
+        if value.is_nan() {
+            Self(f64::NAN)
+        } else {
+            // -0.0 + 0.0 is 0.0 and all other values are unchanged
+            Self(value + 0.0)
+        }
+    }
+
+    /// Returns the canonical value.
+    #[inline]
+    pub fn get(self) -> f64 {
+        self.0
+    }
+}
+
+impl From<f64> for CanonicalF64 {
+    #[inline]
+    fn from(value: f64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl PartialEq for CanonicalF64 {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for CanonicalF64 {}
+
+impl Hash for CanonicalF64 {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+/// A `f32` key with `Eq` and `Hash` that compares the bits of the canonical form of the value.
+///
+/// All NaN values are replaced with [`f32::NAN`] and `-0.0` with `0.0`, see [`CanonicalF64`].
+///
+/// # Examples
+///
+/// ```
+/// use interner::CanonicalF32;
+///
+/// assert_eq!(CanonicalF32::new(0.0), CanonicalF32::new(-0.0));
+/// assert_eq!(CanonicalF32::new(f32::NAN), CanonicalF32::new(-f32::NAN));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct CanonicalF32(f32);
+
+impl CanonicalF32 {
+    /// Creates the key of the canonical form of `value`.
+    #[inline]
+    pub fn new(value: f32) -> Self {
+        if value.is_nan() {
+            Self(f32::NAN)
+        } else {
+            // -0.0 + 0.0 is 0.0 and all other values are unchanged
+            Self(value + 0.0)
+        }
+    }
+
+    /// Returns the canonical value.
+    #[inline]
+    pub fn get(self) -> f32 {
+        self.0
+    }
+}
+
+impl From<f32> for CanonicalF32 {
+    #[inline]
+    fn from(value: f32) -> Self {
+        Self::new(value)
+    }
+}
+
+impl PartialEq for CanonicalF32 {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for CanonicalF32 {}
+
+impl Hash for CanonicalF32 {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+impl<S> Interner<CanonicalF64, S>
+where
+    S: BuildHasher,
+{
+    /// Interns the canonical form of `value` and returns it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{CanonicalF64, Interner};
+    ///
+    /// let interner: Interner<CanonicalF64> = Interner::new();
+    /// assert_eq!(0.0, interner.intern_f64(-0.0).get());
+    /// assert!(interner.intern_f64(-f64::NAN).get().is_nan());
+    /// interner.intern_f64(0.0);
+    /// assert_eq!(2, interner.len());
+    /// ```
+    pub fn intern_f64(&self, value: f64) -> CanonicalF64 {
+        self.intern(CanonicalF64::new(value), |value| value)
+    }
+}
+
+impl<S> Interner<CanonicalF32, S>
+where
+    S: BuildHasher,
+{
+    /// Interns the canonical form of `value` and returns it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{CanonicalF32, Interner};
+    ///
+    /// let interner: Interner<CanonicalF32> = Interner::new();
+    /// assert_eq!(0.0, interner.intern_f32(-0.0).get());
+    /// assert!(interner.intern_f32(-f32::NAN).get().is_nan());
+    /// ```
+    pub fn intern_f32(&self, value: f32) -> CanonicalF32 {
+        self.intern(CanonicalF32::new(value), |value| value)
+    }
+}
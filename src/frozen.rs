@@ -0,0 +1,142 @@
+use crate::interner::DefaultHashBuilder;
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+
+/// A read-only copy of the values of an [`Interner`](crate::Interner) for the read phase of
+/// insert-then-read workloads.
+///
+/// The values are stored sorted by their hash next to an array of the hashes. A lookup uses the
+/// top bits of the hash to find the range of hashes with the same top bits, about one hash for
+/// well distributed hashes, and does a branchless binary search in the range followed by a
+/// compare of the values with the same hash. There are no atomic operations, no tables to move
+/// values between and no locked slots to wait on. This `struct` is created by
+/// [`Interner::freeze`](crate::Interner::freeze).
+///
+/// # Examples
+///
+/// ```
+/// use interner::Interner;
+///
+/// let values = [1, 2, 3];
+/// let interner: Interner<&i32> = Interner::new();
+/// for value in values.iter() {
+///     interner.intern_ref(value, || value);
+/// }
+/// let frozen = interner.freeze();
+/// assert_eq!(3, frozen.len());
+/// assert!(std::ptr::eq(&values[1], *frozen.get(&2).unwrap()));
+/// assert_eq!(None, frozen.get(&4));
+/// ```
+#[derive(Clone)]
+pub struct FrozenInterner<T, S = DefaultHashBuilder> {
+    hash_builder: S,
+    // the hashes in increasing order, `values[i]` has the hash `hashes[i]`
+    hashes: Box<[u64]>,
+    values: Box<[T]>,
+    // the hashes with the top bits `prefix` are at `starts[prefix]..starts[prefix + 1]`
+    starts: Box<[u32]>,
+    // the number of top bits used for the prefix
+    prefix_bits: u32,
+}
+
+impl<T, S> FrozenInterner<T, S> {
+    pub(crate) fn new(hash_builder: S, mut entries: Vec<(u64, T)>) -> Self {
+        entries.sort_unstable_by_key(|(hash, _)| *hash);
+        let (hashes, values): (Vec<u64>, Vec<T>) = entries.into_iter().unzip();
+        let prefix_bits = hashes.len().next_power_of_two().trailing_zeros();
+        let mut starts = Vec::with_capacity((1 << prefix_bits) + 1);
+        let mut pos = 0;
+        for prefix in 0..=(1u64 << prefix_bits) {
+            while pos < hashes.len() && Self::prefix(prefix_bits, hashes[pos]) < prefix {
+                pos += 1;
+            }
+            starts.push(u32::try_from(pos).expect("too many values to freeze"));
+        }
+        Self {
+            hash_builder,
+            hashes: hashes.into_boxed_slice(),
+            values: values.into_boxed_slice(),
+            starts: starts.into_boxed_slice(),
+            prefix_bits,
+        }
+    }
+
+    #[inline]
+    fn prefix(prefix_bits: u32, hash: u64) -> u64 {
+        // a shift by 64 is not allowed, all hashes have the prefix 0 when no bits are used
+        hash.checked_shr(64 - prefix_bits).unwrap_or(0)
+    }
+
+    /// Returns the number of values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if there are no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns a reference to the interner's [`BuildHasher`].
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Returns the values ordered by their hash.
+    #[inline]
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Returns the value with `hash` that `is_match` accepts.
+    #[inline]
+    pub fn get_from_hash(&self, hash: u64, mut is_match: impl FnMut(&T) -> bool) -> Option<&T> {
+        let prefix = Self::prefix(self.prefix_bits, hash) as usize;
+        let (first, end) = (self.starts[prefix] as usize, self.starts[prefix + 1] as usize);
+        let start = first + lower_bound(&self.hashes[first..end], hash);
+        self.hashes[start..end]
+            .iter()
+            .take_while(|slot_hash| **slot_hash == hash)
+            .zip(&self.values[start..])
+            .map(|(_, value)| value)
+            .find(|value| is_match(value))
+    }
+}
+
+// the index of the first hash that is not less than `hash`, the loop only depends on the length
+// so the compare is turned in to a conditional move instead of a branch
+#[inline]
+fn lower_bound(hashes: &[u64], hash: u64) -> usize {
+    let mut base = 0;
+    let mut size = hashes.len();
+    if size == 0 {
+        return 0;
+    }
+    while size > 1 {
+        let half = size / 2;
+        let mid = base + half;
+        base = if hashes[mid] < hash { mid } else { base };
+        size -= half;
+    }
+    base + usize::from(hashes[base] < hash)
+}
+
+impl<T, S> FrozenInterner<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Returns the interned value equal to `value`.
+    #[inline]
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.hash_builder.hash_one(value);
+        self.get_from_hash(hash, |result| value.eq(result.borrow()))
+    }
+}
@@ -0,0 +1,18 @@
+//! Branch hints that do not need the `core_intrinsics` feature.
+//!
+//! A call to a `#[cold]` function on one side of a branch makes the optimizer treat that side as
+//! unlikely, which gives the same code layout as `std::intrinsics::likely` on both stable and
+//! nightly. Can be replaced with `core::hint::likely` when that is stabilized.
+
+#[cold]
+#[inline(always)]
+fn cold_path() {}
+
+/// Hints that `b` is most likely true.
+#[inline(always)]
+pub(crate) fn likely(b: bool) -> bool {
+    if !b {
+        cold_path();
+    }
+    b
+}
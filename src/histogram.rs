@@ -0,0 +1,106 @@
+use crate::interner::{DefaultHashBuilder, Interner};
+use crate::side_table::SideTable;
+use std::hash::{BuildHasher, Hash};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Counts how often each value is seen, indexed by the dense ids of an [`Interner`] instead of
+/// a map from the values to counters.
+///
+/// The values are interned as they are added and counted in a [`SideTable`] by their dense id,
+/// see [`Interner::dense_id`]. `add` only needs a shared reference, so the values can be
+/// counted by several threads at once. [`Interner::histogram`] counts the items of an iterator.
+///
+/// # Panics
+///
+/// [`Histogram::new`] panics if the interner is not built with
+/// [`InternerBuilder::dense_ids`](crate::InternerBuilder::dense_ids).
+///
+/// # Examples
+///
+/// ```
+/// use interner::{Histogram, Interner, InternerBuilder};
+///
+/// let interner: Interner<&str> = InternerBuilder::new().dense_ids(true).build();
+/// let histogram = Histogram::new(&interner);
+/// std::thread::scope(|scope| {
+///     scope.spawn(|| ["a", "b", "a"].into_iter().for_each(|word| { histogram.add(word); }));
+///     scope.spawn(|| ["b", "a"].into_iter().for_each(|word| { histogram.add(word); }));
+/// });
+/// let mut counts = histogram.into_vec();
+/// counts.sort_unstable();
+/// assert_eq!(vec![("a", 3), ("b", 2)], counts);
+/// ```
+pub struct Histogram<'a, T, S = DefaultHashBuilder> {
+    interner: &'a Interner<T, S>,
+    counts: SideTable<AtomicU64>,
+    // the counted values with their dense ids, in the order they were first counted
+    seen: Mutex<Vec<(u32, T)>>,
+    // the values of the preset have no dense ids and are counted here
+    preset_counts: Mutex<Vec<(T, u64)>>,
+}
+
+impl<'a, T, S> Histogram<'a, T, S>
+where
+    T: Eq + Hash + Copy,
+    S: BuildHasher,
+{
+    /// Creates an empty `Histogram` that interns the values in `interner`.
+    pub fn new(interner: &'a Interner<T, S>) -> Self {
+        assert!(interner.has_dense_ids(), "the interner is not built with dense ids");
+        Self {
+            interner,
+            counts: SideTable::new(),
+            seen: Mutex::new(Vec::new()),
+            preset_counts: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Interns `value`, counts it once and returns the interned value.
+    pub fn add(&self, value: T) -> T {
+        let (value, dense_id) = self.interner.intern_with_dense_id(value);
+        match dense_id {
+            Some(id) => {
+                if self.counts.get(id).fetch_add(1, Ordering::Relaxed) == 0 {
+                    self.seen.lock().unwrap().push((id, value));
+                }
+            }
+            None => {
+                let mut preset_counts = self.preset_counts.lock().unwrap();
+                match preset_counts.iter_mut().find(|(counted, _)| *counted == value) {
+                    Some((_, count)) => *count += 1,
+                    None => preset_counts.push((value, 1)),
+                }
+            }
+        }
+        value
+    }
+
+    /// Returns the counted values with their counts, the values of the preset of the interner
+    /// last.
+    pub fn into_vec(self) -> Vec<(T, u64)> {
+        let seen = self.seen.into_inner().unwrap();
+        let mut counts: Vec<(T, u64)> = seen
+            .into_iter()
+            .map(|(id, value)| {
+                let count =
+                    self.counts.try_get(id).map_or(0, |count| count.load(Ordering::Relaxed));
+                (value, count)
+            })
+            .collect();
+        counts.extend(self.preset_counts.into_inner().unwrap());
+        counts
+    }
+}
+
+impl<T, S> Extend<T> for Histogram<'_, T, S>
+where
+    T: Eq + Hash + Copy,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|value| {
+            self.add(value);
+        });
+    }
+}
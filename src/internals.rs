@@ -0,0 +1,85 @@
+//! The raw building blocks of the interner.
+//!
+//! Only available with the `unstable-internals` feature. Nothing in this module is covered by
+//! semver, it changes together with the implementation of the interner.
+
+pub use crate::bitmask::BitMaskIter;
+pub use crate::bucket::ReserveResult;
+pub use crate::meta_data::{MetaData, MetaDataHandling};
+pub use crate::slot_state::{step, SlotEvent, SlotState, Transition};
+
+use crate::interner::Interner;
+use crate::raw_interner::RawInterner;
+use std::hash::{BuildHasher, Hash};
+use std::ops::Range;
+
+/// Creates a table after the newest table of `interner` without moving any values to it.
+///
+/// Together with [`move_buckets`] this builds the chains of partially moved tables that are
+/// otherwise only seen when inserts race with resizes, so tests can run the API against them
+/// deterministically. Inserts continue in the old tables until they find a moved bucket or
+/// need a resize.
+pub fn grow<T, S>(interner: &Interner<T, S>)
+where
+    T: Copy + Hash,
+{
+    let mut raw_interner = interner.first_raw_interner();
+    while let Some(next) = raw_interner.try_get_next_raw_interner() {
+        raw_interner = next;
+    }
+    raw_interner.create_next_raw_interner();
+}
+
+/// Moves the buckets at the positions in `buckets` of table `table` to the next table, the first
+/// table is 0. Returns true if this finished the move of all values of the table.
+///
+/// # Panics
+///
+/// Panics if the table or the table after it does not exist or if `buckets` is not a range of
+/// buckets of the table.
+pub fn move_buckets<T, S>(interner: &Interner<T, S>, table: usize, buckets: Range<usize>) -> bool
+where
+    T: Copy + Hash,
+    S: BuildHasher,
+{
+    let raw_interner = nth_table(interner, table);
+    assert!(raw_interner.try_get_next_raw_interner().is_some(), "table {table} has no next table");
+    assert!(
+        buckets.end <= raw_interner.buckets(),
+        "buckets {buckets:?} out of range for table {table} with {} buckets",
+        raw_interner.buckets()
+    );
+    raw_interner.transfer_buckets(buckets, interner.hasher())
+}
+
+/// Marks the empty slot `index` of bucket `bucket` in table `table` valid without writing a value
+/// to it, so tests can check that the `checked` feature catches the read of the slot.
+///
+/// # Safety
+///
+/// Any read of the slot, e.g. a lookup or an iteration that finds it, is undefined behavior
+/// without the `checked` feature.
+///
+/// # Panics
+///
+/// Panics if the table does not exist or if the slot is not empty.
+pub unsafe fn publish_unwritten_slot<T, S>(
+    interner: &Interner<T, S>,
+    table: usize,
+    bucket: usize,
+    index: usize,
+) {
+    let raw_interner = nth_table(interner, table);
+    assert!(bucket < raw_interner.buckets(), "bucket {bucket} out of range for table {table}");
+    raw_interner.publish_unwritten_slot(bucket, index);
+}
+
+fn nth_table<T, S>(interner: &Interner<T, S>, table: usize) -> &RawInterner<T> {
+    let mut raw_interner = interner.first_raw_interner();
+    for _ in 0..table {
+        raw_interner = raw_interner
+            .try_get_next_raw_interner()
+            .unwrap_or_else(|| panic!("interner has no table {table}"));
+    }
+    raw_interner
+}
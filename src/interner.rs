@@ -1,17 +1,72 @@
-use crate::raw_interner::{LockResult, RawInterner};
+use crate::bucket::BUCKET_SLOTS;
+#[cfg(doc)]
+use crate::builder::InternerBuilder;
+use crate::builder::Settings;
+use crate::capacity_policy::CapacityPolicy;
+use crate::collision::CollisionReport;
+use crate::error::{AlreadyPresent, InternError, MergeError, ValidationError};
+use crate::frozen::FrozenInterner;
+use crate::histogram::Histogram;
+use crate::new_value_hook::NewValueHook;
+use crate::preset::Preset;
+use crate::raw_interner::{self, HashPair, LockResult, LockedData, RawInterner};
+use crate::read_sampler::ReadSampler;
+use crate::session::Session;
+use crate::stats::{BucketInfo, FragmentationReport, InternerStats, SlowMakeReport};
 use std::borrow::Borrow;
 use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
+use std::convert::Infallible;
 use std::hash::{BuildHasher, Hash};
-use std::sync::atomic::{AtomicPtr, Ordering};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicPtr, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, Weak};
+use std::time::Instant;
 
 /// Default hasher for `HashMap`.
 pub type DefaultHashBuilder = RandomState;
 
+/// The next identity for a hasher that is not cloned from another interner.
+static NEXT_HASHER_ID: AtomicU64 = AtomicU64::new(0);
+
 /// A concurrent interner implemented with quadratic probing and SIMD lookup.
+///
+/// # Moving
+///
+/// The first table is stored in the `Interner` and the newer tables are allocated on the heap,
+/// the pointers between the tables and to the current table never point in to the `Interner`
+/// itself. So the `Interner` can be moved at any time, also after values have been interned and
+/// in to other threads, without being boxed first.
+///
+/// The `Interner` is invariant in `T` as values are interned through a shared reference. An
+/// interner of `&'static str` can not be used as an interner of shorter lived `&str`, that would
+/// let values that do not live long enough in to it:
+///
+/// ```compile_fail
+/// use interner::Interner;
+///
+/// fn shorten<'a>(interner: &'a Interner<&'static str>) -> &'a Interner<&'a str> {
+///     interner
+/// }
+/// ```
 pub struct Interner<T, S = DefaultHashBuilder> {
     hash_builder: S,
-    _raw_interners: Box<RawInterner<T>>,
+    // the first table, newer tables are owned by the previous table
+    raw_interners: RawInterner<T>,
+    // the table where new operations starts, null for `raw_interners` so the interner can be
+    // moved and created in a const context
     current_raw_interner: AtomicPtr<RawInterner<T>>,
+    capacity_policy: Option<Arc<CapacityPolicy>>,
+    // values known ahead of time, checked before the tables
+    preset: Option<Box<Preset<T>>>,
+    // called with each value inserted in to the tables
+    on_new_value: Option<NewValueHook<T>>,
+    // the identity of the hasher, shared by the interners created with `child`. set on the first
+    // use, so the interner can be created in a const context
+    hasher_id: OnceLock<u64>,
+    // the number of values inserted by operations on the interner, the values copied to a newer
+    // table during a resize are not counted again
+    len: AtomicUsize,
 }
 
 impl<T> Interner<T, DefaultHashBuilder> {
@@ -68,9 +123,34 @@ impl<T, S> Interner<T, S> {
     /// let s = DefaultHashBuilder::default();
     /// let mut interner: Interner<i32> = Interner::with_hasher(s);
     /// ```
+    ///
+    /// The function is `const` and does not allocate, so together with a hash builder that can
+    /// be created in a const context it can initialize a `static` interner. `Interner::new` can
+    /// not be `const` as the default hash builder is randomly seeded at runtime.
+    ///
+    /// ```
+    /// use interner::Interner;
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    ///
+    /// static INTERNER: Interner<&'static str, BuildHasherDefault<DefaultHasher>> =
+    ///     Interner::with_hasher(BuildHasherDefault::new());
+    ///
+    /// let a = INTERNER.intern_ref("a", || "a");
+    /// assert!(std::ptr::eq(a, INTERNER.intern_ref("a", || unreachable!())));
+    /// ```
     #[inline]
-    pub fn with_hasher(hash_builder: S) -> Self {
-        Self::with_capacity_and_hasher(0, hash_builder)
+    pub const fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            hash_builder,
+            raw_interners: RawInterner::new(Settings::new()),
+            current_raw_interner: AtomicPtr::new(std::ptr::null_mut()),
+            capacity_policy: None,
+            preset: None,
+            on_new_value: None,
+            hasher_id: OnceLock::new(),
+            len: AtomicUsize::new(0),
+        }
     }
 
     /// Creates an empty `HashMap` with the specified capacity, using `hash_builder`
@@ -95,9 +175,140 @@ impl<T, S> Interner<T, S> {
     /// ```
     #[inline]
     pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
-        let mut raw_interners = Box::new(RawInterner::with_capacity(capacity));
-        let current_raw_interner = AtomicPtr::new(&mut *raw_interners);
-        Self { hash_builder, _raw_interners: raw_interners, current_raw_interner }
+        Self::with_settings(capacity, hash_builder, Settings::default(), None)
+    }
+
+    pub(crate) fn with_settings(
+        capacity: usize,
+        hash_builder: S,
+        settings: Settings,
+        capacity_policy: Option<Arc<CapacityPolicy>>,
+    ) -> Self {
+        Self {
+            hash_builder,
+            raw_interners: RawInterner::with_capacity(capacity, settings),
+            current_raw_interner: AtomicPtr::new(std::ptr::null_mut()),
+            capacity_policy,
+            preset: None,
+            on_new_value: None,
+            hasher_id: OnceLock::new(),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn with_preset(mut self, values: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Hash + Eq,
+        S: BuildHasher,
+    {
+        self.preset = Some(Box::new(Preset::new(values, &self.hash_builder)));
+        self
+    }
+
+    pub(crate) fn with_on_new_value(mut self, hook: impl Fn(T) + Send + Sync + 'static) -> Self {
+        self.on_new_value = Some(NewValueHook::new(hook));
+        self
+    }
+
+    /// Returns the number of values in the preset given to
+    /// [`InternerBuilder::build_with_preset`], 0 if there is no preset.
+    #[inline]
+    pub fn preset_len(&self) -> usize {
+        self.preset.as_ref().map_or(0, |preset| preset.len())
+    }
+
+    /// Returns the number of values interned in the tables, the values of the preset are not
+    /// included, see [`Interner::preset_len`].
+    ///
+    /// The count is kept by the inserts, so a value is counted once even while a resize has
+    /// copied it to the new table and the old bucket is not yet marked as moved. Inserts that
+    /// run concurrently with this call may or may not be counted. The count is incremented before
+    /// the value is published, so a value that a thread has found is always counted by a later
+    /// call on that thread, while the count can briefly include values that are not yet
+    /// visible. The dense ids depend on this, see [`Interner::dense_id`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let interner: Interner<i32> = Interner::with_capacity(1);
+    /// for value in 0..100 {
+    ///     interner.intern(value, |value| value);
+    ///     interner.intern(value, |value| value);
+    /// }
+    /// assert_eq!(100, interner.len());
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if no values are interned in the tables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let interner: Interner<i32> = Interner::new();
+    /// assert!(interner.is_empty());
+    /// interner.intern(1, |value| value);
+    /// assert!(!interner.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of valid slots in all tables, for diagnostics.
+    ///
+    /// Unlike [`Interner::len`] this counts the copies of the values in the older tables, also
+    /// the ones in buckets that are moved to a newer table, so it is larger than `len` while the
+    /// interner holds more than one table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let interner: Interner<i32> = Interner::with_capacity(1);
+    /// for value in 0..100 {
+    ///     interner.intern(value, |value| value);
+    /// }
+    /// assert!(interner.deep_slot_count() >= interner.len());
+    /// ```
+    pub fn deep_slot_count(&self) -> usize {
+        let mut count = 0;
+        let mut raw_interner = Some(&self.raw_interners);
+        while let Some(table) = raw_interner {
+            count += table.valid_slots();
+            raw_interner = table.try_get_next_raw_interner();
+        }
+        count
+    }
+
+    /// Returns the shard in `0..shards` for a value with `hash`, for sharding layers on top of
+    /// several interners.
+    ///
+    /// The shard is picked from hash bits that the interner does not use for small and medium
+    /// sized tables, so the values of a shard are still spread over the whole table of the
+    /// shard's interner. On 32-bit platforms only 16 bits are available and they are used by
+    /// tables with more than 65536 buckets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    /// use std::hash::BuildHasher;
+    ///
+    /// let shards: Vec<Interner<i32>> = (0..4).map(|_| Interner::new()).collect();
+    /// let hash = shards[0].hasher().hash_one(42);
+    /// assert!(Interner::<i32>::shard_hint(hash, shards.len()) < shards.len());
+    /// ```
+    #[inline]
+    pub fn shard_hint(hash: u64, shards: usize) -> usize {
+        raw_interner::shard_hint(hash, shards)
     }
 
     /// Returns a reference to the map's [`BuildHasher`].
@@ -118,156 +329,2370 @@ impl<T, S> Interner<T, S> {
     pub fn hasher(&self) -> &S {
         &self.hash_builder
     }
-}
 
-impl<T, S> Interner<T, S>
-where
-    T: Eq + Hash,
-    S: BuildHasher,
-{
-    /// Interns the value and returns a reference to the interned value.
+    /// Returns a clone of the hasher, the hashes of the clone are the same as the hashes of this
+    /// interner.
     ///
     /// # Examples
     ///
     /// ```
     /// use interner::Interner;
+    /// use std::hash::BuildHasher;
     ///
-    /// let value1 :i32 = 42;
-    /// let value2 :i32 = 300;
-    /// let mut interner: Interner<&i32> = Interner::with_capacity(2);
-    /// let result = interner.intern_ref(&value1,|| {&value1});
-    /// assert_eq!(&value1,result);
-    /// let result = interner.intern_ref(&value2,|| {&value2});
-    /// assert_eq!(&value2,result);
-    /// let result = interner.intern_ref(&value2,|| {&value2});
-    /// assert_eq!(&value2,result);
+    /// let interner: Interner<&str> = Interner::new();
+    /// let hasher = interner.hasher_clone();
+    /// assert_eq!(interner.hasher().hash_one("a"), hasher.hash_one("a"));
     /// ```
-    pub fn intern_ref<Q: ?Sized>(&self, value: &Q, make: impl FnOnce() -> T) -> T
+    #[inline]
+    pub fn hasher_clone(&self) -> S
     where
-        T: Borrow<Q> + Copy,
-        Q: Hash + Eq,
+        S: Clone,
     {
-        let hash = self.hash_builder.hash_one(value);
-        let mut raw_interner = unsafe { &*self.current_raw_interner.load(Ordering::Relaxed) };
-        let mut is_current_interner = true;
-        loop {
-            let lock_result = raw_interner.lock_or_get_slot(hash, value);
-            if let LockResult::Found(result) = lock_result {
-                return result;
-            }
-            if let LockResult::Locked(locked_data) = lock_result {
-                let result = make();
-                if raw_interner.unlock_and_set_value(hash, result, locked_data, &self.hash_builder)
-                    && is_current_interner
-                {
-                    self.current_raw_interner
-                        .store(raw_interner.get_next_moved_raw_interner_ptr(), Ordering::Relaxed);
-                }
-                return result;
-            }
-            if let LockResult::ResizeNeeded = lock_result {
-                if raw_interner.create_and_stor_next_raw_interner(&self.hash_builder)
-                    && is_current_interner
-                {
-                    self.current_raw_interner
-                        .store(raw_interner.get_next_moved_raw_interner_ptr(), Ordering::Relaxed);
-                }
-            }
-            raw_interner = raw_interner.get_next_raw_interner();
-            is_current_interner = false;
-        }
+        self.hash_builder.clone()
     }
 
-    /// Interns the value and returns a reference to the interned value.
+    /// Creates an empty `Interner` with a clone of the hasher of this interner.
+    ///
+    /// The hashes of the child are the same as the hashes of this interner, so a hash calculated
+    /// with one of them can be used with [`Interner::get_from_hash`] of the other and the
+    /// interners can be combined with [`Interner::merge`]. Interners that are not created with
+    /// `child` from each other are never treated as having the same hasher, also when the
+    /// hasher is deterministic.
     ///
     /// # Examples
     ///
     /// ```
     /// use interner::Interner;
+    /// use std::hash::BuildHasher;
     ///
-    /// let value1 :i32 = 42;
-    /// let value2 :i32 = 300;
-    /// let mut interner: Interner<&i32> = Interner::with_capacity(2);
-    /// let result = interner.intern(&value1,|val| {&val});
-    /// assert_eq!(&value1,result);
-    /// let result = interner.intern(&value2,|val| {&val});
-    /// assert_eq!(&value2,result);
-    /// let result = interner.intern(&value2,|val| {&val});
-    /// assert_eq!(&value2,result);
+    /// let interner: Interner<&str> = Interner::new();
+    /// let child = interner.child();
+    /// assert_eq!(interner.hasher().hash_one("a"), child.hasher().hash_one("a"));
     /// ```
-    pub fn intern<Q>(&self, value: Q, make: impl FnOnce(Q) -> T) -> T
+    pub fn child(&self) -> Self
     where
-        T: Borrow<Q> + Copy,
-        Q: Hash + Eq,
+        S: Clone,
     {
-        let hash = self.hash_builder.hash_one(&value);
-        let mut raw_interner = unsafe { &*self.current_raw_interner.load(Ordering::Relaxed) };
-        let mut is_current_interner = true;
-        loop {
-            let lock_result = raw_interner.lock_or_get_slot(hash, &value);
-            if let LockResult::Found(result) = lock_result {
-                return result;
-            }
-            if let LockResult::Locked(locked_data) = lock_result {
-                let result = make(value);
-                if raw_interner.unlock_and_set_value(hash, result, locked_data, &self.hash_builder)
-                    && is_current_interner
-                {
-                    self.current_raw_interner
-                        .store(raw_interner.get_next_moved_raw_interner_ptr(), Ordering::Relaxed);
-                }
-                return result;
+        let child = Self::with_hasher(self.hasher_clone());
+        child.hasher_id.get_or_init(|| self.hasher_id());
+        child
+    }
+
+    // returns the identity of the hasher, equal for interners that share the hasher
+    fn hasher_id(&self) -> u64 {
+        *self.hasher_id.get_or_init(|| NEXT_HASHER_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Creates a [`WeakInterner`] handle to an interner in an `Arc`.
+    ///
+    /// The handle does not keep the tables alive, they are freed when the last `Arc` is
+    /// dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    /// use std::sync::Arc;
+    ///
+    /// let interner: Arc<Interner<i32>> = Arc::new(Interner::new());
+    /// let weak = Interner::downgrade(&interner);
+    /// assert!(weak.upgrade().is_some());
+    /// drop(interner);
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+    #[inline]
+    pub fn downgrade(this: &Arc<Self>) -> WeakInterner<T, S> {
+        WeakInterner { interner: Arc::downgrade(this) }
+    }
+
+    /// Returns a new [`Session`] for the interner.
+    #[inline]
+    pub fn session(&self) -> Session<'_, T, S> {
+        Session::new(self)
+    }
+
+    /// Calls `f` with a new [`Session`] for the interner, clone the session to use it in
+    /// parallel tasks.
+    #[inline]
+    pub fn in_scope<R>(&self, f: impl FnOnce(&Session<'_, T, S>) -> R) -> R {
+        f(&self.session())
+    }
+
+    /// Returns the policy that the size of the interner is recorded in when it is dropped, see
+    /// [`InternerBuilder::capacity_policy`].
+    #[inline]
+    pub fn capacity_policy(&self) -> Option<&Arc<CapacityPolicy>> {
+        self.capacity_policy.as_ref()
+    }
+
+    /// Returns the collision statistics of the inserts if the interner was built with
+    /// [`InternerBuilder::collision_detection`].
+    #[inline]
+    pub fn collision_report(&self) -> Option<CollisionReport> {
+        self.raw_interners.settings().collision_detector.as_ref().map(|detector| detector.report())
+    }
+
+    /// Returns the calls of `make` that was slower than the threshold set with
+    /// [`InternerBuilder::slow_make_threshold`], `None` if no threshold is set.
+    #[inline]
+    pub fn slow_make_report(&self) -> Option<SlowMakeReport> {
+        self.raw_interners.settings().make_timer.as_ref().map(|make_timer| make_timer.report())
+    }
+
+    /// Returns the average number of tables after the current table that a lookup checks before
+    /// it finds its value, estimated from a sample of the recent lookups. `None` if the interner
+    /// was not built with [`InternerBuilder::adaptive_get_start`].
+    ///
+    /// The estimate is 0 when there is no resize in progress or all values are found in the
+    /// oldest table, and near 1 when most values are found in the table after it.
+    #[inline]
+    pub fn estimated_chain_read_penalty(&self) -> Option<f64> {
+        self.raw_interners
+            .settings()
+            .read_sampler
+            .as_ref()
+            .map(|read_sampler| read_sampler.estimate())
+    }
+
+    /// Returns the number of tables that a lookup can need to check, the newest table and the
+    /// older tables with values that are not moved to the next table.
+    ///
+    /// The number is 1 when no resize is in progress, with
+    /// [`InternerBuilder::strict_two_table`] it is at most 2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let values: Vec<i32> = (0..1000).collect();
+    /// let interner: Interner<&i32> = Interner::new();
+    /// for value in values.iter() {
+    ///     interner.intern_ref(value, || value);
+    /// }
+    /// assert!(interner.max_chain_depth() >= 1);
+    /// ```
+    pub fn max_chain_depth(&self) -> usize {
+        let mut raw_interner = self.current_raw_interner();
+        let mut depth = 1;
+        while let Some(next) = raw_interner.try_get_next_raw_interner() {
+            if depth > 1 || !raw_interner.is_transfer_done() {
+                depth += 1;
             }
-            if let LockResult::ResizeNeeded = lock_result {
-                if raw_interner.create_and_stor_next_raw_interner(&self.hash_builder)
-                    && is_current_interner
-                {
-                    self.current_raw_interner
-                        .store(raw_interner.get_next_moved_raw_interner_ptr(), Ordering::Relaxed);
-                }
+            raw_interner = next;
+        }
+        depth
+    }
+
+    /// Stops the moving of values to a new table after a resize and waits for the moves in
+    /// progress to stop.
+    ///
+    /// Cancels the [`CancellationToken`](crate::CancellationToken) of the interner, that can be
+    /// shared with other interners. Intern operations in progress and later intern operations
+    /// works as normal, but a started resize is not finished so operations can need to check
+    /// more than one table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let value1: i32 = 42;
+    /// let interner: Interner<&i32> = Interner::new();
+    /// interner.shutdown();
+    /// assert_eq!(&value1, interner.intern_ref(&value1, || &value1));
+    /// ```
+    pub fn shutdown(&self) {
+        self.raw_interners.settings().cancellation_token().cancel_and_wait();
+    }
+
+    /// Returns the bytes of table memory used per interned value, or `None` if the interner is
+    /// empty.
+    ///
+    /// All tables kept by the interner are counted, including the padding of the buckets and
+    /// the old tables that are kept after a resize, but not the memory the values point to.
+    /// Every bucket of every table is checked so this is slow for large interners.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let values: Vec<u64> = (0..100).collect();
+    /// let interner: Interner<&u64> = Interner::new();
+    /// assert_eq!(None, interner.approx_memory_per_entry());
+    /// for value in values.iter() {
+    ///     interner.intern_ref(value, || value);
+    /// }
+    /// assert!(interner.approx_memory_per_entry().unwrap() > std::mem::size_of::<&u64>() as f64);
+    /// ```
+    pub fn approx_memory_per_entry(&self) -> Option<f64> {
+        let report = self.fragmentation_report();
+        let entries: usize = report.tables.iter().map(|table| table.entries).sum();
+        let bytes: usize = report.tables.iter().map(|table| table.bytes).sum();
+        (entries != 0).then(|| bytes as f64 / entries as f64)
+    }
+
+    /// Returns a report of all tables that are kept by the interner.
+    ///
+    /// Old tables are kept after a resize until [`Interner::reclaim_moved_tables`], as references
+    /// to their values can have been handed out, the report shows the size of each table, how many
+    /// buckets that still need to be moved to the next table and the memory held by the tables that
+    /// are older than the current table. Every bucket of every table is checked so this is slow for
+    /// large interners.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let interner: Interner<i32> = Interner::with_capacity(10);
+    /// let report = interner.fragmentation_report();
+    /// assert_eq!(1, report.tables.len());
+    /// assert!(report.tables[0].is_current);
+    /// assert_eq!(0, report.non_current_bytes);
+    /// ```
+    pub fn fragmentation_report(&self) -> FragmentationReport {
+        let current_raw_interner = self.current_raw_interner();
+        let mut report = FragmentationReport::default();
+        let mut raw_interner = Some(&self.raw_interners);
+        let mut is_before_current = true;
+        while let Some(table) = raw_interner {
+            let is_current = std::ptr::eq(table, current_raw_interner);
+            is_before_current &= !is_current;
+            let table_report = table.table_report(is_current);
+            if is_before_current {
+                report.non_current_bytes += table_report.bytes;
             }
-            raw_interner = raw_interner.get_next_raw_interner();
-            is_current_interner = false;
+            report.tables.push(table_report);
+            raw_interner = table.try_get_next_raw_interner();
         }
+        report
     }
 
-    /// get already interned value if available.
+    /// Returns the count of the moves to the next table that are not done of each table, from the
+    /// oldest to the newest table that has a next table.
+    ///
+    /// The count of a table starts at minus the number of buckets. Each moved bucket adds one and
+    /// the number of its slots that were locked when it was moved, and each of those slots
+    /// subtracts one when its value is set and moved. The count is zero when all values of the
+    /// table are moved, a count that never reaches zero means that a slot stays locked, see
+    /// [`InternerBuilder::stuck_transfer_alert`]. The alert is checked by this call.
     ///
     /// # Examples
     ///
     /// ```
     /// use interner::Interner;
-    /// use std::hash::{BuildHasher, Hash, Hasher };
     ///
-    /// let value1 :i32 = 42;
-    /// let mut interner: Interner<&i32> = Interner::with_capacity(2);
-    /// let mut state = interner.hasher().build_hasher();
-    /// value1.hash(&mut state);
-    /// let hash = state.finish();
-    /// assert!(interner.get_from_hash(hash,|val| {*val == &value1}).is_none());
-    /// let result = interner.intern_ref(&value1,|| {&value1});
-    /// assert_eq!(&value1,result);
-    /// let result = interner.get_from_hash(hash, |val| {*val == &value1}).expect("was interned above");
-    /// assert_eq!(&value1,*result);
+    /// let values: Vec<i32> = (0..100).collect();
+    /// let interner: Interner<&i32> = Interner::with_capacity(1);
+    /// for value in values.iter() {
+    ///     interner.intern_ref(value, || value);
+    /// }
+    /// assert!(interner.to_be_moved().iter().all(|&to_be_moved| to_be_moved == 0));
     /// ```
-    pub fn get_from_hash<F>(&self, hash: u64, mut is_match: F) -> Option<&T>
+    pub fn to_be_moved(&self) -> Vec<isize> {
+        let mut to_be_moved = Vec::new();
+        let mut raw_interner = &self.raw_interners;
+        while let Some(next_raw_interner) = raw_interner.try_get_next_raw_interner() {
+            raw_interner.check_stuck_transfer();
+            to_be_moved.push(raw_interner.to_be_moved());
+            raw_interner = next_raw_interner;
+        }
+        to_be_moved
+    }
+
+    /// Returns the number of values the newest table is sized for, an interner created with
+    /// this capacity starts with a table of the same size. 0 if no table is allocated.
+    ///
+    /// The newest table is where the inserts end up, a table is resized when an insert does not
+    /// find a free slot near the home bucket of the value, so the number of values that fits
+    /// before the next resize depends on the hashes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// assert_eq!(0, Interner::<i32>::new().capacity());
+    /// let interner: Interner<i32> = Interner::with_capacity(100);
+    /// assert!(interner.capacity() >= 100);
+    /// assert_eq!(interner.capacity(), Interner::<i32>::with_capacity(interner.capacity()).capacity());
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.newest_raw_interner().capacity()
+    }
+
+    /// Returns the size and load of the tables, see [`InternerStats`].
+    ///
+    /// Every bucket of the newest table is checked so this is slow for large interners.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let values: Vec<u64> = (0..1000).collect();
+    /// let interner: Interner<&u64> = Interner::with_capacity(10);
+    /// for value in values.iter() {
+    ///     interner.intern_ref(value, || value);
+    /// }
+    /// let stats = interner.stats();
+    /// assert_eq!(1000, stats.len);
+    /// assert!(stats.tables > 1);
+    /// assert!(stats.occupancy > 0.0 && stats.occupancy <= 1.0);
+    /// ```
+    pub fn stats(&self) -> InternerStats {
+        let mut tables = 1;
+        let mut pending_moves = 0;
+        let mut raw_interner = &self.raw_interners;
+        while let Some(next_raw_interner) = raw_interner.try_get_next_raw_interner() {
+            tables += 1;
+            pending_moves += raw_interner.to_be_moved().unsigned_abs();
+            raw_interner = next_raw_interner;
+        }
+        let slots = raw_interner.buckets() * BUCKET_SLOTS;
+        let occupancy =
+            if slots == 0 { 0.0 } else { raw_interner.valid_slots() as f64 / slots as f64 };
+        InternerStats {
+            len: self.len(),
+            capacity: raw_interner.capacity(),
+            occupancy,
+            tables,
+            pending_moves,
+        }
+    }
+
+    /// Checks the invariants of the buckets of all tables and returns the first one that is
+    /// broken.
+    ///
+    /// Every slot must be in a state that the slot transitions reach, e.g. no park bit without
+    /// the lock bit outside of a poisoned slot, and no two valid slots of a bucket may hold equal
+    /// values. The slots of a bucket are checked together with the same SIMD masks as the
+    /// lookups, so even tables of several GB are checked in seconds, which makes it cheap enough
+    /// to run after every iteration of stress and fuzz tests. The checks do not assume that the
+    /// interner is idle, but are only exhaustive when no value is interned concurrently.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`ValidationError`] of the first broken invariant found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let values: Vec<u64> = (0..1000).collect();
+    /// let interner: Interner<&u64> = Interner::with_capacity(10);
+    /// for value in values.iter() {
+    ///     interner.intern_ref(value, || value);
+    /// }
+    /// assert_eq!(Ok(()), interner.debug_validate());
+    /// ```
+    pub fn debug_validate(&self) -> Result<(), ValidationError>
     where
-        F: FnMut(&T) -> bool,
+        T: Eq,
     {
-        let mut raw_interner = unsafe { &*self.current_raw_interner.load(Ordering::Relaxed) };
-        loop {
-            match raw_interner.get(hash, &mut is_match) {
-                Some(result) => {
-                    return result;
-                }
+        let mut raw_interner = Some(&self.raw_interners);
+        let mut table = 0;
+        while let Some(current) = raw_interner {
+            current.validate(table)?;
+            raw_interner = current.try_get_next_raw_interner();
+            table += 1;
+        }
+        Ok(())
+    }
+
+    // returns the newest table of the chain of tables
+    fn newest_raw_interner(&self) -> &RawInterner<T> {
+        let mut raw_interner = self.current_raw_interner();
+        while let Some(next_raw_interner) = raw_interner.try_get_next_raw_interner() {
+            raw_interner = next_raw_interner;
+        }
+        raw_interner
+    }
+
+    // calls `f` once with every interned value, no values are moved while `self` is borrowed
+    // mutably so the values of moved buckets are only visited in the newer table
+    pub(crate) fn for_each_value_mut(&mut self, mut f: impl FnMut(T))
+    where
+        T: Copy,
+    {
+        if let Some(preset) = &self.preset {
+            preset.iter().for_each(|(_, value)| f(*value));
+        }
+        let mut values = Vec::new();
+        let mut raw_interner = Some(&self.raw_interners);
+        while let Some(current) = raw_interner {
+            current.collect_chunk(&mut 0, &mut 0, usize::MAX, &mut values);
+            values.drain(..).for_each(&mut f);
+            raw_interner = current.try_get_next_raw_interner();
+        }
+    }
+
+    // returns true if the values get dense ids, see `InternerBuilder::dense_ids`
+    #[inline]
+    pub(crate) fn has_dense_ids(&self) -> bool {
+        self.raw_interners.settings().dense_ids
+    }
+
+    /// Returns the first table of the chain of tables.
+    #[cfg(feature = "unstable-internals")]
+    #[inline]
+    pub(crate) fn first_raw_interner(&self) -> &RawInterner<T> {
+        &self.raw_interners
+    }
+
+    #[inline]
+    fn current_raw_interner(&self) -> &RawInterner<T> {
+        let current_raw_interner = self.current_raw_interner.load(Ordering::Acquire);
+        if current_raw_interner.is_null() {
+            &self.raw_interners
+        } else {
+            // SAFTY: points to a table owned by the chain of tables starting at `raw_interners`
+            unsafe { &*current_raw_interner }
+        }
+    }
+}
+
+impl<T, S> Interner<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Interns the value and returns a reference to the interned value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let value1 :i32 = 42;
+    /// let value2 :i32 = 300;
+    /// let mut interner: Interner<&i32> = Interner::with_capacity(2);
+    /// let result = interner.intern_ref(&value1,|| {&value1});
+    /// assert_eq!(&value1,result);
+    /// let result = interner.intern_ref(&value2,|| {&value2});
+    /// assert_eq!(&value2,result);
+    /// let result = interner.intern_ref(&value2,|| {&value2});
+    /// assert_eq!(&value2,result);
+    /// ```
+    pub fn intern_ref<Q>(&self, value: &Q, make: impl FnOnce() -> T) -> T
+    where
+        T: Borrow<Q> + Copy,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.try_intern_ref(value, make).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Same as [`Interner::intern_ref`] but debug asserts that the value returned by `make`
+    /// does not borrow memory of `value`.
+    ///
+    /// The key is often a temporary of the caller, e.g. a `&str` borrowed from a buffer that is
+    /// reused, and `make` must return a value that points to storage that lives as long as the
+    /// interner, like an arena or a leaked allocation. Returning the key itself compiles when the
+    /// key happens to have the right lifetime, and the interned value then changes when the
+    /// buffer is reused. The check compares the memory range of `value` with the range that the
+    /// new value borrows as, so it catches any overlap for slice-like keys. It is only done in
+    /// debug builds and only when `make` is called.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if the value returned by `make` borrows memory of `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let interner: Interner<&'static str> = Interner::new();
+    /// let buffer = String::from("hello");
+    /// let hello = interner.intern_ref_checked(buffer.as_str(), || buffer.clone().leak());
+    /// assert_eq!("hello", hello);
+    /// ```
+    ///
+    /// Returning the key is caught in debug builds:
+    ///
+    /// ```no_run
+    /// use interner::Interner;
+    ///
+    /// static BUFFER: &str = "hello world";
+    /// let interner: Interner<&'static str> = Interner::new();
+    /// let key = &BUFFER[..5];
+    /// // panics in debug builds
+    /// interner.intern_ref_checked(key, || key);
+    /// ```
+    pub fn intern_ref_checked<Q>(&self, value: &Q, make: impl FnOnce() -> T) -> T
+    where
+        T: Borrow<Q> + Copy,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.intern_ref(value, || {
+            let result = make();
+            debug_assert!(
+                !overlaps(value, result.borrow()),
+                "the value returned by `make` borrows the key given to `intern_ref_checked`"
+            );
+            result
+        })
+    }
+
+    /// Interns the value and returns a reference to the interned value or an error if the
+    /// interner is full.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::MemoryLimit`] if the value is not interned and a resize is needed
+    /// that would pass the limit set with
+    /// [`InternerBuilder::max_memory_bytes`](crate::InternerBuilder::max_memory_bytes).
+    /// Returns [`InternError::ResizeDenied`] if a resize is needed and resizes are denied with
+    /// [`InternerBuilder::deny_resize`](crate::InternerBuilder::deny_resize).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{InternError, Interner, InternerBuilder};
+    ///
+    /// let values: Vec<i32> = (0..100).collect();
+    /// let interner: Interner<&i32> = InternerBuilder::new().max_memory_bytes(1024).build();
+    /// let result: Result<Vec<&i32>, InternError> =
+    ///     values.iter().map(|value| interner.try_intern_ref(value, || value)).collect();
+    /// assert!(matches!(result, Err(InternError::MemoryLimit { .. })));
+    /// ```
+    pub fn try_intern_ref<Q>(&self, value: &Q, make: impl FnOnce() -> T) -> Result<T, InternError>
+    where
+        T: Borrow<Q> + Copy,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.hash_builder.hash_one(value);
+        let ptr_eq = self.raw_interners.settings().ptr_eq_fast_path;
+        self.intern_hashed(
+            hash,
+            value,
+            |value, result| borrowed_eq(ptr_eq, *value, result.borrow()),
+            |_| make(),
+        )
+    }
+
+    /// Same as [`Interner::intern_ref`] but `make` gets a [`SlotToken`] for the slot that the
+    /// value is inserted in to.
+    ///
+    /// `make` is only called when the slot for the value is locked by this call, so the value
+    /// is always inserted when `make` returns and `make` is called at most once per distinct
+    /// value over all threads. Other threads that intern an equal value wait for the value
+    /// instead of calling their `make`, so the value can be allocated in `make` without an
+    /// allocation being wasted on a race. That holds for all intern methods, the token makes it
+    /// explicit in the signature and gives the hash of the value to key the allocation by.
+    ///
+    /// If `make` panics the slot is poisoned as for an error of [`Interner::try_make_intern_ref`]
+    /// and the panic continues, the threads waiting for the value retry instead of waiting
+    /// forever.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interner, SlotToken};
+    /// use std::hash::BuildHasher;
+    ///
+    /// let interner: Interner<&'static str> = Interner::new();
+    /// let make = |token: &SlotToken| -> &'static str {
+    ///     assert_eq!(interner.hasher().hash_one("a"), token.hash());
+    ///     String::from("a").leak()
+    /// };
+    /// let a = interner.intern_ref_with_token("a", make);
+    /// assert!(std::ptr::eq(a, interner.intern_ref_with_token("a", |_| unreachable!())));
+    /// ```
+    pub fn intern_ref_with_token<Q>(&self, value: &Q, make: impl FnOnce(&SlotToken) -> T) -> T
+    where
+        T: Borrow<Q> + Copy,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.try_intern_ref_with_token(value, make).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Same as [`Interner::try_intern_ref`] but `make` gets a [`SlotToken`], see
+    /// [`Interner::intern_ref_with_token`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Interner::try_intern_ref`], `make` is not called when an error is
+    /// returned.
+    pub fn try_intern_ref_with_token<Q>(
+        &self,
+        value: &Q,
+        make: impl FnOnce(&SlotToken) -> T,
+    ) -> Result<T, InternError>
+    where
+        T: Borrow<Q> + Copy,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.hash_builder.hash_one(value);
+        let ptr_eq = self.raw_interners.settings().ptr_eq_fast_path;
+        self.intern_hashed(
+            hash,
+            value,
+            |value, result| borrowed_eq(ptr_eq, *value, result.borrow()),
+            |_| make(&SlotToken { hash }),
+        )
+    }
+
+    /// Same as [`Interner::intern_ref`] but also returns if the value was inserted by this call.
+    ///
+    /// When several threads intern equal values at the same time exactly one of them gets
+    /// `was_new` set, the one that called `make`, so side effects like counting the distinct
+    /// values or logging the first occurrence can be attached to it. Values found in the preset
+    /// are never new.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interned, Interner};
+    ///
+    /// let value: i32 = 42;
+    /// let interner: Interner<&i32> = Interner::new();
+    /// let interned = interner.intern_ref_entry(&value, || &value);
+    /// assert_eq!(Interned { value: &42, was_new: true }, interned);
+    /// let interned = interner.intern_ref_entry(&42, || unreachable!());
+    /// assert_eq!(Interned { value: &42, was_new: false }, interned);
+    /// ```
+    pub fn intern_ref_entry<Q>(&self, value: &Q, make: impl FnOnce() -> T) -> Interned<T>
+    where
+        T: Borrow<Q> + Copy,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.try_intern_ref_entry(value, make).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Same as [`Interner::try_intern_ref`] but also returns if the value was inserted by this
+    /// call, see [`Interner::intern_ref_entry`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Interner::try_intern_ref`].
+    pub fn try_intern_ref_entry<Q>(
+        &self,
+        value: &Q,
+        make: impl FnOnce() -> T,
+    ) -> Result<Interned<T>, InternError>
+    where
+        T: Borrow<Q> + Copy,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.hash_builder.hash_one(value);
+        let ptr_eq = self.raw_interners.settings().ptr_eq_fast_path;
+        let (value, was_new) = self.intern_hashed_is_new(
+            hash,
+            value,
+            |value, result| borrowed_eq(ptr_eq, *value, result.borrow()),
+            |_| make(),
+            None,
+        )?;
+        Ok(Interned { value, was_new })
+    }
+
+    /// Same as [`Interner::intern_ref`] but `make` can fail, the error of `make` is returned and
+    /// the value is not interned.
+    ///
+    /// The slot that was locked for the value is poisoned, it is never used again, and the
+    /// threads waiting for the value retry their inserts, so another call can succeed to make
+    /// the value. A poisoned slot stays in the table until the table is resized, many failing
+    /// calls of `make` fill the table and makes it resize earlier.
+    ///
+    /// # Panics
+    ///
+    /// Panics on the errors of [`Interner::try_intern_ref`], as [`Interner::intern_ref`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let interner: Interner<&'static str> = Interner::new();
+    /// let parse = |value: &str| -> Result<&'static str, String> {
+    ///     match value.parse::<u32>() {
+    ///         Ok(_) => Ok(String::from(value).leak()),
+    ///         Err(error) => Err(format!("{value}: {error}")),
+    ///     }
+    /// };
+    /// assert!(interner.try_make_intern_ref("x", || parse("x")).is_err());
+    /// assert_eq!(None, interner.get("x"));
+    /// assert_eq!(Ok("42"), interner.try_make_intern_ref("42", || parse("42")));
+    /// assert_eq!(Ok::<_, String>("42"), interner.try_make_intern_ref("42", || unreachable!()));
+    /// ```
+    pub fn try_make_intern_ref<Q, E>(
+        &self,
+        value: &Q,
+        make: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E>
+    where
+        T: Borrow<Q> + Copy,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.hash_builder.hash_one(value);
+        let ptr_eq = self.raw_interners.settings().ptr_eq_fast_path;
+        self.try_make_hashed_is_new(
+            hash,
+            value,
+            |value, result| borrowed_eq(ptr_eq, *value, result.borrow()),
+            |_| make(),
+            None,
+        )
+        .unwrap_or_else(|error| panic!("{error}"))
+        .map(|(result, _)| result)
+    }
+
+    /// Interns the value and returns a reference to the interned value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let value1 :i32 = 42;
+    /// let value2 :i32 = 300;
+    /// let mut interner: Interner<&i32> = Interner::with_capacity(2);
+    /// let result = interner.intern(&value1,|val| {&val});
+    /// assert_eq!(&value1,result);
+    /// let result = interner.intern(&value2,|val| {&val});
+    /// assert_eq!(&value2,result);
+    /// let result = interner.intern(&value2,|val| {&val});
+    /// assert_eq!(&value2,result);
+    /// ```
+    pub fn intern<Q>(&self, value: Q, make: impl FnOnce(Q) -> T) -> T
+    where
+        T: Borrow<Q> + Copy,
+        Q: Hash + Eq,
+    {
+        self.try_intern(value, make).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Interns the value and returns a reference to the interned value or an error if the
+    /// interner is full.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::MemoryLimit`] if the value is not interned and a resize is needed
+    /// that would pass the limit set with
+    /// [`InternerBuilder::max_memory_bytes`](crate::InternerBuilder::max_memory_bytes).
+    /// Returns [`InternError::ResizeDenied`] if a resize is needed and resizes are denied with
+    /// [`InternerBuilder::deny_resize`](crate::InternerBuilder::deny_resize).
+    pub fn try_intern<Q>(&self, value: Q, make: impl FnOnce(Q) -> T) -> Result<T, InternError>
+    where
+        T: Borrow<Q> + Copy,
+        Q: Hash + Eq,
+    {
+        let hash = self.hash_builder.hash_one(&value);
+        self.intern_hashed(hash, value, |value, result| value.eq(result.borrow()), make)
+    }
+
+    /// Interns an owned value that is looked up by its borrowed form `Q`, e.g. a `String` in an
+    /// `Interner<&str>`, and returns the interned value.
+    ///
+    /// The owned value is only moved in to `make` if it is not already interned, so `make` can
+    /// reuse its allocation, e.g. with [`String::leak`], and an already interned value costs no
+    /// allocation. The owned value is dropped if it was already interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let interner: Interner<&str> = Interner::new();
+    /// let a = interner.intern_owned::<_, str>(String::from("a"), |owned| owned.leak());
+    /// assert!(std::ptr::eq(a, interner.intern_owned::<_, str>(String::from("a"), |_| unreachable!())));
+    /// assert!(std::ptr::eq(a, interner.intern_ref("a", || unreachable!())));
+    /// ```
+    pub fn intern_owned<O, Q>(&self, owned: O, make: impl FnOnce(O) -> T) -> T
+    where
+        T: Borrow<Q> + Copy,
+        O: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.try_intern_owned(owned, make).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Interns an owned value that is looked up by its borrowed form `Q` or returns an error if
+    /// the interner is full, see [`Interner::intern_owned`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::MemoryLimit`] if the value is not interned and a resize is needed
+    /// that would pass the limit set with
+    /// [`InternerBuilder::max_memory_bytes`](crate::InternerBuilder::max_memory_bytes).
+    /// Returns [`InternError::ResizeDenied`] if a resize is needed and resizes are denied with
+    /// [`InternerBuilder::deny_resize`](crate::InternerBuilder::deny_resize).
+    pub fn try_intern_owned<O, Q>(
+        &self,
+        owned: O,
+        make: impl FnOnce(O) -> T,
+    ) -> Result<T, InternError>
+    where
+        T: Borrow<Q> + Copy,
+        O: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.hash_builder.hash_one(owned.borrow());
+        let ptr_eq = self.raw_interners.settings().ptr_eq_fast_path;
+        self.intern_hashed(
+            hash,
+            owned,
+            |owned, result| borrowed_eq(ptr_eq, owned.borrow(), result.borrow()),
+            make,
+        )
+    }
+
+    /// Interns the value like [`Interner::try_intern_ref`] but gives up at `deadline`, for
+    /// callers that can not block for an unbounded time on a slow `make` of another thread.
+    ///
+    /// The time spent waiting on slots that other threads are inserting values in to and walking
+    /// the chain of tables during a resize is bounded by the deadline. The time of `make` and of
+    /// the waits for a resize with
+    /// [`InternerBuilder::strict_two_table`](crate::InternerBuilder::strict_two_table) are not.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::DeadlineExceeded`] if the deadline passed before the value was found
+    /// or a slot was locked for it, `make` is then not called. Returns the other errors of
+    /// [`Interner::try_intern_ref`] as it does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{InternError, Interner};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let value = 42;
+    /// let interner: Interner<&i32> = Interner::new();
+    /// let deadline = Instant::now() + Duration::from_millis(10);
+    /// assert_eq!(Ok(&value), interner.intern_ref_with_deadline(&value, || &value, deadline));
+    /// ```
+    pub fn intern_ref_with_deadline<Q>(
+        &self,
+        value: &Q,
+        make: impl FnOnce() -> T,
+        deadline: Instant,
+    ) -> Result<T, InternError>
+    where
+        T: Borrow<Q> + Copy,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.hash_builder.hash_one(value);
+        let ptr_eq = self.raw_interners.settings().ptr_eq_fast_path;
+        self.intern_hashed_is_new(
+            hash,
+            value,
+            |value, result| borrowed_eq(ptr_eq, *value, result.borrow()),
+            |_| make(),
+            Some(deadline),
+        )
+        .map(|(result, _)| result)
+    }
+
+    /// Interns the value like [`Interner::intern_ref_with_deadline`] but makes the value without
+    /// interning it if the deadline passes.
+    ///
+    /// Returns `Ok` with the interned value, or `Err` with the value returned by `make` that is
+    /// not interned, so it is not equal by address to the value that the other threads gets.
+    ///
+    /// # Panics
+    ///
+    /// Panics on the other errors of [`Interner::try_intern_ref`], as [`Interner::intern_ref`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let value = 42;
+    /// let interner: Interner<&i32> = Interner::new();
+    /// let deadline = Instant::now() + Duration::from_millis(10);
+    /// let result = interner.intern_ref_with_deadline_or_make(&value, || &value, deadline);
+    /// assert_eq!(Ok(&value), result);
+    /// ```
+    pub fn intern_ref_with_deadline_or_make<Q>(
+        &self,
+        value: &Q,
+        make: impl FnOnce() -> T,
+        deadline: Instant,
+    ) -> Result<T, T>
+    where
+        T: Borrow<Q> + Copy,
+        Q: ?Sized + Hash + Eq,
+    {
+        let mut make = Some(make);
+        let make_once = || make.take().expect("make is only called once")();
+        match self.intern_ref_with_deadline(value, make_once, deadline) {
+            Ok(result) => Ok(result),
+            Err(InternError::DeadlineExceeded) => {
+                Err(make.take().expect("make is not called when the deadline passes")())
+            }
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Interns the value using `eq` instead of `Eq` to find already interned values and
+    /// returns a reference to the interned value.
+    ///
+    /// `hash` must be the hash that the interner's hasher produces for the value returned by
+    /// `make` and for all interned values that `eq` considers equal to `value`, as interned
+    /// values are rehashed with the interner's hasher when the interner is resized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    /// use std::hash::BuildHasher;
+    ///
+    /// let value1 :i32 = 42;
+    /// let mut interner: Interner<&i32> = Interner::with_capacity(2);
+    /// let hash = interner.hasher().hash_one(&value1);
+    /// let result = interner.intern_with(hash, &42, |val, value| {**val == *value}, || {&value1});
+    /// assert_eq!(&value1,result);
+    /// let result = interner.intern_with(hash, &42, |val, value| {**val == *value}, || {unimplemented!()});
+    /// assert_eq!(&value1,result);
+    /// ```
+    pub fn intern_with<Q: ?Sized>(
+        &self,
+        hash: u64,
+        value: &Q,
+        eq: impl Fn(&T, &Q) -> bool,
+        make: impl FnOnce() -> T,
+    ) -> T
+    where
+        T: Copy,
+    {
+        self.intern_hashed(hash, value, |value, result| eq(result, value), |_| make())
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Inserts a value that must not already be interned.
+    ///
+    /// Returns the inserted value, or [`AlreadyPresent`] with the interned value if an equal
+    /// value was already interned. This is meant for values that are expected to be unique, like
+    /// newly generated symbols, to catch id collisions early instead of silently deduplicating.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the limit set with
+    /// [`InternerBuilder::max_memory_bytes`](crate::InternerBuilder::max_memory_bytes) is
+    /// reached, see [`Interner::try_intern_ref`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{AlreadyPresent, Interner};
+    ///
+    /// let interner: Interner<u64> = Interner::new();
+    /// assert_eq!(Ok(1), interner.insert_new(1));
+    /// assert_eq!(Err(AlreadyPresent(1)), interner.insert_new(1));
+    /// ```
+    pub fn insert_new(&self, value: T) -> Result<T, AlreadyPresent<T>>
+    where
+        T: Copy,
+    {
+        let hash = self.hash_builder.hash_one(value);
+        match self.intern_hashed_is_new(
+            hash,
+            value,
+            |value, result| value.eq(result),
+            |value| value,
+            None,
+        ) {
+            Ok((result, true)) => Ok(result),
+            Ok((result, false)) => Err(AlreadyPresent(result)),
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Interns all values of `other` in to this interner and returns the number of values that
+    /// were not already interned.
+    ///
+    /// The interners must share the same hasher, see [`Interner::child`], so hashes calculated
+    /// before the merge stays valid for both interners. The values of the preset of `other` are
+    /// merged with the hashes stored in the preset, other values are hashed again as the tables
+    /// do not store the hashes. Values that are interned in to `other` during the merge may not
+    /// be merged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MergeError::HasherMismatch`] if `other` does not share the hasher of this
+    /// interner and [`MergeError::Intern`] if a value could not be interned, the values before
+    /// it are still merged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interner, MergeError};
+    ///
+    /// let values = [1, 2, 3];
+    /// let interner: Interner<&i32> = Interner::new();
+    /// interner.intern_ref(&values[0], || &values[0]);
+    /// let child = interner.child();
+    /// child.intern_ref(&values[0], || &values[0]);
+    /// child.intern_ref(&values[1], || &values[1]);
+    /// assert_eq!(Ok(1), interner.merge(&child));
+    /// assert_eq!(&values[1], interner.intern_ref(&values[1], || unreachable!()));
+    ///
+    /// let other: Interner<&i32> = Interner::new();
+    /// assert_eq!(Err(MergeError::HasherMismatch), interner.merge(&other));
+    /// ```
+    pub fn merge(&self, other: &Interner<T, S>) -> Result<usize, MergeError>
+    where
+        T: Copy,
+    {
+        if self.hasher_id() != other.hasher_id() {
+            return Err(MergeError::HasherMismatch);
+        }
+        let mut merged = 0;
+        let mut merge_value = |hash, value: T| -> Result<(), InternError> {
+            let (_, is_new) = self.intern_hashed_is_new(
+                hash,
+                value,
+                |value, result| value.eq(result),
+                |value| value,
+                None,
+            )?;
+            merged += usize::from(is_new);
+            Ok(())
+        };
+        if let Some(preset) = &other.preset {
+            for (hash, value) in preset.iter() {
+                merge_value(hash, *value)?;
+            }
+        }
+        let mut cursor = Some(Cursor::default());
+        while let Some(current) = cursor {
+            let (chunk, next) = other.iter_chunk(current, COPY_CHUNK_SIZE);
+            for value in chunk {
+                merge_value(self.hash_builder.hash_one(value), value)?;
+            }
+            cursor = next;
+        }
+        Ok(merged)
+    }
+
+    /// Frees the tables that are older than the newest table and returns the number of bytes
+    /// freed.
+    ///
+    /// Old tables are kept after a resize until the interner is dropped, as references to their
+    /// values can have been handed out by [`Interner::get_from_hash`]. Borrowing the interner
+    /// mutably guarantees that no such reference is alive and that no thread is using an old
+    /// table, so the moves that are not done are finished and every table but the newest is
+    /// freed, up to half of the memory after a resize. Nothing is freed if a move is cancelled.
+    /// A [`Cursor`] from before the call can skip or repeat values when the iteration continues.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let values: Vec<u64> = (0..1000).collect();
+    /// let mut interner: Interner<&u64> = Interner::with_capacity(10);
+    /// for value in values.iter() {
+    ///     interner.intern_ref(value, || value);
+    /// }
+    /// assert!(interner.stats().tables > 1);
+    /// assert!(interner.reclaim_moved_tables() > 0);
+    /// assert_eq!(1, interner.stats().tables);
+    /// assert_eq!(0, interner.reclaim_moved_tables());
+    /// assert!(values.iter().all(|value| interner.get(value) == Some(value)));
+    /// ```
+    pub fn reclaim_moved_tables(&mut self) -> usize
+    where
+        T: Copy,
+    {
+        self.finish_transfers();
+        let Some((newest, freed)) = self.raw_interners.detach_newest_if_moved() else {
+            return 0;
+        };
+        // the old first table owns and drops the other old tables
+        self.raw_interners = *newest;
+        *self.current_raw_interner.get_mut() = std::ptr::null_mut();
+        freed
+    }
+
+    /// Moves all values to a new table sized for [`Interner::len`] values, when that table is
+    /// smaller than the newest table. Returns `true` if the values were moved.
+    ///
+    /// A bulk load can leave the interner with a table much larger than needed, as a resize is
+    /// triggered when the probe sequence of one value is full. The new table is where the
+    /// operations start after the call, the old tables are kept until
+    /// [`Interner::reclaim_moved_tables`]. Inserts can continue during the call, as during a
+    /// resize. Nothing is moved if a resize is ongoing, resizes are denied or the new table would
+    /// pass the memory limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interner, InternerBuilder};
+    ///
+    /// let values: Vec<u64> = (0..1000).collect();
+    /// let mut interner: Interner<&u64> = InternerBuilder::new().capacity(1_000_000).build();
+    /// for value in values.iter() {
+    ///     interner.intern_ref(value, || value);
+    /// }
+    /// assert!(interner.compact());
+    /// assert!(interner.capacity() < 10_000);
+    /// assert!(!interner.compact());
+    /// interner.reclaim_moved_tables();
+    /// assert!(values.iter().all(|value| interner.get(value) == Some(value)));
+    /// ```
+    pub fn compact(&self) -> bool
+    where
+        T: Copy,
+    {
+        self.finish_transfers();
+        let newest_raw_interner = self.newest_raw_interner();
+        if !newest_raw_interner.compact(self.len(), &self.hash_builder)
+            || !newest_raw_interner.is_transfer_done()
+        {
+            return false;
+        }
+        let mut raw_interner = self.current_raw_interner();
+        while let Some(next_raw_interner) = raw_interner.try_get_next_raw_interner() {
+            if !raw_interner.is_transfer_done() {
+                return true;
+            }
+            self.move_current_raw_interner(raw_interner);
+            raw_interner = next_raw_interner;
+        }
+        true
+    }
+
+    // moves the values of the tables with a next table that are not moved, unless the moves are
+    // cancelled
+    fn finish_transfers(&self)
+    where
+        T: Copy,
+    {
+        let mut raw_interner = &self.raw_interners;
+        while let Some(next_raw_interner) = raw_interner.try_get_next_raw_interner() {
+            if !raw_interner.is_transfer_done() {
+                raw_interner.finish_transfer(&self.hash_builder);
+            }
+            raw_interner = next_raw_interner;
+        }
+    }
+
+    /// Turns the interner in to a read-only [`FrozenInterner`] with all values, also the values
+    /// of the preset, for the read phase of workloads that intern all values before reading.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let interner: Interner<&str> = Interner::new();
+    /// let a = interner.intern_ref("a", || "a");
+    /// let frozen = interner.freeze();
+    /// assert!(std::ptr::eq(a, *frozen.get("a").unwrap()));
+    /// ```
+    pub fn freeze(self) -> FrozenInterner<T, S>
+    where
+        T: Copy,
+        S: Clone,
+    {
+        self.snapshot()
+    }
+
+    // copies the values of the preset and the tables out, values interned during the copy may
+    // be missing
+    pub(crate) fn values(&self) -> Vec<T>
+    where
+        T: Copy,
+    {
+        let mut values = Vec::with_capacity(self.len() + self.preset_len());
+        if let Some(preset) = &self.preset {
+            values.extend(preset.iter().map(|(_, value)| *value));
+        }
+        let mut cursor = Some(Cursor::default());
+        while let Some(current) = cursor {
+            let (chunk, next) = self.iter_chunk(current, COPY_CHUNK_SIZE);
+            values.extend(chunk);
+            cursor = next;
+        }
+        values
+    }
+
+    // copies the values to a `FrozenInterner` without consuming the interner, values interned
+    // during the copy may be missing
+    pub(crate) fn snapshot(&self) -> FrozenInterner<T, S>
+    where
+        T: Copy,
+        S: Clone,
+    {
+        let entries = self
+            .values()
+            .into_iter()
+            .map(|value| (self.hash_builder.hash_one(value), value))
+            .collect();
+        FrozenInterner::new(self.hasher_clone(), entries)
+    }
+
+    /// Returns an iterator over the values that are interned in both this interner and `other`.
+    ///
+    /// The interner with the smaller table is walked and each of its values is looked up in the
+    /// other interner, the values are hashed once as the tables do not store the hashes. The
+    /// interners must share the same hasher, see [`Interner::child`]. The values are returned
+    /// from the walked interner and values that are interned during the iteration may not be
+    /// returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MergeError::HasherMismatch`] if `other` does not share the hasher of this
+    /// interner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let values = [1, 2, 3];
+    /// let interner: Interner<&i32> = Interner::new();
+    /// let other = interner.child();
+    /// interner.intern_ref(&values[0], || &values[0]);
+    /// interner.intern_ref(&values[1], || &values[1]);
+    /// other.intern_ref(&values[1], || &values[1]);
+    /// other.intern_ref(&values[2], || &values[2]);
+    /// let both: Vec<&i32> = interner.intersection(&other).unwrap().collect();
+    /// assert_eq!(vec![&2], both);
+    /// ```
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a Interner<T, S>,
+    ) -> Result<Intersection<'a, T, S>, MergeError>
+    where
+        T: Copy,
+    {
+        if self.hasher_id() != other.hasher_id() {
+            return Err(MergeError::HasherMismatch);
+        }
+        let (walked, probed) =
+            if self.current_raw_interner().capacity() <= other.current_raw_interner().capacity() {
+                (self, other)
+            } else {
+                (other, self)
+            };
+        Ok(Intersection { walk: Walk::new(walked, probed) })
+    }
+
+    /// Returns an iterator over the values that are interned in this interner but not in
+    /// `other`.
+    ///
+    /// This interner is walked and each of its values is looked up in `other`, the values are
+    /// hashed once as the tables do not store the hashes. The interners must share the same
+    /// hasher, see [`Interner::child`]. Values that are interned during the iteration may not be
+    /// returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MergeError::HasherMismatch`] if `other` does not share the hasher of this
+    /// interner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let values = [1, 2, 3];
+    /// let interner: Interner<&i32> = Interner::new();
+    /// let other = interner.child();
+    /// interner.intern_ref(&values[0], || &values[0]);
+    /// interner.intern_ref(&values[1], || &values[1]);
+    /// other.intern_ref(&values[1], || &values[1]);
+    /// other.intern_ref(&values[2], || &values[2]);
+    /// let only_interner: Vec<&i32> = interner.difference(&other).unwrap().collect();
+    /// assert_eq!(vec![&1], only_interner);
+    /// ```
+    pub fn difference<'a>(
+        &'a self,
+        other: &'a Interner<T, S>,
+    ) -> Result<Difference<'a, T, S>, MergeError>
+    where
+        T: Copy,
+    {
+        if self.hasher_id() != other.hasher_id() {
+            return Err(MergeError::HasherMismatch);
+        }
+        Ok(Difference { walk: Walk::new(self, other) })
+    }
+
+    /// Returns an iterator that interns each item of `iter` and yields the interned values.
+    ///
+    /// The items are hashed in batches ahead of the interning and the buckets of a batch are
+    /// prefetched, so the memory accesses of a batch overlap. `make` is called for each item that
+    /// is not already interned. As the `Interner` is `Sync` a large input can be split in chunks
+    /// that are deduplicated on separate threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let values = ["a", "b", "a", "c", "b"];
+    /// let interner: Interner<&str> = Interner::new();
+    /// let interned: Vec<&str> = interner.dedup_stream(values, |val| val).collect();
+    /// assert_eq!(values[..], interned[..]);
+    /// assert!(std::ptr::eq(interned[0], interned[2]));
+    /// ```
+    pub fn dedup_stream<I, F>(&self, iter: I, make: F) -> DedupStream<'_, I::IntoIter, T, S, F>
+    where
+        I: IntoIterator,
+        I::Item: Hash + Eq,
+        T: Borrow<I::Item> + Copy,
+        F: FnMut(I::Item) -> T,
+    {
+        DedupStream {
+            interner: self,
+            iter: iter.into_iter(),
+            make,
+            hashed: VecDeque::with_capacity(DEDUP_STREAM_BATCH_SIZE),
+            key: PhantomData,
+        }
+    }
+
+    /// Returns an iterator that interns the borrowed form of each item of `iter` and yields the
+    /// interned values in the order of `iter`.
+    ///
+    /// The same as [`Interner::dedup_stream`], but `make` gets the item borrowed as `Q`, like
+    /// with [`Interner::intern_ref`], so e.g. a column of strings borrowed from an input buffer
+    /// is mapped to canonical values that outlive the buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let column = "b,a,b,c".to_string();
+    /// let interner: Interner<&'static str> = Interner::new();
+    /// let canonical: Vec<&'static str> = interner
+    ///     .map_intern(column.split(','), |value: &str| String::from(value).leak())
+    ///     .collect();
+    /// drop(column);
+    /// assert_eq!(vec!["b", "a", "b", "c"], canonical);
+    /// assert!(std::ptr::eq(canonical[0], canonical[2]));
+    /// assert_eq!(3, interner.len());
+    /// ```
+    pub fn map_intern<I, Q, F>(
+        &self,
+        iter: I,
+        mut make: F,
+    ) -> DedupStream<'_, I::IntoIter, T, S, impl FnMut(I::Item) -> T, Q>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+        T: Borrow<Q> + Copy,
+        F: FnMut(&Q) -> T,
+    {
+        DedupStream {
+            interner: self,
+            iter: iter.into_iter(),
+            make: move |item: I::Item| make(item.borrow()),
+            hashed: VecDeque::with_capacity(DEDUP_STREAM_BATCH_SIZE),
+            key: PhantomData,
+        }
+    }
+
+    /// Interns all `values` and returns the interned values in the order of `values`.
+    ///
+    /// All values are hashed before any of them is interned and are then interned in the order
+    /// of their buckets in the current table, so the buckets are visited mostly sequentially.
+    /// The current table is only loaded again when a resize is started, which makes this faster
+    /// than interning the values one by one when loading many values, e.g. at startup.
+    /// `make` is called for each value that is not already interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let values = ["a", "b", "a", "c"];
+    /// let interner: Interner<&str> = Interner::new();
+    /// let interned = interner.intern_many(values, |val| val);
+    /// assert_eq!(values[..], interned[..]);
+    /// assert!(std::ptr::eq(interned[0], interned[2]));
+    /// assert_eq!(3, interner.len());
+    /// ```
+    pub fn intern_many<I>(&self, values: I, mut make: impl FnMut(I::Item) -> T) -> Vec<T>
+    where
+        I: IntoIterator,
+        I::Item: Hash + Eq,
+        T: Borrow<I::Item> + Copy,
+    {
+        let mut raw_interner = self.current_raw_interner();
+        let buckets = raw_interner.buckets().max(1);
+        let mut hashed: Vec<(usize, u64, I::Item)> = values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| (i, self.hash_builder.hash_one(&value), value))
+            .collect();
+        hashed.sort_unstable_by_key(|&(i, hash, _)| (raw_interner::bucket_index(hash, buckets), i));
+        let mut results = vec![None; hashed.len()];
+        for (i, hash, value) in hashed {
+            let result = self.try_make_hashed_is_new_in(
+                raw_interner,
+                hash,
+                value,
+                |value, result| value.eq(result.borrow()),
+                |value| Ok::<T, Infallible>(make(value)),
+                None,
+            );
+            let slot = result
+                .unwrap_or_else(|error| panic!("{error}"))
+                .unwrap_or_else(|never| match never {});
+            results[i] = Some(slot.value);
+            if raw_interner.try_get_next_raw_interner().is_some() {
+                // a resize is started, the current table moves when the transfer is done
+                raw_interner = self.current_raw_interner();
+            }
+        }
+        results.into_iter().map(|result| result.expect("every value is interned")).collect()
+    }
+
+    pub(crate) fn intern_hashed<V>(
+        &self,
+        hash: u64,
+        value: V,
+        is_match: impl Fn(&V, &T) -> bool,
+        make: impl FnOnce(V) -> T,
+    ) -> Result<T, InternError>
+    where
+        T: Copy,
+    {
+        self.intern_hashed_is_new(hash, value, is_match, make, None).map(|(result, _)| result)
+    }
+
+    // same as `intern_hashed` but also returns true if the value was inserted by this call, and
+    // gives up waiting on locked slots and walking the chain of tables at `deadline`
+    fn intern_hashed_is_new<V>(
+        &self,
+        hash: u64,
+        value: V,
+        is_match: impl Fn(&V, &T) -> bool,
+        make: impl FnOnce(V) -> T,
+        deadline: Option<Instant>,
+    ) -> Result<(T, bool), InternError>
+    where
+        T: Copy,
+    {
+        let result = self.try_make_hashed_is_new(
+            hash,
+            value,
+            is_match,
+            |value| Ok::<T, Infallible>(make(value)),
+            deadline,
+        )?;
+        Ok(result.unwrap_or_else(|never| match never {}))
+    }
+
+    // same as `intern_hashed_is_new` but `make` can fail, the slot locked for the value is then
+    // poisoned and the error of `make` is returned. The slot is also poisoned if `make` panics.
+    #[inline]
+    fn try_make_hashed_is_new<V, E>(
+        &self,
+        hash: u64,
+        value: V,
+        is_match: impl Fn(&V, &T) -> bool,
+        make: impl FnOnce(V) -> Result<T, E>,
+        deadline: Option<Instant>,
+    ) -> Result<Result<(T, bool), E>, InternError>
+    where
+        T: Copy,
+    {
+        let result = self.try_make_hashed_is_new_in(
+            self.current_raw_interner(),
+            hash,
+            value,
+            is_match,
+            make,
+            deadline,
+        )?;
+        Ok(result.map(|slot| (slot.value, slot.is_new)))
+    }
+
+    // same as `try_make_hashed_is_new` but starts in `raw_interner` instead of loading the
+    // current table, the table must be the current table or a table before it in the chain, and
+    // also returns the dense id of the value
+    fn try_make_hashed_is_new_in<V, E>(
+        &self,
+        mut raw_interner: &RawInterner<T>,
+        hash: u64,
+        value: V,
+        is_match: impl Fn(&V, &T) -> bool,
+        make: impl FnOnce(V) -> Result<T, E>,
+        deadline: Option<Instant>,
+    ) -> Result<Result<InternedSlot<T>, E>, InternError>
+    where
+        T: Copy,
+    {
+        if let Some(preset) = &self.preset {
+            if let Some(result) = preset.get(hash, |result| is_match(&value, result)) {
+                let slot = InternedSlot { value: *result, is_new: false, dense_id: None };
+                return Ok(Ok(slot));
+            }
+        }
+        let hash_pair = HashPair::new(hash);
+        let mut is_current_interner = true;
+        loop {
+            let lock_result = raw_interner.lock_or_get_slot(
+                hash_pair,
+                |result| is_match(&value, result),
+                deadline,
+            );
+            if let LockResult::Found(result, pos, index) = lock_result {
+                let dense_id = raw_interner.dense_id(pos, index);
+                return Ok(Ok(InternedSlot { value: result, is_new: false, dense_id }));
+            }
+            if let LockResult::TimedOut = lock_result {
+                return Err(InternError::DeadlineExceeded);
+            }
+            if let LockResult::Locked(locked_data) = lock_result {
+                let guard = PoisonOnDrop {
+                    interner: self,
+                    raw_interner,
+                    is_current_interner,
+                    hash: hash_pair,
+                    locked_data: Some(locked_data),
+                };
+                let result = match &raw_interner.settings().make_timer {
+                    Some(make_timer) => make_timer.time(hash, || make(value)),
+                    None => make(value),
+                };
+                let result = match result {
+                    Ok(result) => result,
+                    // dropping the guard poisons the slot
+                    Err(error) => return Ok(Err(error)),
+                };
+                // counted before the value is visible, so a value that is seen has an id less
+                // than the length
+                let len = self.len.fetch_add(1, Ordering::Relaxed);
+                let dense_id = raw_interner.settings().dense_ids.then(|| {
+                    u32::try_from(len).expect("more than u32::MAX values interned with dense ids")
+                });
+                let is_transfer_done = raw_interner.unlock_and_set_value(
+                    hash_pair,
+                    result,
+                    dense_id.unwrap_or(0),
+                    guard.disarm(),
+                    &self.hash_builder,
+                );
+                if is_transfer_done && is_current_interner {
+                    self.move_current_raw_interner(raw_interner);
+                }
+                if let Some(on_new_value) = &self.on_new_value {
+                    on_new_value.call(result);
+                }
+                return Ok(Ok(InternedSlot { value: result, is_new: true, dense_id }));
+            }
+            if let LockResult::ResizeNeeded = lock_result {
+                if raw_interner.try_create_and_stor_next_raw_interner(&self.hash_builder)?
+                    && is_current_interner
+                {
+                    self.move_current_raw_interner(raw_interner);
+                }
+            }
+            if raw_interner.settings().strict_two_table && !raw_interner.is_transfer_done() {
+                // the next table is only inserted in to when all values are moved to it
+                raw_interner.finish_transfer(&self.hash_builder);
+                if is_current_interner {
+                    self.move_current_raw_interner(raw_interner);
+                }
+            }
+            raw_interner.check_stuck_transfer();
+            raw_interner = raw_interner.get_next_raw_interner();
+            is_current_interner = false;
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Err(InternError::DeadlineExceeded);
+            }
+        }
+    }
+
+    // all values have been moved from `raw_interner` so new operations can start in the next interner
+    #[cold]
+    fn move_current_raw_interner(&self, raw_interner: &RawInterner<T>) {
+        // release so a thread that loads the pointer sees the initialization of the table, even
+        // when it did not create it or load it from the chain, see the loom models
+        self.current_raw_interner
+            .store(raw_interner.get_next_moved_raw_interner_ptr(), Ordering::Release);
+        if let Some(read_sampler) = &raw_interner.settings().read_sampler {
+            read_sampler.reset();
+        }
+    }
+
+    /// Calls `f` with the state of each bucket a lookup of `hash` checks.
+    ///
+    /// The buckets are visited in probe order from the current table to the newest table,
+    /// in each table the visit stops at the first bucket with an empty slot. This is meant for
+    /// understanding collisions of specific keys in tests and diagnostics, the states are
+    /// snapshots and can change concurrently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::stats::SlotInfo;
+    /// use interner::Interner;
+    /// use std::hash::BuildHasher;
+    ///
+    /// let value1: i32 = 42;
+    /// let interner: Interner<&i32> = Interner::with_capacity(10);
+    /// interner.intern_ref(&value1, || &value1);
+    /// let hash = interner.hasher().hash_one(&value1);
+    /// let mut found = false;
+    /// interner.visit_bucket_of(hash, |bucket| {
+    ///     for slot in bucket.slots.iter() {
+    ///         if let SlotInfo::Valid { value, .. } = slot {
+    ///             found |= **value == &value1;
+    ///         }
+    ///     }
+    /// });
+    /// assert!(found);
+    /// ```
+    pub fn visit_bucket_of<'a>(&'a self, hash: u64, mut f: impl FnMut(BucketInfo<'a, T>)) {
+        let mut raw_interner = Some(self.current_raw_interner());
+        let mut table = 0;
+        while let Some(current) = raw_interner {
+            current.visit_probe_seq(hash, table, &mut f);
+            raw_interner = current.try_get_next_raw_interner();
+            table += 1;
+        }
+    }
+
+    /// Returns up to `n` interned values starting at `cursor` and the cursor to continue from,
+    /// or `None` if all values have been returned.
+    ///
+    /// The iteration can be continued at any time later, so a large interner can be visited in
+    /// small chunks. Values interned during the iteration can be skipped, and if the interner
+    /// is resized during the iteration values can be returned more than once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::interner::Cursor;
+    /// use interner::Interner;
+    ///
+    /// let values: Vec<u64> = (0..100).collect();
+    /// let interner: Interner<&u64> = Interner::new();
+    /// for value in values.iter() {
+    ///     interner.intern_ref(value, || value);
+    /// }
+    /// let mut cursor = Some(Cursor::default());
+    /// let mut result: Vec<&u64> = Vec::new();
+    /// while let Some(current) = cursor {
+    ///     let (chunk, next) = interner.iter_chunk(current, 10);
+    ///     assert!(chunk.len() <= 10);
+    ///     result.extend(chunk);
+    ///     cursor = next;
+    /// }
+    /// result.sort();
+    /// assert_eq!(values.iter().collect::<Vec<_>>(), result);
+    /// ```
+    pub fn iter_chunk(&self, cursor: Cursor, n: usize) -> (Vec<T>, Option<Cursor>)
+    where
+        T: Copy,
+    {
+        let Cursor { mut table, mut pos, mut index } = cursor;
+        let mut raw_interner = Some(&self.raw_interners);
+        for _ in 0..table {
+            raw_interner = raw_interner.and_then(|current| current.try_get_next_raw_interner());
+        }
+        let mut chunk = Vec::with_capacity(n);
+        while let Some(current) = raw_interner {
+            current.collect_chunk(&mut pos, &mut index, n, &mut chunk);
+            if chunk.len() == n {
+                return (chunk, Some(Cursor { table, pos, index }));
+            }
+            raw_interner = current.try_get_next_raw_interner();
+            table += 1;
+            pos = 0;
+            index = 0;
+        }
+        (chunk, None)
+    }
+
+    /// Returns up to `n` randomly picked interned values.
+    ///
+    /// The values are found by checking random slots with random numbers from `rng` and
+    /// rejecting the empty slots, so no full iteration of the table is needed. The sample is
+    /// approximately uniform and the same value can be returned more than once. Less than `n`
+    /// values are returned if too many of the checked slots are empty, e.g. when the interner
+    /// is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let values = [1, 2, 3, 4, 5];
+    /// let interner: Interner<&i32> = Interner::new();
+    /// for value in values.iter() {
+    ///     interner.intern_ref(value, || value);
+    /// }
+    /// let mut state = 0x2545_F491_4F6C_DD1Du64;
+    /// let rng = || {
+    ///     state ^= state << 13;
+    ///     state ^= state >> 7;
+    ///     state ^= state << 17;
+    ///     state
+    /// };
+    /// for value in interner.sample(3, rng) {
+    ///     assert!(values.contains(value));
+    /// }
+    /// ```
+    pub fn sample(&self, n: usize, mut rng: impl FnMut() -> u64) -> Vec<T>
+    where
+        T: Copy,
+    {
+        let raw_interner = self.current_raw_interner();
+        let mut result = Vec::with_capacity(n);
+        for _ in 0..n.saturating_mul(SAMPLE_ATTEMPTS_PER_VALUE) {
+            if result.len() == n {
+                break;
+            }
+            if let Some(value) = raw_interner.random_slot(&mut rng) {
+                result.push(*value);
+            }
+        }
+        result
+    }
+
+    /// get already interned value if available.
+    ///
+    /// If the interner is in the middle of a resize the lookup can need to check more than one
+    /// table, see [`InternerBuilder::read_transfer_budget`](crate::InternerBuilder::read_transfer_budget)
+    /// for letting lookups help finish the resize.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    /// use std::hash::{BuildHasher, Hash, Hasher };
+    ///
+    /// let value1 :i32 = 42;
+    /// let mut interner: Interner<&i32> = Interner::with_capacity(2);
+    /// let mut state = interner.hasher().build_hasher();
+    /// value1.hash(&mut state);
+    /// let hash = state.finish();
+    /// assert!(interner.get_from_hash(hash,|val| {*val == &value1}).is_none());
+    /// let result = interner.intern_ref(&value1,|| {&value1});
+    /// assert_eq!(&value1,result);
+    /// let result = interner.get_from_hash(hash, |val| {*val == &value1}).expect("was interned above");
+    /// assert_eq!(&value1,*result);
+    /// ```
+    pub fn get_from_hash<F>(&self, hash: u64, mut is_match: F) -> Option<&T>
+    where
+        T: Copy,
+        F: FnMut(&T) -> bool,
+    {
+        if let Some(preset) = &self.preset {
+            if let Some(result) = preset.get(hash, &mut is_match) {
+                return Some(result);
+            }
+        }
+        let mut raw_interner = self.current_raw_interner();
+        let read_sampler = raw_interner.settings().read_sampler.as_deref();
+        let is_sampled = read_sampler.is_some() && ReadSampler::is_sampled(hash);
+        if let Some(read_sampler) = read_sampler {
+            if let Some(next_raw_interner) =
+                raw_interner.try_get_next_raw_interner().filter(|_| read_sampler.starts_in_next())
+            {
+                // a miss here does not mean that the value is missing, it can be in a bucket of
+                // the current table that is not moved
+                if let Some(Some(result)) = next_raw_interner.get(hash, &mut is_match) {
+                    if is_sampled {
+                        read_sampler.record(1);
+                    }
+                    return Some(result);
+                }
+            }
+        }
+        let mut is_current_interner = true;
+        let mut extra_tables = 0;
+        loop {
+            match raw_interner.get(hash, &mut is_match) {
+                Some(result) => {
+                    if let Some(read_sampler) =
+                        read_sampler.filter(|_| is_sampled && result.is_some())
+                    {
+                        read_sampler.record(extra_tables);
+                    }
+                    return result;
+                }
                 None => {
+                    extra_tables += 1;
+                    let budget = raw_interner.settings().read_transfer_budget;
+                    if budget != 0
+                        && raw_interner.help_transfer(hash, budget, &self.hash_builder)
+                        && is_current_interner
+                    {
+                        self.move_current_raw_interner(raw_interner);
+                    }
                     raw_interner = raw_interner.get_next_raw_interner();
+                    is_current_interner = false;
+                }
+            }
+        }
+    }
+
+    /// Returns the interned value equal to `value`, or `None` if it is not interned.
+    ///
+    /// The value is hashed with the hasher of the interner and compared with `Eq`, as
+    /// [`Interner::intern_ref`] does, see [`Interner::get_from_hash`] for lookups with a
+    /// precomputed hash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let interner: Interner<&str> = Interner::new();
+    /// assert_eq!(None, interner.get("a"));
+    /// interner.intern_ref("a", || "a");
+    /// assert_eq!(Some("a"), interner.get("a"));
+    /// ```
+    pub fn get<Q>(&self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q> + Copy,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.hash_builder.hash_one(value);
+        let ptr_eq = self.raw_interners.settings().ptr_eq_fast_path;
+        self.get_from_hash(hash, |result| borrowed_eq(ptr_eq, value, result.borrow())).copied()
+    }
+
+    /// Returns the dense id of the interned value equal to `value`, or `None` if it is not
+    /// interned.
+    ///
+    /// The ids are given out when the values are inserted, counting from 0 without gaps, so
+    /// they can index `Vec` based side tables like [`SideTable`](crate::SideTable) without
+    /// hashing. A value that a thread has seen in the interner has an id less than
+    /// [`Interner::len`] read after, so a side table grown to the length covers every value
+    /// that can be looked up. The values of the preset have no ids.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interner is not built with [`InternerBuilder::dense_ids`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interner, InternerBuilder};
+    ///
+    /// let interner: Interner<&str> = InternerBuilder::new().dense_ids(true).build();
+    /// interner.intern_ref("a", || "a");
+    /// interner.intern_ref("b", || "b");
+    /// interner.intern_ref("a", || "a");
+    /// assert_eq!(Some(0), interner.dense_id("a"));
+    /// assert_eq!(Some(1), interner.dense_id("b"));
+    /// assert_eq!(None, interner.dense_id("c"));
+    /// ```
+    pub fn dense_id<Q>(&self, value: &Q) -> Option<u32>
+    where
+        T: Borrow<Q> + Copy,
+        Q: ?Sized + Hash + Eq,
+    {
+        assert!(self.has_dense_ids(), "the interner is not built with dense ids");
+        let hash = self.hash_builder.hash_one(value);
+        let ptr_eq = self.raw_interners.settings().ptr_eq_fast_path;
+        let mut is_match = |result: &T| borrowed_eq(ptr_eq, value, result.borrow());
+        let mut raw_interner = self.current_raw_interner();
+        loop {
+            match raw_interner.get_slot(hash, &mut is_match) {
+                Some(Some((pos, index, _))) => return raw_interner.dense_id(pos, index),
+                Some(None) => return None,
+                None => raw_interner = raw_interner.get_next_raw_interner(),
+            }
+        }
+    }
+
+    // interns the value and returns the interned value with its dense id, `None` for the values
+    // of the preset, with one probe instead of `intern` followed by `dense_id`
+    pub(crate) fn intern_with_dense_id(&self, value: T) -> (T, Option<u32>)
+    where
+        T: Hash + Eq + Copy,
+    {
+        let hash = self.hash_builder.hash_one(value);
+        let slot = self
+            .try_make_hashed_is_new_in(
+                self.current_raw_interner(),
+                hash,
+                value,
+                |value, result| value == result,
+                Ok::<T, Infallible>,
+                None,
+            )
+            .unwrap_or_else(|error| panic!("{error}"))
+            .unwrap_or_else(|never| match never {});
+        (slot.value, slot.dense_id)
+    }
+
+    /// Interns the items of `iter` and returns each value with the number of times it was seen,
+    /// in the order the values were first seen and the values of the preset last.
+    ///
+    /// The counts are kept in a [`SideTable`](crate::SideTable) indexed by the dense ids of the
+    /// values, see [`Histogram`] to count from several threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interner is not built with [`InternerBuilder::dense_ids`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::{Interner, InternerBuilder};
+    ///
+    /// let interner: Interner<&str> = InternerBuilder::new().dense_ids(true).build();
+    /// let counts = interner.histogram("b a b c b".split(' '));
+    /// assert_eq!(vec![("b", 3), ("a", 1), ("c", 1)], counts);
+    /// ```
+    pub fn histogram<I>(&self, iter: I) -> Vec<(T, u64)>
+    where
+        I: IntoIterator<Item = T>,
+        T: Copy,
+    {
+        let mut histogram = Histogram::new(self);
+        histogram.extend(iter);
+        histogram.into_vec()
+    }
+
+    /// Looks up all `keys` and returns the interned values and a bitmap of the keys that are
+    /// not interned.
+    ///
+    /// All keys are hashed and their buckets prefetched before any bucket is probed, so the
+    /// memory accesses of the lookups overlap. The bitmap lets a caller fetch all missing values
+    /// in one round trip before interning them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let values = [1, 2, 3];
+    /// let interner: Interner<&i32> = Interner::new();
+    /// interner.intern_ref(&values[1], || &values[1]);
+    /// let result = interner.get_many(&[1, 2, 3]);
+    /// assert_eq!(vec![None, Some(&2), None], result.values);
+    /// assert_eq!(vec![0, 2], result.missing().collect::<Vec<_>>());
+    /// ```
+    pub fn get_many<Q>(&self, keys: &[Q]) -> GetMany<T>
+    where
+        T: Borrow<Q> + Copy,
+        Q: Hash + Eq,
+    {
+        let hashes: Vec<u64> = keys.iter().map(|key| self.hash_builder.hash_one(key)).collect();
+        let raw_interner = self.current_raw_interner();
+        for &hash in hashes.iter() {
+            raw_interner.prefetch(hash);
+        }
+        let mut misses = vec![0; keys.len().div_ceil(64)];
+        let values = keys
+            .iter()
+            .zip(hashes)
+            .enumerate()
+            .map(|(i, (key, hash))| {
+                let value = self.get_from_hash(hash, |value| key.eq(value.borrow())).copied();
+                if value.is_none() {
+                    misses[i / 64] |= 1 << (i % 64);
+                }
+                value
+            })
+            .collect();
+        GetMany { values, misses }
+    }
+}
+
+impl<'a, S> Interner<&'a str, S>
+where
+    S: BuildHasher,
+{
+    /// Interns the string slice and returns the interned slice, the same as
+    /// [`Interner::intern_ref`] but without the generic `Borrow` and `Eq` calls.
+    ///
+    /// The values are compared by length and address before the bytes are compared with
+    /// `memcmp`. The hash is computed with the hasher of the interner the same way as in
+    /// `intern_ref`, so both can be used on the same interner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let interner: Interner<&str> = Interner::new();
+    /// let value = String::from("value");
+    /// let result = interner.intern_str(&value, || "value");
+    /// assert!(std::ptr::eq(result, interner.intern_ref("value", || unreachable!())));
+    /// ```
+    pub fn intern_str(&self, value: &str, make: impl FnOnce() -> &'a str) -> &'a str {
+        let hash = self.hash_builder.hash_one(value);
+        self.intern_hashed(
+            hash,
+            value,
+            |value, result| bytes_eq(value.as_bytes(), result.as_bytes()),
+            |_| make(),
+        )
+        .unwrap_or_else(|error| panic!("{error}"))
+    }
+}
+
+impl<'a, S> Interner<&'a [u8], S>
+where
+    S: BuildHasher,
+{
+    /// Interns the byte slice and returns the interned slice, the same as
+    /// [`Interner::intern_ref`] but without the generic `Borrow` and `Eq` calls.
+    ///
+    /// The values are compared by length and address before the bytes are compared with
+    /// `memcmp`. The hash is computed with the hasher of the interner the same way as in
+    /// `intern_ref`, so both can be used on the same interner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::Interner;
+    ///
+    /// let interner: Interner<&[u8]> = Interner::new();
+    /// let value = vec![1, 2, 3];
+    /// let result = interner.intern_bytes(&value, || &[1, 2, 3]);
+    /// assert!(std::ptr::eq(result, interner.intern_ref(&[1u8, 2, 3][..], || unreachable!())));
+    /// ```
+    pub fn intern_bytes(&self, value: &[u8], make: impl FnOnce() -> &'a [u8]) -> &'a [u8] {
+        let hash = self.hash_builder.hash_one(value);
+        self.intern_hashed(hash, value, |value, result| bytes_eq(value, result), |_| make())
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+}
+
+// with `ptr_eq` a value borrowed from the candidate is equal without calling `Eq`
+#[inline(always)]
+fn borrowed_eq<Q: ?Sized + Eq>(ptr_eq: bool, value: &Q, result: &Q) -> bool {
+    (ptr_eq && std::ptr::eq(value, result)) || value == result
+}
+
+// true if the memory of `value` and `result` overlaps, empty values never overlaps
+#[inline]
+fn overlaps<Q: ?Sized>(value: &Q, result: &Q) -> bool {
+    let value_start = value as *const Q as *const u8 as usize;
+    let result_start = result as *const Q as *const u8 as usize;
+    value_start < result_start + std::mem::size_of_val(result)
+        && result_start < value_start + std::mem::size_of_val(value)
+}
+
+// the length is checked first as most slices with the same hash but other contents also have
+// another length, and the same address is equal without reading the bytes
+#[inline(always)]
+fn bytes_eq(value: &[u8], result: &[u8]) -> bool {
+    value.len() == result.len()
+        && (std::ptr::eq(value.as_ptr(), result.as_ptr()) || value == result)
+}
+
+/// A handle to an [`Interner`] that does not keep it alive.
+///
+/// This `struct` is created by [`Interner::downgrade`].
+pub struct WeakInterner<T, S = DefaultHashBuilder> {
+    interner: Weak<Interner<T, S>>,
+}
+
+impl<T, S> WeakInterner<T, S> {
+    /// Returns the interner if it has not been dropped.
+    #[inline]
+    pub fn upgrade(&self) -> Option<Arc<Interner<T, S>>> {
+        self.interner.upgrade()
+    }
+}
+
+impl<T, S> Clone for WeakInterner<T, S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self { interner: Weak::clone(&self.interner) }
+    }
+}
+
+/// The position to continue an iteration from with [`Interner::iter_chunk`].
+///
+/// `Cursor::default()` starts a new iteration.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Cursor {
+    table: usize,
+    pos: usize,
+    index: usize,
+}
+
+/// Proof given to `make` of [`Interner::intern_ref_with_token`] that the slot of the value is
+/// locked by the call, so the value returned by `make` is inserted.
+///
+/// The token can only be created by the interner and only lives for the call of `make`.
+#[derive(Debug)]
+pub struct SlotToken {
+    hash: u64,
+}
+
+impl SlotToken {
+    /// Returns the hash of the value that is inserted, as hashed by the interner's hasher.
+    #[inline]
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// The result of [`Interner::intern_ref_entry`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Interned<T> {
+    /// The interned value.
+    pub value: T,
+    /// The value was inserted by the call, `make` was called.
+    pub was_new: bool,
+}
+
+/// The result of [`Interner::get_many`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GetMany<T> {
+    /// The interned value of each key, `None` if the key is not interned.
+    pub values: Vec<Option<T>>,
+    /// Bit `i % 64` of word `i / 64` is set if key `i` is not interned.
+    pub misses: Vec<u64>,
+}
+
+impl<T> GetMany<T> {
+    /// Returns the indexes of the keys that are not interned in increasing order.
+    pub fn missing(&self) -> impl Iterator<Item = usize> + '_ {
+        self.misses.iter().enumerate().flat_map(|(word_index, &word)| {
+            (0..64).filter(move |bit| word & (1 << bit) != 0).map(move |bit| word_index * 64 + bit)
+        })
+    }
+}
+
+/// Number of random slots checked per requested value in `Interner::sample` before giving up.
+const SAMPLE_ATTEMPTS_PER_VALUE: usize = 64;
+
+const DEDUP_STREAM_BATCH_SIZE: usize = 32;
+
+/// Number of values `Interner::merge` and the set operations copies out of an interner at a time.
+const COPY_CHUNK_SIZE: usize = 64;
+
+// walks the values of one interner and looks them up in another interner with the same hasher
+struct Walk<'a, T, S> {
+    walked: &'a Interner<T, S>,
+    probed: &'a Interner<T, S>,
+    // the values copied out of `walked` that are not checked yet, with their hashes
+    chunk: std::vec::IntoIter<(u64, T)>,
+    cursor: Option<Cursor>,
+}
+
+// a value returned by `Interner::try_make_hashed_is_new_in`
+struct InternedSlot<T> {
+    value: T,
+    // the value was inserted by the call
+    is_new: bool,
+    // the dense id of the value, `None` for the values of the preset and if the interner has no
+    // dense ids
+    dense_id: Option<u32>,
+}
+
+// poisons the locked slot when dropped before it is disarmed, so the threads parked on the slot
+// wake up and retry the insert when `make` returns an error or panics
+struct PoisonOnDrop<'a, T, S>
+where
+    T: Eq + Hash + Copy,
+    S: BuildHasher,
+{
+    interner: &'a Interner<T, S>,
+    raw_interner: &'a RawInterner<T>,
+    is_current_interner: bool,
+    hash: HashPair,
+    locked_data: Option<LockedData>,
+}
+
+impl<T, S> PoisonOnDrop<'_, T, S>
+where
+    T: Eq + Hash + Copy,
+    S: BuildHasher,
+{
+    fn disarm(mut self) -> LockedData {
+        self.locked_data.take().unwrap()
+    }
+}
+
+impl<T, S> Drop for PoisonOnDrop<'_, T, S>
+where
+    T: Eq + Hash + Copy,
+    S: BuildHasher,
+{
+    fn drop(&mut self) {
+        if let Some(locked_data) = self.locked_data.take() {
+            if self.raw_interner.unlock_and_poison(self.hash, locked_data)
+                && self.is_current_interner
+            {
+                self.interner.move_current_raw_interner(self.raw_interner);
+            }
+        }
+    }
+}
+
+impl<'a, T, S> Walk<'a, T, S>
+where
+    T: Eq + Hash + Copy,
+    S: BuildHasher,
+{
+    fn new(walked: &'a Interner<T, S>, probed: &'a Interner<T, S>) -> Self {
+        // the preset stores the hashes of its values
+        let preset: Vec<(u64, T)> = walked
+            .preset
+            .iter()
+            .flat_map(|preset| preset.iter())
+            .map(|(hash, value)| (hash, *value))
+            .collect();
+        Self { walked, probed, chunk: preset.into_iter(), cursor: Some(Cursor::default()) }
+    }
+
+    // returns the next walked value that is found or not found in `probed` as given by `found`
+    fn next_where(&mut self, found: bool) -> Option<T> {
+        loop {
+            for (hash, value) in self.chunk.by_ref() {
+                if self.probed.get_from_hash(hash, |result| *result == value).is_some() == found {
+                    return Some(value);
+                }
+            }
+            let (chunk, next) = self.walked.iter_chunk(self.cursor?, COPY_CHUNK_SIZE);
+            self.cursor = next;
+            let hash_builder = &self.walked.hash_builder;
+            self.chunk = chunk
+                .into_iter()
+                .map(|value| (hash_builder.hash_one(value), value))
+                .collect::<Vec<_>>()
+                .into_iter();
+        }
+    }
+}
+
+/// An iterator over the values that are interned in two interners.
+///
+/// This `struct` is created by [`Interner::intersection`].
+pub struct Intersection<'a, T, S> {
+    walk: Walk<'a, T, S>,
+}
+
+impl<T, S> Iterator for Intersection<'_, T, S>
+where
+    T: Eq + Hash + Copy,
+    S: BuildHasher,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.walk.next_where(true)
+    }
+}
+
+/// An iterator over the values that are interned in one interner but not in another.
+///
+/// This `struct` is created by [`Interner::difference`].
+pub struct Difference<'a, T, S> {
+    walk: Walk<'a, T, S>,
+}
+
+impl<T, S> Iterator for Difference<'_, T, S>
+where
+    T: Eq + Hash + Copy,
+    S: BuildHasher,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.walk.next_where(false)
+    }
+}
+
+/// An iterator that interns the items of another iterator.
+///
+/// This `struct` is created by [`Interner::dedup_stream`] and [`Interner::map_intern`], the
+/// items are looked up by their borrowed form `Q`.
+pub struct DedupStream<'a, I: Iterator, T, S, F, Q: ?Sized = <I as Iterator>::Item> {
+    interner: &'a Interner<T, S>,
+    iter: I,
+    make: F,
+    hashed: VecDeque<(u64, I::Item)>,
+    key: PhantomData<fn(&Q)>,
+}
+
+impl<'a, I, T, S, F, Q> Iterator for DedupStream<'a, I, T, S, F, Q>
+where
+    I: Iterator,
+    I::Item: Borrow<Q>,
+    Q: ?Sized + Hash + Eq,
+    T: Eq + Hash + Borrow<Q> + Copy,
+    S: BuildHasher,
+    F: FnMut(I::Item) -> T,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.hashed.is_empty() {
+            let hash_builder = &self.interner.hash_builder;
+            self.hashed.extend(
+                self.iter
+                    .by_ref()
+                    .take(DEDUP_STREAM_BATCH_SIZE)
+                    .map(|value| (hash_builder.hash_one(value.borrow()), value)),
+            );
+            let raw_interner = self.interner.current_raw_interner();
+            for &(hash, _) in self.hashed.iter() {
+                raw_interner.prefetch(hash);
+            }
+        }
+        let (hash, value) = self.hashed.pop_front()?;
+        let make = &mut self.make;
+        let result = self.interner.intern_hashed(
+            hash,
+            value,
+            |value, result| value.borrow() == result.borrow(),
+            make,
+        );
+        Some(result.unwrap_or_else(|error| panic!("{error}")))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        let len = self.hashed.len();
+        (lower.saturating_add(len), upper.and_then(|upper| upper.checked_add(len)))
+    }
+}
+
+drop_may_dangle! {
+    impl<T, S> Drop for Interner<T, S> {
+        fn drop(&mut self) {
+            if let Some(capacity_policy) = &self.capacity_policy {
+                // only the table sizes are read, not the values
+                let mut raw_interner = &self.raw_interners;
+                let mut resizes = 0;
+                while let Some(next) = raw_interner.try_get_next_raw_interner() {
+                    raw_interner = next;
+                    resizes += 1;
                 }
+                capacity_policy.record(raw_interner.capacity(), resizes);
             }
         }
     }
 }
 
+impl<T, S> FromIterator<T> for Interner<T, S>
+where
+    T: Eq + Hash + Copy,
+    S: BuildHasher + Default,
+{
+    /// Creates an `Interner` sized for the values of `iter` and interns them with
+    /// [`Interner::intern_many`].
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values: Vec<T> = iter.into_iter().collect();
+        let interner = Self::with_capacity_and_hasher(values.len(), S::default());
+        interner.intern_many(values, |value| value);
+        interner
+    }
+}
+
 impl<T, S> Default for Interner<T, S>
 where
     S: Default,
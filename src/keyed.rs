@@ -0,0 +1,128 @@
+use crate::interner::{DefaultHashBuilder, Interner};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+// stored in the slots of the interner, hashed and compared by the key only so the pairs can be
+// found with the key
+#[derive(Clone, Copy)]
+struct KeyValue<K, V> {
+    key: K,
+    value: V,
+}
+
+impl<K: PartialEq, V> PartialEq for KeyValue<K, V> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: Eq, V> Eq for KeyValue<K, V> {}
+
+impl<K: Hash, V> Hash for KeyValue<K, V> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state)
+    }
+}
+
+impl<K, V> Borrow<K> for KeyValue<K, V> {
+    #[inline]
+    fn borrow(&self) -> &K {
+        &self.key
+    }
+}
+
+/// An interner that maps each distinct key to the value that was made for it.
+///
+/// The probing and equality use the key `K` while the slots of the tables store the pairs of the
+/// key and the value `V`, e.g. the `&str` of a symbol and the symbol with its metadata. Both are
+/// copied in to the slots, so the value is returned without a second lookup or an allocation per
+/// key. The value of a key is made once, other threads that intern the same key while it is made
+/// parks until it is available.
+///
+/// # Examples
+///
+/// ```
+/// use interner::KeyedInterner;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct Symbol {
+///     id: u32,
+///     is_keyword: bool,
+/// }
+///
+/// let interner: KeyedInterner<&str, Symbol> = KeyedInterner::new();
+/// let fn_symbol = interner.intern_with("fn", || Symbol { id: 0, is_keyword: true });
+/// let x_symbol = interner.intern_with("x", || Symbol { id: 1, is_keyword: false });
+/// assert_eq!(fn_symbol, interner.intern_with("fn", || unreachable!()));
+/// assert_eq!(Some(x_symbol), interner.get("x"));
+/// assert_eq!(None, interner.get("y"));
+/// assert_eq!(2, interner.len());
+/// ```
+pub struct KeyedInterner<K, V, S = DefaultHashBuilder> {
+    interner: Interner<KeyValue<K, V>, S>,
+}
+
+impl<K, V> KeyedInterner<K, V, DefaultHashBuilder> {
+    /// Creates an empty `KeyedInterner`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V> Default for KeyedInterner<K, V, DefaultHashBuilder> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, S> KeyedInterner<K, V, S> {
+    /// Creates an empty `KeyedInterner` which will use the given hash builder to hash the keys.
+    #[inline]
+    pub const fn with_hasher(hash_builder: S) -> Self {
+        Self { interner: Interner::with_hasher(hash_builder) }
+    }
+
+    /// Returns the number of interned keys.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.interner.len()
+    }
+
+    /// Returns `true` if no keys are interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.interner.is_empty()
+    }
+}
+
+impl<K, V, S> KeyedInterner<K, V, S>
+where
+    K: Hash + Eq + Copy,
+    V: Copy,
+    S: BuildHasher,
+{
+    /// Returns the value of `key`, `make` is only called if the key is not interned.
+    ///
+    /// # Panics
+    ///
+    /// If `make` panics the slot of the key is poisoned and the next call with the key makes the
+    /// value again.
+    pub fn intern_with(&self, key: K, make: impl FnOnce() -> V) -> V {
+        self.interner.intern(key, |key| KeyValue { key, value: make() }).value
+    }
+
+    /// Returns the value of `key`, or `None` if the key is not interned.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.interner.hasher().hash_one(key);
+        let pair = self.interner.get_from_hash(hash, |pair| pair.key.borrow() == key)?;
+        Some(pair.value)
+    }
+}
@@ -1,16 +1,152 @@
-#![feature(dropck_eyepatch)]
-#![feature(portable_simd)]
-#![feature(core_intrinsics)]
+#![cfg_attr(feature = "nightly", feature(dropck_eyepatch))]
+#![cfg_attr(feature = "nightly", feature(portable_simd))]
+#![cfg_attr(feature = "nightly", feature(cfg_sanitize))]
 
 //! This library provides an concurrent insert only interner.
 //! Inserts is only locking one slot and store part of the hash in the look to let other inserts with eough diffrent hash to not block on the looked slot.
 //! During resize insert is still possible and if the value was already interned only possibly extra lookup in newer interners is done.
+//!
+//! # Stability
+//!
+//! The items exported from the crate root are the stable API of the crate. The
+//! `unstable-internals` feature exposes the raw meta data types in the [`internals`] module
+//! for power users, those can change in any release.
+//!
+//! # Features
+//!
+//! * `nightly` - enabled by default, uses the nightly only features `portable_simd` to match
+//!   the slots of a bucket with SIMD, `dropck_eyepatch` so the values can dangle when the
+//!   interner is dropped and `cfg_sanitize` for the thread sanitizer support. Without it the
+//!   crate builds on stable Rust, the slots are matched one at a time and the values must
+//!   outlive the interner.
+//! * `checked` - validates slot indexes, that slots are only written once, that read slots have
+//!   been initialized, that every change of the meta data of a slot follows the slot state
+//!   machine and the traversal of the table chain with assertions, to help debugging suspected
+//!   memory corruption. Slow.
+//! * `tracing` - emits [`tracing`](https://docs.rs/tracing) spans and events with table sizes and
+//!   counts around resizes and the transfer of values to the new table.
+//! * `unstable-internals` - exposes the [`internals`] module.
+//! * `replay` - records the outcome of every table operation in per thread ring buffers that
+//!   [`replay::trace`] merges to one trace, to reconstruct the interleaving of the threads after a
+//!   bug. Slow, every operation takes a number from a global counter.
+//! * `no-inline-hot` - never inlines the probe, reserve and unlock phases of an insert, so they
+//!   show up as separate symbols when profiling. Slower, only intended for profiling builds.
+//! * `timestamps` - adds the [`TimestampedInterner`] that records when each value is first
+//!   interned and can iterate the values in the order they arrived.
+//! * `chaos` - injects delays between locking and unlocking a slot, spurious failures of the
+//!   meta data compare exchanges and stalls of the transfers with the thread local
+//!   probabilities set with [`chaos::set`], to make stress tests hit rare interleavings. Slow,
+//!   only intended for testing.
+//! * `serde` - implements `Serialize` and `Deserialize` from [`serde`](https://docs.rs/serde)
+//!   for the [`Interner`], it is serialized as a sequence of the interned values and is
+//!   deserialized in to a table sized for the values, so the values can be kept between runs.
+//!
+//! # Sanitizers
+//!
+//! The interner can be run with the address and thread sanitizers, e.g.
+//!
+//! ```text
+//! RUSTFLAGS="-Zsanitizer=thread" cargo +nightly test -Zbuild-std --target x86_64-unknown-linux-gnu
+//! ```
+//!
+//! When built with `-Zsanitizer=thread` and the `nightly` feature, threads blocked on a slot that is being inserted wait on
+//! a condvar instead of being parked with `parking_lot_core`, and the acquire fence used when the
+//! slot is released is annotated, so the sanitizer sees all happens-before edges. The blocking is
+//! slower, only intended for sanitizer runs.
 
+// implements `Drop` with `#[may_dangle]` on `T` with the `nightly` feature, the drop must not
+// access the values of `T`
+macro_rules! drop_may_dangle {
+    (impl<T $(, $param:ident)*> Drop for $ty:ty { $($body:tt)* }) => {
+        #[cfg(feature = "nightly")]
+        unsafe impl<#[may_dangle] T $(, $param)*> Drop for $ty { $($body)* }
+        #[cfg(not(feature = "nightly"))]
+        impl<T $(, $param)*> Drop for $ty { $($body)* }
+    };
+}
+
+mod arc_interner;
+mod arena;
+mod arena_interner;
 mod bitmask;
 mod bucket;
+mod builder;
+mod cancellation;
+mod capacity_policy;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+#[cfg(not(feature = "chaos"))]
+mod chaos;
+mod collision;
+mod content;
+mod error;
+mod float;
+mod frozen;
+mod hint;
+mod histogram;
+#[cfg(feature = "unstable-internals")]
+pub mod internals;
 /// A interner implemented with quadratic probing and SIMD lookup.
 pub mod interner;
+mod keyed;
+mod make_timer;
+mod memoizer;
 mod meta_data;
+mod new_value_hook;
+mod normalizing;
+mod owning_interner;
+mod payload;
+mod preset;
 mod raw_interner;
+mod read_sampler;
+#[cfg(feature = "replay")]
+pub mod replay;
+#[cfg(not(feature = "replay"))]
+mod replay;
+mod replicated;
+mod sanitizer;
+#[cfg(feature = "serde")]
+mod serde_impls;
+pub mod serialized;
+mod session;
+mod side_table;
+#[cfg(any(feature = "checked", feature = "unstable-internals"))]
+mod slot_state;
+mod stable_id;
+pub mod stats;
+mod string_interner;
+mod stuck_transfer;
+#[cfg(feature = "timestamps")]
+mod timestamped;
+mod written_slots;
 
-pub use crate::interner::Interner;
+pub use crate::arc_interner::ArcInterner;
+pub use crate::arena::Arena;
+pub use crate::arena_interner::ArenaInterner;
+pub use crate::builder::{InternerBuilder, ResizePolicy};
+pub use crate::cancellation::CancellationToken;
+pub use crate::capacity_policy::CapacityPolicy;
+pub use crate::collision::{CollisionReport, CollisionThresholds};
+pub use crate::content::{ContentInterner, Verification};
+pub use crate::error::{AlreadyPresent, InternError, MergeError, ValidationError};
+pub use crate::float::{CanonicalF32, CanonicalF64};
+pub use crate::frozen::FrozenInterner;
+pub use crate::histogram::Histogram;
+pub use crate::interner::{DefaultHashBuilder, Interned, Interner, SlotToken, WeakInterner};
+pub use crate::keyed::KeyedInterner;
+pub use crate::memoizer::Memoizer;
+pub use crate::normalizing::{Normalize, NormalizingInterner};
+pub use crate::owning_interner::OwningInterner;
+pub use crate::payload::InternerWithPayload;
+pub use crate::raw_interner::{bucket_index, fingerprint};
+pub use crate::replicated::ReplicatedInterner;
+pub use crate::serialized::{Codec, SerializedInterner};
+pub use crate::session::Session;
+pub use crate::side_table::SideTable;
+pub use crate::stable_id::{IdCollision, StableHasher, StableIds};
+pub use crate::stats::{
+    FragmentationReport, InternerStats, SlowMakeReport, StuckTransfer, TableReport, WaitStats,
+};
+pub use crate::string_interner::{StringInterner, Symbol};
+#[cfg(feature = "timestamps")]
+pub use crate::timestamped::{FirstSeen, TimestampedInterner};
@@ -0,0 +1,66 @@
+use crate::stats::{SlowMake, SlowMakeReport};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Number of slow calls of `make` kept in the report.
+const RECENT_SLOW_MAKES: usize = 64;
+
+/// Measures the calls of `make` done while a slot is locked and records the calls that took
+/// longer than the threshold.
+#[derive(Debug)]
+pub(crate) struct MakeTimer {
+    threshold: Duration,
+    report: Mutex<SlowMakeReportState>,
+}
+
+#[derive(Debug, Default)]
+struct SlowMakeReportState {
+    count: usize,
+    max: Duration,
+    recent: VecDeque<SlowMake>,
+}
+
+impl MakeTimer {
+    pub(crate) fn new(threshold: Duration) -> Self {
+        Self { threshold, report: Mutex::default() }
+    }
+
+    /// Calls `make` and records the call if it is slower than the threshold.
+    #[inline]
+    pub(crate) fn time<R>(&self, hash: u64, make: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let result = make();
+        let duration = start.elapsed();
+        if duration > self.threshold {
+            self.record(SlowMake { hash, duration });
+        }
+        result
+    }
+
+    #[cold]
+    fn record(&self, slow_make: SlowMake) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            hash = slow_make.hash,
+            duration_us = slow_make.duration.as_micros() as u64,
+            "slow interner make"
+        );
+        let mut report = self.report.lock().unwrap();
+        report.count += 1;
+        report.max = report.max.max(slow_make.duration);
+        if report.recent.len() == RECENT_SLOW_MAKES {
+            report.recent.pop_front();
+        }
+        report.recent.push_back(slow_make);
+    }
+
+    pub(crate) fn report(&self) -> SlowMakeReport {
+        let report = self.report.lock().unwrap();
+        SlowMakeReport {
+            count: report.count,
+            max: report.max,
+            recent: report.recent.iter().copied().collect(),
+        }
+    }
+}
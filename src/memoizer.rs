@@ -0,0 +1,125 @@
+use crate::arena::Arena;
+use crate::interner::{DefaultHashBuilder, Interner};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::ptr::NonNull;
+
+pub(crate) struct Entry<K, V> {
+    pub(crate) key: K,
+    pub(crate) value: V,
+}
+
+// hashed and compared by the key only so the entries can be found with the key, points in to the
+// arena of the `Memoizer` or `InternerWithPayload`
+pub(crate) struct EntryRef<K, V>(pub(crate) NonNull<Entry<K, V>>);
+
+impl<K, V> EntryRef<K, V> {
+    #[inline]
+    pub(crate) fn entry(&self) -> &Entry<K, V> {
+        // SAFTY: the entries lives as long as the arena and the `EntryRef`s are only used by the
+        // interner that is stored next to the arena
+        unsafe { self.0.as_ref() }
+    }
+}
+
+unsafe impl<K: Send + Sync, V: Send + Sync> Send for EntryRef<K, V> {}
+unsafe impl<K: Send + Sync, V: Send + Sync> Sync for EntryRef<K, V> {}
+
+impl<K, V> Clone for EntryRef<K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for EntryRef<K, V> {}
+
+impl<K: PartialEq, V> PartialEq for EntryRef<K, V> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.entry().key == other.entry().key
+    }
+}
+
+impl<K: Eq, V> Eq for EntryRef<K, V> {}
+
+impl<K: Hash, V> Hash for EntryRef<K, V> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.entry().key.hash(state)
+    }
+}
+
+impl<K, V> Borrow<K> for EntryRef<K, V> {
+    #[inline]
+    fn borrow(&self) -> &K {
+        &self.entry().key
+    }
+}
+
+/// A concurrent memoization cache built on the [`Interner`].
+///
+/// The value of a key is computed once, other threads that asks for the same key while it is
+/// computed parks until the value is available.
+///
+/// # Examples
+///
+/// ```
+/// use interner::Memoizer;
+///
+/// let memoizer: Memoizer<u64, String> = Memoizer::new();
+/// let value = memoizer.get_or_compute(42, |key| key.to_string());
+/// assert_eq!("42", value);
+/// let value = memoizer.get_or_compute(42, |_| unimplemented!());
+/// assert_eq!("42", value);
+/// ```
+pub struct Memoizer<K, V, S = DefaultHashBuilder> {
+    // the references point in to `arena` and is only handed out with the lifetime of `self`
+    interner: Interner<EntryRef<K, V>, S>,
+    arena: Arena<Entry<K, V>>,
+}
+
+impl<K, V> Memoizer<K, V, DefaultHashBuilder> {
+    /// Creates an empty `Memoizer`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V> Default for Memoizer<K, V, DefaultHashBuilder> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, S> Memoizer<K, V, S> {
+    /// Creates an empty `Memoizer` which will use the given hash builder to hash the keys.
+    #[inline]
+    pub const fn with_hasher(hash_builder: S) -> Self {
+        Self { interner: Interner::with_hasher(hash_builder), arena: Arena::new() }
+    }
+}
+
+impl<K, V, S> Memoizer<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Returns the value of `key`, `compute` is only called if the value is not already
+    /// computed.
+    ///
+    /// # Panics
+    ///
+    /// If `compute` panics the slot of the key is poisoned and the next call with the key
+    /// computes the value again.
+    pub fn get_or_compute(&self, key: K, compute: impl FnOnce(&K) -> V) -> &V {
+        let entry = self.interner.intern(key, |key| {
+            let value = compute(&key);
+            EntryRef(self.arena.alloc(Box::new(Entry { key, value })))
+        });
+        // SAFTY: the entry lives as long as `self`
+        unsafe { &(*entry.0.as_ptr()).value }
+    }
+}
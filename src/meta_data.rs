@@ -1,10 +1,9 @@
 use crate::bitmask::BitMaskIter;
-use std::{
-    simd::{u8x8, SimdPartialEq, ToBitMask},
-    sync::atomic::Ordering,
-};
+#[cfg(feature = "nightly")]
+use std::simd::{u8x8, SimdPartialEq, ToBitMask};
+use std::sync::atomic::Ordering;
 
-pub(crate) trait MetaDataHandling {
+pub trait MetaDataHandling {
     fn load_meta_data(&self, order: Ordering) -> MetaData;
     fn store_moved_flag_to_meta_data(&self, order: Ordering) -> MetaData;
     fn compare_exchange_weak_meta_data(
@@ -27,33 +26,54 @@ pub(crate) trait MetaDataHandling {
 ///      1      |     0      | invalid state, The mutex is not locked and one or more thread is
 ///             |            | parked or about to park. When the lock is released it is not
 ///             |            | setting the LOCKED_BIT as the lock shall not be retaken.
+///             |            | Except when all other bits of the slot are 0, then the slot is
+///             |            | poisoned, the insert that locked it gave up without a value and
+///             |            | the slot is never used again.
 /// ------------+------------+------------------------------------------------------------------
 ///      1      |     1      | The mutex is locked by exactly one thread. One or more thread is
 ///             |            | parked or about to park, waiting for the lock to become available.
 ///
 /// |1 group moved bit | 7 slot valid bits| 7 slots of 8 bit h2 hash or 6 bit h2 hash and lock bits when valid bit unset
-pub(crate) struct MetaData(u64);
+///
+/// The layout is defined on the value of the `u64`, slot `k` uses bits `8 * k..8 * k + 8`
+/// independent of the byte order of the target. The SIMD matching splits the value with
+/// `to_le_bytes` so lane `k` is slot `k` also on big-endian targets. Without the `nightly`
+/// feature the lanes are compared one by one instead.
+///
+/// The valid bit is set with release ordering after the value is written to the slot, so a slot
+/// is only allowed to be read when the valid bit is set in a `MetaData` that was read with acquire
+/// ordering. All snapshots used to find slots to read comes from an acquire load, the acquire
+/// failure ordering of a compare exchange or a relaxed load followed by an acquire fence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MetaData(u64);
 
 impl MetaData {
     /// This bit is set instead of h2 if valid bit is not set when that mutex is locked by some thread.
     pub const LOCKED_BIT: u8 = 0x80;
     const PARK_BIT: u8 = 0x40;
+    const POISONED: u8 = Self::PARK_BIT;
     const VALID_BIT_MASK: u8 = 0x7F;
 
     const GROUP_FULL_BIT_MASK: u64 = 0xFE00_0000_0000_0000;
     const GROUP_MOVED_BIT_MASK: u64 = 0x0100_0000_0000_0000;
 
     #[inline]
-    pub(crate) fn new(meta_data: u64) -> Self {
+    pub fn new(meta_data: u64) -> Self {
         Self(meta_data)
     }
 
+    /// Returns the `u64` value of the meta data.
+    #[inline]
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
     #[inline]
     fn valid_bit(index: usize) -> u64 {
         1 << (64 - 7 + index)
     }
     #[inline]
-    pub(crate) fn test_valid_bit(&self, index: usize) -> bool {
+    pub fn test_valid_bit(&self, index: usize) -> bool {
         self.0 & Self::valid_bit(index) != 0
     }
     #[inline]
@@ -62,7 +82,7 @@ impl MetaData {
     }
 
     #[inline]
-    pub(crate) fn h2_from_meta(&self, index: usize) -> u8 {
+    pub fn h2_from_meta(&self, index: usize) -> u8 {
         (self.0 >> (8 * index)) as u8
     }
 
@@ -71,17 +91,17 @@ impl MetaData {
         (Self::LOCKED_BIT as u64) << (8 * index)
     }
     #[inline]
-    pub(crate) fn test_lock_bit(&self, index: usize) -> bool {
+    pub fn test_lock_bit(&self, index: usize) -> bool {
         self.0 & Self::lock_bit(index) != 0
     }
 
     #[inline]
-    pub(crate) fn lock(&self, h2: u8, index: usize) -> Self {
+    pub fn lock(&self, h2: u8, index: usize) -> Self {
         Self(self.0 | Self::h2_bits(h2 & 0x3F | Self::LOCKED_BIT, index))
     }
 
     #[inline]
-    pub(crate) fn unlock(&self, h2: u8, index: usize) -> Self {
+    pub fn unlock(&self, h2: u8, index: usize) -> Self {
         Self(
             (self.0 & !Self::h2_bits(0xff, index))
                 | Self::valid_bit(index)
@@ -90,59 +110,123 @@ impl MetaData {
     }
 
     #[inline]
-    pub(crate) fn park_bit(index: usize) -> u64 {
+    pub fn park_bit(index: usize) -> u64 {
         (Self::PARK_BIT as u64) << (8 * index)
     }
     #[inline]
-    pub(crate) fn test_park_bit(&self, index: usize) -> bool {
+    pub fn test_park_bit(&self, index: usize) -> bool {
         self.0 & Self::park_bit(index) != 0
     }
     #[inline]
-    pub(crate) fn park(&self, index: usize) -> Self {
+    pub fn park(&self, index: usize) -> Self {
         Self(self.0 | MetaData::park_bit(index))
     }
+    /// Returns the meta data with slot `index` poisoned, replacing the lock and park bits.
+    #[inline]
+    pub fn poison(&self, index: usize) -> Self {
+        Self((self.0 & !Self::h2_bits(0xff, index)) | Self::h2_bits(Self::POISONED, index))
+    }
     #[inline]
-    pub(crate) fn bucket_moved(&self) -> bool {
+    pub fn test_poisoned(&self, index: usize) -> bool {
+        !self.test_valid_bit(index) && self.h2_from_meta(index) == Self::POISONED
+    }
+    /// Returns the meta data with the group moved bit set, as set by
+    /// `MetaDataHandling::store_moved_flag_to_meta_data`.
+    #[cfg(any(feature = "checked", feature = "unstable-internals"))]
+    #[inline]
+    pub fn moved(&self) -> Self {
+        Self(self.0 | Self::GROUP_MOVED_BIT_MASK)
+    }
+    #[inline]
+    pub fn bucket_moved(&self) -> bool {
         self.0 & Self::GROUP_MOVED_BIT_MASK == Self::GROUP_MOVED_BIT_MASK
     }
     #[inline]
-    pub(crate) fn bucket_full(&self) -> bool {
+    pub fn bucket_full(&self) -> bool {
         self.0 & Self::GROUP_FULL_BIT_MASK == Self::GROUP_FULL_BIT_MASK
     }
 
     #[inline]
-    pub(crate) fn get_valid_bits(&self) -> u8 {
+    pub fn get_valid_bits(&self) -> u8 {
         ((self.0 & Self::GROUP_FULL_BIT_MASK) >> (64 - 7)) as u8
     }
 
     #[inline]
-    pub(crate) fn valid_indexes_iter(&self) -> BitMaskIter {
+    pub fn valid_indexes_iter(&self) -> BitMaskIter {
         BitMaskIter::new(self.get_valid_bits())
     }
 
+    /// Returns a `BitMaskIter` over the slots that is not valid, starting at slot `start` and
+    /// wrapping around so threads that starts at different slots tries to reserve different
+    /// slots first.
     #[inline]
-    pub(crate) fn not_valid_indexes_iter(&self) -> BitMaskIter {
-        BitMaskIter::new(!self.get_valid_bits() & Self::VALID_BIT_MASK)
+    pub fn not_valid_indexes_iter_from(&self, start: usize) -> BitMaskIter {
+        BitMaskIter::new_rotated(!self.get_valid_bits() & Self::VALID_BIT_MASK, start)
     }
 
     /// Returns a `BitMaskIter` indicating all hash bytes in the group which have
     /// the given value.
     #[inline]
-    pub(crate) fn match_indexes_iter(&self, value: u8) -> BitMaskIter {
-        let hashes = u8x8::from_slice(&self.0.to_ne_bytes());
-        let values = u8x8::splat(value);
-        BitMaskIter::new(hashes.simd_eq(values).to_bitmask() & self.get_valid_bits())
+    pub fn match_indexes_iter(&self, value: u8) -> BitMaskIter {
+        BitMaskIter::new(eq_mask(self.0, value) & self.get_valid_bits())
     }
 
-    pub(crate) fn count_locked_slots(&self) -> isize {
-        let hashes = u8x8::from_slice(&self.0.to_ne_bytes());
-        const NOT_USED: u8x8 = u8x8::from_slice(&0x0u64.to_ne_bytes());
+    /// Returns a `BitMaskIter` indicating all slots in the group that is locked by an insert
+    /// of a value with the same 6 bit h2 as the given value, independent of the park bit.
+    #[inline]
+    pub fn match_locked_indexes_iter(&self, h2: u8) -> BitMaskIter {
+        let hashes = self.0 & u64::from_le_bytes([!Self::PARK_BIT; 8]);
+        BitMaskIter::new(
+            eq_mask(hashes, h2 & 0x3F | Self::LOCKED_BIT)
+                & !self.get_valid_bits()
+                & Self::VALID_BIT_MASK,
+        )
+    }
+
+    /// Returns true if a slot in the bucket is neither valid nor locked.
+    ///
+    /// An insert only continues to the next bucket of the probe sequence when no slot of the
+    /// bucket is empty, and a slot never becomes empty again, so a lookup can stop at the first
+    /// bucket with an empty slot. A bucket that is not full because of locked or poisoned slots
+    /// is not enough, the value can have been inserted after it while the slots were locked.
+    #[inline]
+    pub fn has_empty_slot(&self) -> bool {
+        (eq_mask(self.0, 0) & !self.get_valid_bits() & Self::VALID_BIT_MASK) != 0
+    }
+
+    /// Returns a mask of the slots with bits that no sequence of transitions from an empty
+    /// bucket reaches, a slot that is neither valid nor locked must be empty or poisoned, e.g. a
+    /// park bit without the lock bit is only allowed as the poisoned state.
+    #[inline]
+    pub fn unreachable_slots(&self) -> u8 {
+        let unlocked = eq_mask(self.0 & u64::from_le_bytes([Self::LOCKED_BIT; 8]), 0);
+        let empty_or_poisoned = eq_mask(self.0, 0) | eq_mask(self.0, Self::POISONED);
+        unlocked & !empty_or_poisoned & !self.get_valid_bits() & Self::VALID_BIT_MASK
+    }
 
-        (hashes.simd_ne(NOT_USED).to_bitmask() & !self.get_valid_bits() & Self::VALID_BIT_MASK)
-            .count_ones() as isize
+    pub fn count_locked_slots(&self) -> isize {
+        let not_locked = eq_mask(self.0, 0) | eq_mask(self.0, Self::POISONED);
+        (!not_locked & !self.get_valid_bits() & Self::VALID_BIT_MASK).count_ones() as isize
     }
 }
 
+/// Returns a mask with bit `k` set if byte `k` of `bits` is `value`.
+#[cfg(feature = "nightly")]
+#[inline]
+fn eq_mask(bits: u64, value: u8) -> u8 {
+    u8x8::from_array(bits.to_le_bytes()).simd_eq(u8x8::splat(value)).to_bitmask()
+}
+
+/// Returns a mask with bit `k` set if byte `k` of `bits` is `value`.
+#[cfg(not(feature = "nightly"))]
+#[inline]
+fn eq_mask(bits: u64, value: u8) -> u8 {
+    bits.to_le_bytes()
+        .iter()
+        .enumerate()
+        .fold(0, |mask, (k, &byte)| mask | (u8::from(byte == value) << k))
+}
+
 impl MetaDataHandling for std::sync::atomic::AtomicU64 {
     fn load_meta_data(&self, order: Ordering) -> MetaData {
         MetaData::new(self.load(order))
@@ -159,7 +243,11 @@ impl MetaDataHandling for std::sync::atomic::AtomicU64 {
         success: Ordering,
         failure: Ordering,
     ) -> bool {
-        match self.compare_exchange_weak(current.0, new.0, success, failure) {
+        if crate::chaos::cas_failure() {
+            *current = MetaData::new(self.load(failure));
+            return false;
+        }
+        match self.compare_exchange_weak(current.bits(), new.bits(), success, failure) {
             Ok(_) => {
                 *current = new;
                 true
@@ -0,0 +1,28 @@
+use std::mem::ManuallyDrop;
+
+/// Calls the hook of `InternerBuilder::build_with_on_new_value` with each value that is inserted
+/// in to the interner.
+pub(crate) struct NewValueHook<T> {
+    // the hook is `'static`, so dropping it never accesses values of `T` that have been dropped
+    hook: ManuallyDrop<Box<dyn Fn(T) + Send + Sync>>,
+}
+
+impl<T> NewValueHook<T> {
+    pub(crate) fn new(hook: impl Fn(T) + Send + Sync + 'static) -> Self {
+        Self { hook: ManuallyDrop::new(Box::new(hook)) }
+    }
+
+    #[inline]
+    pub(crate) fn call(&self, value: T) {
+        (self.hook)(value);
+    }
+}
+
+drop_may_dangle! {
+    impl<T> Drop for NewValueHook<T> {
+        fn drop(&mut self) {
+            // SAFTY: the hook is only dropped here
+            unsafe { ManuallyDrop::drop(&mut self.hook) };
+        }
+    }
+}
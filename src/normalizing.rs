@@ -0,0 +1,92 @@
+use crate::error::InternError;
+use crate::interner::{DefaultHashBuilder, Interner};
+use std::borrow::{Borrow, Cow};
+use std::hash::{BuildHasher, Hash};
+
+/// Transforms a value to its canonical form before it is hashed and compared.
+pub type Normalize<Q> = for<'q> fn(&'q Q) -> Cow<'q, Q>;
+
+/// An [`Interner`] that normalizes every value before it is interned or looked up.
+///
+/// Values that normalize to the same canonical form are interned as one value, so e.g. unicode
+/// normalization or lowercasing is done in one place and a lookup can not miss because a caller
+/// forgot to normalize. This `struct` is created by [`InternerBuilder::build_normalizing`].
+///
+/// [`InternerBuilder::build_normalizing`]: crate::InternerBuilder::build_normalizing
+///
+/// # Examples
+///
+/// ```
+/// use interner::{InternerBuilder, NormalizingInterner};
+/// use std::borrow::Cow;
+///
+/// let interner: NormalizingInterner<&str, str> =
+///     InternerBuilder::new().build_normalizing(|value: &str| {
+///         if value.chars().any(char::is_uppercase) {
+///             Cow::Owned(value.to_lowercase())
+///         } else {
+///             Cow::Borrowed(value)
+///         }
+///     });
+/// let hello = interner.intern_ref("Hello", |value| Box::leak(value.into()));
+/// assert_eq!("hello", hello);
+/// assert!(std::ptr::eq(hello, interner.intern_ref("HELLO", |_| unreachable!())));
+/// assert!(std::ptr::eq(hello, interner.get("hEllo").unwrap()));
+/// ```
+pub struct NormalizingInterner<T, Q: ?Sized + ToOwned, S = DefaultHashBuilder> {
+    interner: Interner<T, S>,
+    normalize: Normalize<Q>,
+}
+
+impl<T, Q: ?Sized + ToOwned, S> NormalizingInterner<T, Q, S> {
+    pub(crate) fn new(interner: Interner<T, S>, normalize: Normalize<Q>) -> Self {
+        Self { interner, normalize }
+    }
+
+    /// Returns the canonical form of `value`.
+    #[inline]
+    pub fn normalize<'q>(&self, value: &'q Q) -> Cow<'q, Q> {
+        (self.normalize)(value)
+    }
+
+    /// Returns the underlying interner, values interned through it directly are not normalized.
+    #[inline]
+    pub fn interner(&self) -> &Interner<T, S> {
+        &self.interner
+    }
+}
+
+impl<T, Q, S> NormalizingInterner<T, Q, S>
+where
+    T: Eq + Hash + Borrow<Q> + Copy,
+    Q: ?Sized + ToOwned + Hash + Eq,
+    S: BuildHasher,
+{
+    /// Interns the canonical form of `value`, `make` is called with the canonical form if it
+    /// was not already interned, see [`Interner::intern_ref`].
+    pub fn intern_ref(&self, value: &Q, make: impl FnOnce(&Q) -> T) -> T {
+        self.try_intern_ref(value, make).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Interns the canonical form of `value` or returns an error if the interner is full, see
+    /// [`Interner::try_intern_ref`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::MemoryLimit`] if the value is not interned and a resize is needed
+    /// that would pass the limit set with
+    /// [`InternerBuilder::max_memory_bytes`](crate::InternerBuilder::max_memory_bytes).
+    /// Returns [`InternError::ResizeDenied`] if a resize is needed and resizes are denied with
+    /// [`InternerBuilder::deny_resize`](crate::InternerBuilder::deny_resize).
+    pub fn try_intern_ref(&self, value: &Q, make: impl FnOnce(&Q) -> T) -> Result<T, InternError> {
+        let value = self.normalize(value);
+        self.interner.try_intern_ref(&*value, || make(&value))
+    }
+
+    /// Returns the interned value with the same canonical form as `value`.
+    pub fn get(&self, value: &Q) -> Option<T> {
+        let value = self.normalize(value);
+        let hash = self.interner.hasher().hash_one(&*value);
+        self.interner.get_from_hash(hash, |result| (*value).eq(result.borrow())).copied()
+    }
+}
@@ -0,0 +1,163 @@
+use crate::arena::Arena;
+use crate::error::InternError;
+use crate::interner::{DefaultHashBuilder, Interner};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::ptr::NonNull;
+
+// a value in the arena of the `OwningInterner`, hashed and compared by the value
+struct ArenaRef<T: ?Sized>(NonNull<T>);
+
+impl<T: ?Sized> ArenaRef<T> {
+    #[inline]
+    fn get(&self) -> &T {
+        // SAFTY: the arena of the `OwningInterner` outlives the interner of the references
+        unsafe { self.0.as_ref() }
+    }
+}
+
+unsafe impl<T: ?Sized + Send + Sync> Send for ArenaRef<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for ArenaRef<T> {}
+
+impl<T: ?Sized> Clone for ArenaRef<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for ArenaRef<T> {}
+
+impl<T: ?Sized + PartialEq> PartialEq for ArenaRef<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for ArenaRef<T> {}
+
+impl<T: ?Sized + Hash> Hash for ArenaRef<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.get().hash(state)
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for ArenaRef<T> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self.get()
+    }
+}
+
+/// An interner that owns the interned values, a value is copied in to an internal [`Arena`] the
+/// first time it is interned and references to it are handed out with the lifetime of the
+/// interner.
+///
+/// Compared to an [`Interner`] of references the caller does not need to leak the values or
+/// manage an arena of its own, compared to an [`ArenaInterner`](crate::ArenaInterner) the values
+/// are freed together with the interner.
+///
+/// # Examples
+///
+/// ```
+/// use interner::OwningInterner;
+///
+/// let interner: OwningInterner<[u32]> = OwningInterner::new();
+/// let values = vec![1, 2, 3];
+/// let interned = interner.intern_owned(&values[..]);
+/// assert!(std::ptr::eq(interned, interner.intern_owned(&[1, 2, 3][..])));
+/// assert!(!std::ptr::eq(interned, &values[..]));
+/// assert_eq!(1, interner.len());
+/// ```
+///
+/// The interned values can not outlive the interner:
+///
+/// ```compile_fail
+/// use interner::OwningInterner;
+///
+/// let hello = {
+///     let interner: OwningInterner<str> = OwningInterner::new();
+///     interner.intern_owned("hello")
+/// };
+/// assert_eq!("hello", hello);
+/// ```
+pub struct OwningInterner<T: ?Sized, S = DefaultHashBuilder> {
+    // declared before the arena so it is dropped first, the references point in to `arena`
+    interner: Interner<ArenaRef<T>, S>,
+    arena: Arena<T>,
+}
+
+impl<T: ?Sized> OwningInterner<T, DefaultHashBuilder> {
+    /// Creates an empty `OwningInterner`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl<T: ?Sized> Default for OwningInterner<T, DefaultHashBuilder> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ?Sized, S> OwningInterner<T, S> {
+    /// Creates an empty `OwningInterner` which will use the given hash builder to hash the
+    /// values.
+    #[inline]
+    pub const fn with_hasher(hash_builder: S) -> Self {
+        Self { interner: Interner::with_hasher(hash_builder), arena: Arena::new() }
+    }
+
+    /// Returns the number of interned values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.interner.len()
+    }
+
+    /// Returns `true` if no values are interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.interner.is_empty()
+    }
+}
+
+impl<T, S> OwningInterner<T, S>
+where
+    T: ?Sized + ToOwned + Hash + Eq,
+    T::Owned: Into<Box<T>>,
+    S: BuildHasher,
+{
+    /// Interns the value and returns a reference to the interned value, the value is only
+    /// cloned with [`ToOwned`] in to the arena if it was not already interned.
+    pub fn intern_owned(&self, value: &T) -> &T {
+        self.try_intern_owned(value).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Interns the value and returns a reference to the interned value or an error if the
+    /// interner is full, see [`OwningInterner::intern_owned`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::MemoryLimit`] if the value is not interned and a resize is needed
+    /// that would pass the limit set with
+    /// [`InternerBuilder::max_memory_bytes`](crate::InternerBuilder::max_memory_bytes).
+    /// Returns [`InternError::ResizeDenied`] if a resize is needed and resizes are denied with
+    /// [`InternerBuilder::deny_resize`](crate::InternerBuilder::deny_resize).
+    pub fn try_intern_owned(&self, value: &T) -> Result<&T, InternError> {
+        let result = self
+            .interner
+            .try_intern_ref(value, || ArenaRef(self.arena.alloc(value.to_owned().into())))?;
+        // SAFTY: the value lives as long as the arena
+        Ok(unsafe { result.0.as_ref() })
+    }
+
+    /// Returns the interned value equal to `value`, or `None` if it is not interned.
+    pub fn get(&self, value: &T) -> Option<&T> {
+        // SAFTY: the value lives as long as the arena
+        self.interner.get(value).map(|result| unsafe { result.0.as_ref() })
+    }
+}
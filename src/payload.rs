@@ -0,0 +1,128 @@
+use crate::arena::Arena;
+use crate::interner::{DefaultHashBuilder, Interner};
+use crate::memoizer::{Entry, EntryRef};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::sync::OnceLock;
+
+/// An interner that attaches a lazily initialized payload to every distinct key.
+///
+/// The canonical key is interned with the slot locking of the [`Interner`], the payload of a key
+/// is initialized at most once, threads that asks for the payload while it is initialized blocks
+/// until it is available. Replaces a second concurrent map keyed by the interned values.
+///
+/// # Examples
+///
+/// ```
+/// use interner::InternerWithPayload;
+///
+/// let interner: InternerWithPayload<String, usize> = InternerWithPayload::new();
+/// let key = interner.intern("a".to_string());
+/// assert_eq!("a", key);
+/// assert_eq!(None, interner.payload("a"));
+/// assert_eq!(&1, interner.get_or_init_payload("a".to_string(), || 1));
+/// assert_eq!(&1, interner.get_or_init_payload("a".to_string(), || unreachable!()));
+/// assert_eq!(Some(&1), interner.payload("a"));
+/// ```
+pub struct InternerWithPayload<K, P, S = DefaultHashBuilder> {
+    // the references point in to `arena` and is only handed out with the lifetime of `self`
+    interner: Interner<EntryRef<K, OnceLock<P>>, S>,
+    arena: Arena<Entry<K, OnceLock<P>>>,
+}
+
+impl<K, P> InternerWithPayload<K, P, DefaultHashBuilder> {
+    /// Creates an empty `InternerWithPayload`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl<K, P> Default for InternerWithPayload<K, P, DefaultHashBuilder> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, P, S> InternerWithPayload<K, P, S> {
+    /// Creates an empty `InternerWithPayload` which will use the given hash builder to hash the
+    /// keys.
+    #[inline]
+    pub const fn with_hasher(hash_builder: S) -> Self {
+        Self { interner: Interner::with_hasher(hash_builder), arena: Arena::new() }
+    }
+
+    /// Returns the number of interned keys, with or without an initialized payload.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.interner.len()
+    }
+
+    /// Returns `true` if no keys are interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.interner.is_empty()
+    }
+}
+
+impl<K, P, S> InternerWithPayload<K, P, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    #[inline]
+    fn entry(&self, key: K) -> &Entry<K, OnceLock<P>> {
+        let entry = self.interner.intern(key, |key| {
+            EntryRef(self.arena.alloc(Box::new(Entry { key, value: OnceLock::new() })))
+        });
+        // SAFTY: the entry lives as long as `self`
+        unsafe { &*entry.0.as_ptr() }
+    }
+
+    /// Interns `key` without initializing its payload and returns the canonical key.
+    pub fn intern(&self, key: K) -> &K {
+        &self.entry(key).key
+    }
+
+    /// Returns the payload of `key`, `init` is only called if the key has no initialized
+    /// payload.
+    ///
+    /// # Panics
+    ///
+    /// If `init` panics the payload stays uninitialized and the next call initializes it.
+    pub fn get_or_init_payload(&self, key: K, init: impl FnOnce() -> P) -> &P {
+        self.entry(key).value.get_or_init(init)
+    }
+
+    /// Returns the canonical key equal to `key` and its payload, `init` is only called if the
+    /// key has no initialized payload.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use interner::InternerWithPayload;
+    ///
+    /// let interner: InternerWithPayload<String, usize> = InternerWithPayload::new();
+    /// let (key, payload) = interner.get_or_init("ab".to_string(), |key| key.len());
+    /// assert_eq!(("ab", &2), (key.as_str(), payload));
+    /// let (other, _) = interner.get_or_init("ab".to_string(), |_| unreachable!());
+    /// assert!(std::ptr::eq(key, other));
+    /// ```
+    pub fn get_or_init(&self, key: K, init: impl FnOnce(&K) -> P) -> (&K, &P) {
+        let entry = self.entry(key);
+        (&entry.key, entry.value.get_or_init(|| init(&entry.key)))
+    }
+
+    /// Returns the payload of `key` if the key is interned and the payload is initialized.
+    pub fn payload<Q>(&self, key: &Q) -> Option<&P>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.interner.hasher().hash_one(key);
+        let entry = self.interner.get_from_hash(hash, |entry| entry.entry().key.borrow() == key)?;
+        // SAFTY: the entry lives as long as `self`
+        unsafe { (*entry.0.as_ptr()).value.get() }
+    }
+}
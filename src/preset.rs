@@ -0,0 +1,65 @@
+use std::hash::{BuildHasher, Hash};
+
+/// An immutable table of values that are known before the interner is created.
+///
+/// The table is checked before the tables of the interner, the lookups does not use any atomic
+/// operations and the values does not use any space in the tables of the interner.
+pub(crate) struct Preset<T> {
+    // open addressing with linear probing, the length is a power of two and at most half of the
+    // slots are used so a probe always ends at an empty slot
+    slots: Box<[Option<(u64, T)>]>,
+}
+
+impl<T> Preset<T> {
+    pub(crate) fn new(values: impl IntoIterator<Item = T>, hash_builder: &impl BuildHasher) -> Self
+    where
+        T: Hash + Eq,
+    {
+        let values: Vec<T> = values.into_iter().collect();
+        let len = (values.len() * 2).next_power_of_two();
+        let mut slots: Box<[Option<(u64, T)>]> = (0..len).map(|_| None).collect();
+        let mask = len - 1;
+        for value in values {
+            let hash = hash_builder.hash_one(&value);
+            let mut pos = hash as usize & mask;
+            loop {
+                match &slots[pos] {
+                    None => {
+                        slots[pos] = Some((hash, value));
+                        break;
+                    }
+                    // the same value given more than once is only stored once
+                    Some((slot_hash, slot_value)) if *slot_hash == hash && *slot_value == value => {
+                        break;
+                    }
+                    Some(_) => pos = (pos + 1) & mask,
+                }
+            }
+        }
+        Self { slots }
+    }
+
+    /// Returns the value with `hash` that `is_match` accepts.
+    #[inline]
+    pub(crate) fn get(&self, hash: u64, mut is_match: impl FnMut(&T) -> bool) -> Option<&T> {
+        let mask = self.slots.len() - 1;
+        let mut pos = hash as usize & mask;
+        while let Some((slot_hash, value)) = &self.slots[pos] {
+            if *slot_hash == hash && is_match(value) {
+                return Some(value);
+            }
+            pos = (pos + 1) & mask;
+        }
+        None
+    }
+
+    /// Returns the values in the table together with their hashes.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u64, &T)> {
+        self.slots.iter().flatten().map(|(hash, value)| (*hash, value))
+    }
+
+    /// Returns the number of values in the table.
+    pub(crate) fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+}
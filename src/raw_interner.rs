@@ -1,13 +1,22 @@
-use crate::bucket::{Bucket, ReserveResult};
+use crate::bucket::{Bucket, ReserveResult, BUCKET_SLOTS};
+use crate::builder::{ResizePolicy, Settings};
+use crate::error::{InternError, ValidationError};
+use crate::hint::likely;
 use crate::meta_data::MetaData;
+use crate::replay::{self, EventKind};
+use crate::stats::{BucketInfo, SlotInfo, StuckTransfer, TableReport};
+use crate::stuck_transfer::StuckTransferAlert;
+use crate::written_slots::WrittenSlots;
 use std::alloc::{alloc_zeroed, dealloc, handle_alloc_error, Layout};
-use std::borrow::Borrow;
 use std::hash::{BuildHasher, Hash};
-use std::intrinsics::likely;
 use std::marker::PhantomData;
+use std::mem::size_of;
 use std::ptr::NonNull;
-use std::sync::atomic::{AtomicIsize, AtomicPtr, Ordering};
+use std::sync::atomic::{
+    compiler_fence, AtomicBool, AtomicIsize, AtomicPtr, AtomicU32, AtomicU8, Ordering,
+};
 use std::sync::Once;
+use std::time::Instant;
 
 /// Probe sequence based on triangular numbers, which is guaranteed (since our
 /// table size is a power of two) to visit every group of elements exactly once.
@@ -41,7 +50,7 @@ impl Iterator for ProbeSeq {
 
 /// Primary hash function, used to select the initial bucket to probe from.
 #[inline]
-fn h1(hash: u64) -> usize {
+const fn h1(hash: u64) -> usize {
     // On 32-bit platforms we simply ignore the higher hash bits.
     hash as usize
 }
@@ -52,20 +61,116 @@ const HASH_BITS: u32 = if usize::BITS < u64::BITS { usize::BITS } else { u64::BI
 
 /// Secondary hash function, saved in the meta data.
 #[inline]
-fn h2(hash: u64) -> u8 {
+const fn h2(hash: u64) -> u8 {
     // Grab the top 8 bits of the hash.
     (hash >> (HASH_BITS - 8)) as u8
 }
 
+/// Returns the 8 bit fingerprint of `hash` that the interner stores in the meta data of the slot
+/// of a value and compares before calling `Eq`.
+///
+/// Together with [`bucket_index`] this lets pre-filters in front of an interner, like Bloom or
+/// Cuckoo filters, use the same bits of the hash as the interner. Only uses `core`. On 32-bit
+/// platforms the fingerprint is the top bits of the lower 32 bits of the hash.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(target_pointer_width = "64")]
+/// assert_eq!(0xab, interner::fingerprint(0xab00_0000_0000_0000));
+/// ```
+#[inline]
+pub const fn fingerprint(hash: u64) -> u8 {
+    h2(hash)
+}
+
+/// Returns the bucket that a lookup of `hash` starts to probe from in a table with `buckets`
+/// buckets, the tables of a [`FragmentationReport`](crate::FragmentationReport) gives the
+/// number of buckets. Only uses `core`.
+///
+/// # Panics
+///
+/// Panics if `buckets` is not a power of two, the tables always have a power of two buckets.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(5, interner::bucket_index(0x1005, 16));
+/// ```
+#[inline]
+pub const fn bucket_index(hash: u64, buckets: usize) -> usize {
+    assert!(buckets.is_power_of_two(), "the number of buckets must be a power of two");
+    h1(hash) & (buckets - 1)
+}
+
+/// Extra fingerprint used together with h2 when the interner is built with wide h2.
+#[inline]
+fn h2_ext(hash: u64) -> u8 {
+    // Grab the 8 bits below the h2 bits.
+    (hash >> (HASH_BITS - 16)) as u8
+}
+
+/// The parts of a hash that a table uses, computed once per operation and passed to the table
+/// operations so the fingerprints are only derived from the hash in one place.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct HashPair {
+    h1: usize,
+    h2: u8,
+}
+
+impl HashPair {
+    #[inline]
+    pub(crate) const fn new(hash: u64) -> Self {
+        Self { h1: h1(hash), h2: h2(hash) }
+    }
+
+    /// Returns the bits of the hash that the tables use, the full hash on 64-bit platforms.
+    #[inline]
+    pub(crate) const fn hash(self) -> u64 {
+        self.h1 as u64
+    }
+
+    /// Returns the extra fingerprint of the hash used with wide h2.
+    #[inline]
+    fn h2_ext(self) -> u8 {
+        h2_ext(self.hash())
+    }
+}
+
+/// Maps the hash to one of `shards` shards using the bits directly below the h2 and h2_ext bits,
+/// the table index uses the low bits so these are the last bits that the table starts to use.
+#[inline]
+pub(crate) fn shard_hint(hash: u64, shards: usize) -> usize {
+    // the 32 bits below the top 16 bits that is used by h2 and h2_ext
+    let bits = (hash << (u64::BITS - HASH_BITS + 16)) >> 32;
+    ((bits * shards as u64) >> 32) as usize
+}
+
 /// Returns the number of buckets needed to hold the given number of items,
 /// taking the maximum load factor into account.
 ///
-/// Returns `None` if an overflow occurs.
+/// # Panics
+///
+/// Panics if the number of buckets overflows `usize`.
 #[inline]
 fn capacity_to_buckets(cap: usize) -> usize {
     // begin with one empty slot per bucket and there is 7 elemnts per bucket and round up.
-    let adjusted_buckets = (cap + 5) / 6;
-    adjusted_buckets.next_power_of_two()
+    let adjusted_buckets = cap.div_ceil(6);
+    adjusted_buckets.checked_next_power_of_two().expect("Interner capacity overflow")
+}
+
+/// Returns the bytes used by a table with the given number of buckets.
+#[inline]
+fn table_bytes<T>(buckets: usize, settings: &Settings) -> usize {
+    let fingerprint_bytes = if settings.wide_h2 { buckets * BUCKET_SLOTS } else { 0 };
+    let dense_id_bytes = if settings.dense_ids { buckets * BUCKET_SLOTS * 4 } else { 0 };
+    buckets * bucket_bytes::<T>() + fingerprint_bytes + dense_id_bytes
+}
+
+/// Returns the bytes of one bucket and its displacement hint.
+#[inline]
+fn bucket_bytes<T>() -> usize {
+    size_of::<Bucket<T>>() + 1
 }
 
 /// Returns the maximum number of buckets to check before a resize is triggered.
@@ -76,14 +181,18 @@ fn buckets_to_resize_limit(buckets: usize) -> usize {
 
 pub(crate) struct LockedData {
     pos: usize,
+    // the number of buckets from the home bucket on the probe sequence
+    distance: usize,
     index: usize,
     group_meta_data: MetaData,
 }
 pub(crate) enum LockResult<T> {
     ResizeNeeded,
+    TimedOut,
     Moved,
     Locked(LockedData),
-    Found(T),
+    // the value and the bucket and slot it was found in
+    Found(T, usize, usize),
 }
 
 /// A raw hash table with an unsafe API.
@@ -95,6 +204,22 @@ pub(crate) struct RawInterner<T> {
     // Pointer to the array of Buckets
     buckets: *mut Bucket<T>,
 
+    // The h2_ext fingerprints of the slots when the interner is built with wide h2, the
+    // fingerprint of a slot is written before the slot is marked valid.
+    fingerprints: Vec<AtomicU8>,
+
+    // The dense ids of the slots when the interner is built with dense ids, the id of a slot is
+    // written before the slot is marked valid and is moved with the value to the next table.
+    dense_ids: Vec<AtomicU32>,
+
+    // The slots that have been written, checked on every read of a valid slot in debug builds and
+    // with the `checked` feature.
+    written: WrittenSlots,
+
+    // The max distance on the probe sequence from a bucket to the bucket of any value with
+    // the bucket as home bucket, lookups do not need to look further than that.
+    displacements: Vec<AtomicU8>,
+
     // Number of buckets that is checked before the table is resized
     resize_limit: usize,
 
@@ -104,21 +229,57 @@ pub(crate) struct RawInterner<T> {
     // count the slots that have not been moved when the bucket was moved due to the slot was looked at the time of the bucket move
     // when the sum is zero the transfer is compleate and only the new interner needs to be used.
     to_be_moved: AtomicIsize,
+    // when the table was created, only set with a stuck transfer alert
+    created: Option<Instant>,
+    // the stuck transfer alert was called for the transfer from this table
+    stuck_alerted: AtomicBool,
+    // bytes of bucket memory used by this table and all older tables
+    memory_bytes: usize,
+    settings: Settings,
     phantom: PhantomData<T>,
 }
 
-unsafe impl<#[may_dangle] T> Drop for RawInterner<T> {
-    fn drop(&mut self) {
-        if !self.buckets.is_null() {
-            let layout = Layout::array::<Bucket<T>>(self.bucket_mask + 1)
-                .expect("Interner capacity overflow");
-            let ptr = self.buckets as *mut u8;
-            unsafe { dealloc(ptr, layout) };
-            self.buckets = std::ptr::null_mut();
-        }
-        let temp = self.next_raw_interner.load(Ordering::Relaxed);
-        if !temp.is_null() {
-            let _next_raw_internere = unsafe { Box::from_raw(temp) };
+/// Zeroes `len` bytes at `ptr` with volatile writes, so the writes are not removed as dead
+/// stores when the memory is freed after.
+///
+/// # Safety
+///
+/// `ptr` must be valid for writes of `len` bytes.
+unsafe fn secure_zero(ptr: *mut u8, len: usize) {
+    for offset in 0..len {
+        std::ptr::write_volatile(ptr.add(offset), 0);
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+drop_may_dangle! {
+    impl<T> Drop for RawInterner<T> {
+        fn drop(&mut self) {
+            if self.settings.secure_wipe {
+                let fingerprint_bytes = self.fingerprints.len();
+                unsafe { secure_zero(self.fingerprints.as_mut_ptr() as *mut u8, fingerprint_bytes) };
+            }
+            if !self.buckets.is_null() {
+                // `needs_drop` is a constant, for the `Copy` values interned today this is removed at
+                // compile time and dropping a table only frees the memory, no matter the capacity
+                if std::mem::needs_drop::<T>() {
+                    for pos in 0..=self.bucket_mask {
+                        unsafe { self.bucket(pos).drop_slots() };
+                    }
+                }
+                let layout = Layout::array::<Bucket<T>>(self.bucket_mask + 1)
+                    .expect("Interner capacity overflow");
+                let ptr = self.buckets as *mut u8;
+                if self.settings.secure_wipe {
+                    unsafe { secure_zero(ptr, layout.size()) };
+                }
+                unsafe { dealloc(ptr, layout) };
+                self.buckets = std::ptr::null_mut();
+            }
+            let temp = self.next_raw_interner.load(Ordering::Relaxed);
+            if !temp.is_null() {
+                let _next_raw_internere = unsafe { Box::from_raw(temp) };
+            }
         }
     }
 }
@@ -126,18 +287,26 @@ unsafe impl<#[may_dangle] T> Drop for RawInterner<T> {
 impl<T> RawInterner<T> {
     /// Creates a new empty hash table without allocating any memory.
     ///
-    /// In effect this returns a table with exactly 1 bucket. However we can
-    /// leave the data pointer dangling since that bucket is never written to
-    /// due to our load factor forcing us to always have at least 1 free bucket.
+    /// The empty table has no buckets, the data pointer is null and every operation that probes
+    /// the table checks for the empty table first. A lookup misses and an insert needs a resize
+    /// without touching a bucket.
     #[inline]
-    pub fn new() -> Self {
+    pub const fn new(settings: Settings) -> Self {
         Self {
             buckets: std::ptr::null_mut(),
+            fingerprints: Vec::new(),
+            dense_ids: Vec::new(),
+            written: WrittenSlots::empty(),
+            displacements: Vec::new(),
             bucket_mask: 0,
             resize_limit: 0,
-            next_raw_interner: AtomicPtr::default(),
+            next_raw_interner: AtomicPtr::new(std::ptr::null_mut()),
             next_raw_interner_lock: Once::new(),
             to_be_moved: AtomicIsize::new(-1),
+            created: None,
+            stuck_alerted: AtomicBool::new(false),
+            memory_bytes: 0,
+            settings,
             phantom: PhantomData,
         }
     }
@@ -146,7 +315,7 @@ impl<T> RawInterner<T> {
     ///
     /// The control bytes are left uninitialized.
     #[inline]
-    fn new_uninitialized(buckets: usize) -> Self {
+    fn new_uninitialized(buckets: usize, settings: Settings) -> Self {
         debug_assert!(buckets.is_power_of_two());
 
         let layout = Layout::array::<Bucket<T>>(buckets).expect("Interner capacity overflow");
@@ -154,53 +323,170 @@ impl<T> RawInterner<T> {
             buckets: NonNull::new(unsafe { alloc_zeroed(layout) } as *mut Bucket<T>)
                 .unwrap_or_else(|| handle_alloc_error(layout))
                 .as_ptr(),
+            fingerprints: if settings.wide_h2 {
+                (0..buckets * BUCKET_SLOTS).map(|_| AtomicU8::new(0)).collect()
+            } else {
+                Vec::new()
+            },
+            dense_ids: if settings.dense_ids {
+                (0..buckets * BUCKET_SLOTS).map(|_| AtomicU32::new(0)).collect()
+            } else {
+                Vec::new()
+            },
+            written: WrittenSlots::new(buckets),
+            displacements: (0..buckets).map(|_| AtomicU8::new(0)).collect(),
             bucket_mask: buckets - 1,
             resize_limit: buckets_to_resize_limit(buckets),
-            next_raw_interner: AtomicPtr::default(),
+            next_raw_interner: AtomicPtr::new(std::ptr::null_mut()),
             next_raw_interner_lock: Once::new(),
             to_be_moved: AtomicIsize::new(0 - (buckets as isize)),
+            created: settings.stuck_transfer_alert.as_ref().map(|_| Instant::now()),
+            stuck_alerted: AtomicBool::new(false),
+            memory_bytes: table_bytes::<T>(buckets, &settings),
+            settings,
             phantom: PhantomData,
         }
     }
 
     /// Allocates a new hash table with at least enough capacity for inserting
     /// the given number of elements without reallocating.
-    pub fn with_capacity(capacity: usize) -> Self {
+    pub fn with_capacity(capacity: usize, settings: Settings) -> Self {
         if capacity == 0 {
-            Self::new()
+            Self::new(settings)
         } else {
-            Self::new_uninitialized(capacity_to_buckets(capacity))
+            Self::new_uninitialized(capacity_to_buckets(capacity), settings)
         }
     }
 
-    /// Returns an iterator for a probe sequence on the table.
     #[inline]
-    fn probe_seq(&self, hash: u64) -> ProbeSeq {
+    pub(crate) fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    // records the outcome of an operation on the table with the `replay` feature
+    #[inline]
+    fn record(&self, kind: EventKind, hash: HashPair, bucket: Option<usize>, slot: Option<usize>) {
+        let table = self as *const Self as *const ();
+        replay::record(kind, table, self.bucket_mask + 1, hash.hash(), bucket, slot);
+    }
+
+    /// Returns the number of buckets of the table, 0 for the empty table.
+    #[inline]
+    pub(crate) fn buckets(&self) -> usize {
+        if self.is_empty_table() { 0 } else { self.bucket_mask + 1 }
+    }
+
+    /// Returns the capacity that gives a table of the same size as this table.
+    #[inline]
+    pub(crate) fn capacity(&self) -> usize {
+        if self.is_empty_table() { 0 } else { (self.bucket_mask + 1) * 6 }
+    }
+
+    /// Returns true if the slot can contain a value with `hash`, only checks the wide h2
+    /// fingerprint as h2 is already matched. The slot must be valid.
+    #[inline]
+    fn fingerprint_matches(&self, pos: usize, index: usize, hash: HashPair) -> bool {
+        self.fingerprints.is_empty()
+            || self.fingerprints[pos * BUCKET_SLOTS + index].load(Ordering::Relaxed) == hash.h2_ext()
+    }
+
+    /// Returns the max distance on the probe sequence to a value with the same home bucket as
+    /// `hash`.
+    #[inline]
+    fn max_displacement(&self, hash: HashPair) -> usize {
+        self.displacements
+            .get(hash.h1 & self.bucket_mask)
+            .map_or(0, |displacement| displacement.load(Ordering::Relaxed) as usize)
+    }
+
+    /// Returns true for the table created by [`RawInterner::new`] that has no buckets.
+    #[inline]
+    fn is_empty_table(&self) -> bool {
+        self.buckets.is_null()
+    }
+
+    /// Returns an iterator for a probe sequence on the table, the table must not be empty.
+    #[inline]
+    fn probe_seq(&self, hash: HashPair) -> ProbeSeq {
+        debug_assert!(!self.is_empty_table(), "probe of the empty table");
         ProbeSeq {
             bucket_mask: self.bucket_mask,
-            pos: h1(hash) & self.bucket_mask,
+            pos: hash.h1 & self.bucket_mask,
             stride: 0,
             resize_limit: self.resize_limit,
         }
     }
 
-    /// Searches for an element in the table and if not found lockes a slot to be able to add the element
+    /// Prefetches the home bucket of `hash` in to the cache.
+    #[inline]
+    pub(crate) fn prefetch(&self, hash: u64) {
+        if self.is_empty_table() {
+            return;
+        }
+        let _bucket = self.bucket(h1(hash) & self.bucket_mask);
+        #[cfg(target_arch = "x86_64")]
+        // SAFTY: prefetching does not access the memory
+        unsafe {
+            use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+            _mm_prefetch::<_MM_HINT_T0>(_bucket as *const Bucket<T> as *const i8);
+        }
+    }
+
+    /// Returns the value of the slot `index` of bucket `pos`, the slot must be valid in
+    /// `group_meta_data` read with acquire ordering. Debug builds and the `checked` feature check
+    /// that the slot is written.
     #[inline]
-    pub(crate) fn lock_or_get_slot<Q: ?Sized>(&self, hash: u64, value: &Q) -> LockResult<T>
+    fn valid_ref_to_slot(&self, pos: usize, group_meta_data: &MetaData, index: usize) -> &T {
+        self.written.assert_written(pos, index);
+        self.bucket(pos).get_valid_ref_to_slot(group_meta_data, index)
+    }
+
+    /// Returns the bucket at `pos`, `pos` must be from the probe sequence of this table.
+    #[inline]
+    fn bucket(&self, pos: usize) -> &Bucket<T> {
+        #[cfg(feature = "checked")]
+        assert!(
+            !self.buckets.is_null() && pos <= self.bucket_mask,
+            "interner bucket {pos} out of bounds for table with {} buckets",
+            self.bucket_mask + 1
+        );
+        // SAFTY: as the index is caped by bucket_mask that is the size of buckets - 1
+        unsafe { &*self.buckets.add(pos) }
+    }
+
+    /// Searches for an element in the table and if not found lockes a slot to be able to add the element.
+    /// Waiting on a slot locked by another thread gives up at `deadline`.
+    #[cfg_attr(not(feature = "no-inline-hot"), inline)]
+    #[cfg_attr(feature = "no-inline-hot", inline(never))]
+    pub(crate) fn lock_or_get_slot(
+        &self,
+        hash: HashPair,
+        is_match: impl Fn(&T) -> bool,
+        deadline: Option<Instant>,
+    ) -> LockResult<T>
     where
-        T: Borrow<Q> + Copy,
-        Q: Eq,
+        T: Copy,
     {
-        let h2 = h2(hash);
-        for pos in self.probe_seq(hash) {
-            // SAFTY: as the index is caped by bucket_mask that is the size of buckets - 1
-            let bucket = unsafe { &*self.buckets.add(pos) };
-            let mut group_meta_data = bucket.get_metadata_acquire();
+        if self.is_empty_table() {
+            self.record(EventKind::ResizeNeeded, hash, None, None);
+            return LockResult::ResizeNeeded;
+        }
+        let h2 = hash.h2;
+        // the number of values with the same h2 that was not a match
+        let mut h2_collisions = 0;
+        for (distance, pos) in self.probe_seq(hash).enumerate() {
+            let bucket = self.bucket(pos);
+            let mut group_meta_data = bucket.get_metadata_acquire(&self.settings);
             for index in group_meta_data.match_indexes_iter(h2) {
-                let result = bucket.get_ref_to_slot(index);
-                if likely((*value).eq((*result).borrow())) {
-                    return LockResult::Found(*result);
+                if !self.fingerprint_matches(pos, index, hash) {
+                    continue;
                 }
+                let result = self.valid_ref_to_slot(pos, &group_meta_data, index);
+                if likely(is_match(result)) {
+                    self.record(EventKind::Found, hash, Some(pos), Some(index));
+                    return LockResult::Found(*result, pos, index);
+                }
+                h2_collisions += 1;
             }
 
             if group_meta_data.bucket_full() {
@@ -211,46 +497,104 @@ impl<T> RawInterner<T> {
             // the bucket was not full when the metadata was fetched but new values can have been added
             // during the search but even if the metadata have been updated and the index is now used the
             // value needs to be check as it can be the value that shall be added
-            for index in group_meta_data.not_valid_indexes_iter() {
-                match bucket.reserve(&mut group_meta_data, h2, index) {
+            // start at a slot given by h2 so inserts of different values in to the same bucket
+            // tries to reserve different slots first
+            let not_valid_indexes = group_meta_data.not_valid_indexes_iter_from(h2 as usize);
+
+            // if an other thread is inserting a value with the same h2 it is most likely the same value
+            // so park on that slot directly instead of first trying to reserve the free slots
+            for index in group_meta_data.match_locked_indexes_iter(h2) {
+                if !bucket.wait_on_lock_release(
+                    &mut group_meta_data,
+                    index,
+                    &self.settings,
+                    deadline,
+                ) {
+                    self.record(EventKind::TimedOut, hash, Some(pos), Some(index));
+                    return LockResult::TimedOut;
+                }
+                if group_meta_data.test_poisoned(index) {
+                    // the insert gave up, the value can be inserted in to another slot
+                    continue;
+                }
+                let result = self.valid_ref_to_slot(pos, &group_meta_data, index);
+                if likely(is_match(result)) {
+                    self.record(EventKind::Found, hash, Some(pos), Some(index));
+                    return LockResult::Found(*result, pos, index);
+                }
+                h2_collisions += 1;
+            }
+
+            for index in not_valid_indexes {
+                match bucket.reserve(&mut group_meta_data, h2, index, &self.settings) {
                     ReserveResult::Reserved => {
-                        return LockResult::Locked(LockedData { pos, index, group_meta_data });
+                        if let Some(collision_detector) = &self.settings.collision_detector {
+                            collision_detector.record_insert(distance, h2_collisions);
+                        }
+                        self.record(EventKind::Locked, hash, Some(pos), Some(index));
+                        return LockResult::Locked(LockedData {
+                            pos,
+                            distance,
+                            index,
+                            group_meta_data,
+                        });
                     }
                     ReserveResult::AlreadyReservedWithSameH2 => {
-                        bucket.wait_on_lock_release(&mut group_meta_data, index);
-                        let result = bucket.get_ref_to_slot(index);
-                        if likely((*value).eq((*result).borrow())) {
-                            return LockResult::Found(*result);
+                        if !bucket.wait_on_lock_release(
+                            &mut group_meta_data,
+                            index,
+                            &self.settings,
+                            deadline,
+                        ) {
+                            self.record(EventKind::TimedOut, hash, Some(pos), Some(index));
+                            return LockResult::TimedOut;
+                        }
+                        if group_meta_data.test_poisoned(index) {
+                            continue;
+                        }
+                        let result = self.valid_ref_to_slot(pos, &group_meta_data, index);
+                        if likely(is_match(result)) {
+                            self.record(EventKind::Found, hash, Some(pos), Some(index));
+                            return LockResult::Found(*result, pos, index);
                         }
+                        h2_collisions += 1;
                         continue;
                     }
-                    ReserveResult::AlreadyReservedWithOtherH2 => {
+                    ReserveResult::AlreadyReservedWithOtherH2 | ReserveResult::Poisoned => {
                         // needs to continue checking as there can still be slots matching
                         continue;
                     }
                     ReserveResult::SlotAvailableButGroupMoved => {
+                        self.record(EventKind::Moved, hash, Some(pos), Some(index));
                         return LockResult::Moved;
                     }
                     ReserveResult::OccupiedWithSameH2 => {
-                        let result = bucket.get_ref_to_slot(index);
-                        if likely((*value).eq((*result).borrow())) {
-                            return LockResult::Found(*result);
+                        let result = self.valid_ref_to_slot(pos, &group_meta_data, index);
+                        if likely(is_match(result)) {
+                            self.record(EventKind::Found, hash, Some(pos), Some(index));
+                            return LockResult::Found(*result, pos, index);
                         }
+                        h2_collisions += 1;
                         continue;
                     }
                 }
             }
         }
+        self.record(EventKind::ResizeNeeded, hash, None, None);
         LockResult::ResizeNeeded
     }
 
     // the value is not allowed to be in this instance of 'RawInterner' and no other thread is allowed to try to intern it
     // this function is used for resize and the value is then in the previous instance of 'RawInterner'.
-    fn lock_slot_for_transfer(&self, h2: u8, hash: u64) -> LockResult<T> {
-        for pos in self.probe_seq(hash) {
-            // SAFTY: as the index is caped by bucket_mask that is the size of buckets - 1
-            let bucket = unsafe { &*self.buckets.add(pos) };
-            let mut group_meta_data = bucket.get_metadata_acquire();
+    #[cold]
+    fn lock_slot_for_transfer(&self, hash: HashPair) -> LockResult<T> {
+        if self.is_empty_table() {
+            return LockResult::ResizeNeeded;
+        }
+        let h2 = hash.h2;
+        for (distance, pos) in self.probe_seq(hash).enumerate() {
+            let bucket = self.bucket(pos);
+            let mut group_meta_data = bucket.get_metadata_acquire(&self.settings);
 
             if group_meta_data.bucket_full() {
                 // this bucket is full try the next bucket
@@ -260,17 +604,23 @@ impl<T> RawInterner<T> {
             // the bucket was not full when the metadata was fetched but new values can have been added
             // during the search but even if the metadata have been updated and the index is now used the
             // value needs to be check as it can be the value that shall be added
-            for index in group_meta_data.not_valid_indexes_iter() {
-                match bucket.reserve(&mut group_meta_data, h2, index) {
+            for index in group_meta_data.not_valid_indexes_iter_from(h2 as usize) {
+                match bucket.reserve(&mut group_meta_data, h2, index, &self.settings) {
                     ReserveResult::Reserved => {
-                        return LockResult::Locked(LockedData { pos, index, group_meta_data });
+                        return LockResult::Locked(LockedData {
+                            pos,
+                            distance,
+                            index,
+                            group_meta_data,
+                        });
                     }
                     ReserveResult::SlotAvailableButGroupMoved => {
                         return LockResult::Moved; // no need to update this table as as there is a newer table
                     }
                     ReserveResult::AlreadyReservedWithSameH2
                     | ReserveResult::AlreadyReservedWithOtherH2
-                    | ReserveResult::OccupiedWithSameH2 => {
+                    | ReserveResult::OccupiedWithSameH2
+                    | ReserveResult::Poisoned => {
                         continue;
                     }
                 }
@@ -280,27 +630,54 @@ impl<T> RawInterner<T> {
     }
 
     /// Searches for an element in the table
-    #[inline]
+    #[cfg_attr(not(feature = "no-inline-hot"), inline)]
+    #[cfg_attr(feature = "no-inline-hot", inline(never))]
     pub(crate) fn get(
         &self,
         hash: u64,
         is_match: &mut dyn FnMut(&T) -> bool,
     ) -> Option<Option<&T>> {
-        let h2 = h2(hash);
+        self.get_slot(hash, is_match).map(|slot| slot.map(|(_, _, result)| result))
+    }
 
-        for pos in self.probe_seq(hash) {
-            // SAFTY: as the index is caped by bucket_mask that is the size of buckets - 1
-            let bucket = unsafe { &*self.buckets.add(pos) };
-            let group_meta_data = bucket.get_metadata_acquire();
+    /// Same as `get` but also returns the bucket and the index of the slot of the value.
+    #[inline]
+    pub(crate) fn get_slot(
+        &self,
+        hash: u64,
+        is_match: &mut dyn FnMut(&T) -> bool,
+    ) -> Option<Option<(usize, usize, &T)>> {
+        let hash = HashPair::new(hash);
+        if self.is_empty_table() {
+            self.record(EventKind::GetMiss, hash, None, None);
+            return if self.next_raw_interner_lock.is_completed() { None } else { Some(None) };
+        }
+        let h2 = hash.h2;
+        // only loaded when the home bucket has no empty slot, so a lookup that ends in the home
+        // bucket only reads the cache line of the bucket
+        let mut max_displacement = None;
+
+        for (distance, pos) in self.probe_seq(hash).enumerate() {
+            let bucket = self.bucket(pos);
+            let group_meta_data = bucket.get_metadata_acquire(&self.settings);
             for index in group_meta_data.match_indexes_iter(h2) {
-                let result = bucket.get_ref_to_slot(index);
+                if !self.fingerprint_matches(pos, index, hash) {
+                    continue;
+                }
+                let result = self.valid_ref_to_slot(pos, &group_meta_data, index);
                 if is_match(result) {
-                    return Some(Some(result));
+                    self.record(EventKind::GetHit, hash, Some(pos), Some(index));
+                    return Some(Some((pos, index, result)));
                 }
             }
 
-            if group_meta_data.bucket_full() {
-                // not found in this bucket and the bucket is full try the next bucket
+            if !group_meta_data.has_empty_slot() {
+                if distance >= *max_displacement.get_or_insert_with(|| self.max_displacement(hash))
+                {
+                    // no value with the same home bucket is further away
+                    break;
+                }
+                // not found in this bucket and the bucket has no empty slot try the next bucket
                 continue;
             }
 
@@ -309,12 +686,294 @@ impl<T> RawInterner<T> {
             }
             break;
         }
+        self.record(EventKind::GetMiss, hash, None, None);
         if self.next_raw_interner_lock.is_completed() { None } else { Some(None) }
     }
 
+    /// Returns the dense id of a valid slot, `None` if the interner is not built with dense ids.
+    #[inline]
+    pub(crate) fn dense_id(&self, pos: usize, index: usize) -> Option<u32> {
+        self.dense_ids.get(pos * BUCKET_SLOTS + index).map(|id| id.load(Ordering::Relaxed))
+    }
+
+    /// Calls `f` with the state of the buckets a lookup of `hash` checks in this table.
+    pub(crate) fn visit_probe_seq<'a>(
+        &'a self,
+        hash: u64,
+        table: usize,
+        f: &mut dyn FnMut(BucketInfo<'a, T>),
+    ) {
+        if self.is_empty_table() {
+            return;
+        }
+        for pos in self.probe_seq(HashPair::new(hash)) {
+            let bucket = self.bucket(pos);
+            let group_meta_data = bucket.get_metadata_acquire(&self.settings);
+            let slots = std::array::from_fn(|index| {
+                if group_meta_data.test_valid_bit(index) {
+                    SlotInfo::Valid {
+                        h2: group_meta_data.h2_from_meta(index),
+                        value: self.valid_ref_to_slot(pos, &group_meta_data, index),
+                    }
+                } else if group_meta_data.h2_from_meta(index) == 0 {
+                    SlotInfo::Empty
+                } else if group_meta_data.test_poisoned(index) {
+                    SlotInfo::Poisoned
+                } else {
+                    SlotInfo::Locked {
+                        h2: group_meta_data.h2_from_meta(index) & 0x3F,
+                        parked: group_meta_data.test_park_bit(index),
+                    }
+                }
+            });
+            f(BucketInfo { table, pos, moved: group_meta_data.bucket_moved(), slots });
+            if group_meta_data.has_empty_slot() {
+                break;
+            }
+        }
+    }
+
+    /// Pushes the values in the buckets that are not moved to `out` until `out` has `n` values,
+    /// starting at slot `index` in bucket `pos`. `pos` and `index` are updated to the slot after
+    /// the last pushed value, `pos` is past the last bucket when the whole table is visited.
+    pub(crate) fn collect_chunk(
+        &self,
+        pos: &mut usize,
+        index: &mut usize,
+        n: usize,
+        out: &mut Vec<T>,
+    ) where
+        T: Copy,
+    {
+        if self.is_empty_table() {
+            return;
+        }
+        while *pos <= self.bucket_mask {
+            let bucket = self.bucket(*pos);
+            let group_meta_data = bucket.get_metadata_acquire(&self.settings);
+            if !group_meta_data.bucket_moved() {
+                for valid_index in group_meta_data.valid_indexes_iter() {
+                    if valid_index < *index {
+                        continue;
+                    }
+                    if out.len() == n {
+                        *index = valid_index;
+                        return;
+                    }
+                    out.push(*self.valid_ref_to_slot(*pos, &group_meta_data, valid_index));
+                }
+            }
+            *pos += 1;
+            *index = 0;
+        }
+    }
+
+    /// Returns the value in a random slot or `None` if the slot is empty, if the random bucket
+    /// is moved a random slot in the next table is used instead.
+    pub(crate) fn random_slot(&self, rng: &mut impl FnMut() -> u64) -> Option<&T> {
+        let mut raw_interner = self;
+        loop {
+            if !raw_interner.is_empty_table() {
+                let random = rng();
+                let pos = h1(random) & raw_interner.bucket_mask;
+                let bucket = raw_interner.bucket(pos);
+                let group_meta_data = bucket.get_metadata_acquire(&raw_interner.settings);
+                if !group_meta_data.bucket_moved() {
+                    // use the high bits for the slot as the low bits selected the bucket
+                    let index = ((random >> 32) % bucket.refs.len() as u64) as usize;
+                    return group_meta_data
+                        .test_valid_bit(index)
+                        .then(|| raw_interner.valid_ref_to_slot(pos, &group_meta_data, index));
+                }
+            }
+            if !raw_interner.next_raw_interner_lock.is_completed() {
+                return None;
+            }
+            raw_interner = raw_interner.get_next_raw_interner();
+        }
+    }
+
     #[cold]
     pub(crate) fn get_next_raw_interner(&self) -> &Self {
-        unsafe { &*self.next_raw_interner.load(Ordering::Acquire) }
+        let next_raw_interner = self.next_raw_interner.load(Ordering::Acquire);
+        #[cfg(feature = "checked")]
+        assert!(!next_raw_interner.is_null(), "interner chain traversal past the newest table");
+        unsafe { &*next_raw_interner }
+    }
+
+    /// Returns true if all values have been moved to the next table.
+    #[inline]
+    pub(crate) fn is_transfer_done(&self) -> bool {
+        self.to_be_moved.load(Ordering::Relaxed) == 0
+    }
+
+    /// Returns the count of the moves to the next table, zero when all values are moved.
+    #[inline]
+    pub(crate) fn to_be_moved(&self) -> isize {
+        self.to_be_moved.load(Ordering::Relaxed)
+    }
+
+    /// Calls the stuck transfer alert if the values are not moved to the next table within the
+    /// timeout of the alert.
+    #[inline]
+    pub(crate) fn check_stuck_transfer(&self) {
+        if let Some(alert) = &self.settings.stuck_transfer_alert {
+            self.check_stuck_transfer_slow(alert);
+        }
+    }
+
+    #[cold]
+    fn check_stuck_transfer_slow(&self, alert: &StuckTransferAlert) {
+        let Some(created) = self.try_get_next_raw_interner().and_then(|next| next.created) else {
+            return;
+        };
+        let elapsed = created.elapsed();
+        if elapsed > alert.timeout
+            && !self.is_transfer_done()
+            && !self.stuck_alerted.swap(true, Ordering::Relaxed)
+        {
+            alert.fire(StuckTransfer {
+                buckets: self.buckets(),
+                to_be_moved: self.to_be_moved(),
+                elapsed,
+            });
+        }
+    }
+
+    /// Returns the next interner if it has been created.
+    pub(crate) fn try_get_next_raw_interner(&self) -> Option<&Self> {
+        if self.next_raw_interner_lock.is_completed() {
+            Some(self.get_next_raw_interner())
+        } else {
+            None
+        }
+    }
+
+    /// Marks the empty slot `index` of bucket `pos` valid without writing a value to it.
+    ///
+    /// # Safety
+    ///
+    /// Reading the slot is undefined behavior without the `checked` feature.
+    #[cfg(feature = "unstable-internals")]
+    pub(crate) unsafe fn publish_unwritten_slot(&self, pos: usize, index: usize) {
+        let bucket = self.bucket(pos);
+        let group_meta_data = bucket.get_metadata_acquire(&self.settings);
+        assert_eq!(
+            Some(crate::slot_state::SlotState::Empty),
+            crate::slot_state::SlotState::decode(&group_meta_data, index),
+            "slot {index} of bucket {pos} is not empty"
+        );
+        bucket.meta_data.fetch_or(group_meta_data.unlock(0, index).bits(), Ordering::Release);
+    }
+
+    /// Detaches the newest table from the chain of tables starting at this table if all values
+    /// of the older tables are moved to it, so the older tables can be dropped. Returns the table
+    /// with the bytes of bucket memory held by the older tables.
+    pub(crate) fn detach_newest_if_moved(&mut self) -> Option<(Box<Self>, usize)> {
+        let mut raw_interner: &Self = self;
+        let mut previous = None;
+        while let Some(next_raw_interner) = raw_interner.try_get_next_raw_interner() {
+            if !raw_interner.is_transfer_done() {
+                return None;
+            }
+            previous = Some(raw_interner);
+            raw_interner = next_raw_interner;
+        }
+        let newest = previous?.next_raw_interner.swap(std::ptr::null_mut(), Ordering::Relaxed);
+        // SAFTY: the table was created with `Box::into_raw` and is no longer owned by the chain
+        let mut newest = unsafe { Box::from_raw(newest) };
+        let bytes = table_bytes::<T>(newest.buckets(), &newest.settings);
+        let older_bytes = newest.memory_bytes - bytes;
+        newest.memory_bytes = bytes;
+        Some((newest, older_bytes))
+    }
+
+    /// Returns a report of the buckets in this table, counting the buckets that have not been
+    /// moved to the next table and the values in them.
+    pub(crate) fn table_report(&self, is_current: bool) -> TableReport {
+        if self.is_empty_table() {
+            return TableReport {
+                buckets: 0,
+                unmoved_buckets: 0,
+                entries: 0,
+                bytes: 0,
+                bucket_bytes: bucket_bytes::<T>(),
+                is_current,
+            };
+        }
+        let buckets = self.bucket_mask + 1;
+        let mut unmoved_buckets = 0;
+        let mut entries = 0;
+        for pos in 0..buckets {
+            let group_meta_data = self.bucket(pos).get_metadata_acquire(&self.settings);
+            if !group_meta_data.bucket_moved() {
+                unmoved_buckets += 1;
+                entries += group_meta_data.get_valid_bits().count_ones() as usize;
+            }
+        }
+        TableReport {
+            buckets,
+            unmoved_buckets,
+            entries,
+            bytes: table_bytes::<T>(buckets, &self.settings),
+            bucket_bytes: bucket_bytes::<T>(),
+            is_current,
+        }
+    }
+
+    /// Returns the number of valid slots in the table, also in the moved buckets.
+    pub(crate) fn valid_slots(&self) -> usize {
+        if self.is_empty_table() {
+            return 0;
+        }
+        (0..=self.bucket_mask)
+            .map(|pos| {
+                let group_meta_data = self.bucket(pos).get_metadata_acquire(&self.settings);
+                group_meta_data.get_valid_bits().count_ones() as usize
+            })
+            .sum()
+    }
+
+    /// Checks the invariants of every bucket of the table, see
+    /// [`Interner::debug_validate`](crate::Interner::debug_validate). The slots of a bucket are
+    /// checked together with the same masks as the lookups, only slots with equal h2 are
+    /// compared.
+    pub(crate) fn validate(&self, table: usize) -> Result<(), ValidationError>
+    where
+        T: Eq,
+    {
+        if self.is_empty_table() {
+            return Ok(());
+        }
+        for bucket in 0..=self.bucket_mask {
+            let group_meta_data = self.bucket(bucket).get_metadata_acquire(&self.settings);
+            let unreachable = group_meta_data.unreachable_slots();
+            if unreachable != 0 {
+                return Err(ValidationError::UnreachableSlotState {
+                    table,
+                    bucket,
+                    index: unreachable.trailing_zeros() as usize,
+                    meta_data: group_meta_data.bits(),
+                });
+            }
+            for first in group_meta_data.valid_indexes_iter() {
+                let h2 = group_meta_data.h2_from_meta(first);
+                let later = group_meta_data.match_indexes_iter(h2).filter(|&second| second > first);
+                for second in later {
+                    if self.valid_ref_to_slot(bucket, &group_meta_data, first)
+                        == self.valid_ref_to_slot(bucket, &group_meta_data, second)
+                    {
+                        return Err(ValidationError::DuplicateValue {
+                            table,
+                            bucket,
+                            first,
+                            second,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
     }
 
     // as the next interner can be moved before the current is moved we need to find the first interner that is not moved
@@ -322,14 +981,11 @@ impl<T> RawInterner<T> {
         let mut moved_interner = self.next_raw_interner.load(Ordering::Acquire);
         loop {
             let raw_interner = unsafe { &*moved_interner };
-            if raw_interner.next_raw_interner_lock.is_completed() {
-                let next_interner = raw_interner.next_raw_interner.load(Ordering::Acquire);
-                let next = unsafe { &*next_interner };
-                if next.to_be_moved.load(Ordering::Relaxed) == 0 {
-                    moved_interner = next_interner;
-                } else {
-                    return moved_interner;
-                }
+            // only skip a table when all of its own values are moved, a newer table can be done
+            // before the values of this table are moved
+            if raw_interner.is_transfer_done() && raw_interner.next_raw_interner_lock.is_completed()
+            {
+                moved_interner = raw_interner.next_raw_interner.load(Ordering::Acquire);
             } else {
                 return moved_interner;
             }
@@ -343,70 +999,290 @@ where
 {
     // unlock the slot by marking the element as valid unparks all threads blocked on this slot
     // and if the bucket is moved transer the value to the new interner also.
-    #[inline]
+    #[cfg_attr(not(feature = "no-inline-hot"), inline)]
+    #[cfg_attr(feature = "no-inline-hot", inline(never))]
     pub(crate) fn unlock_and_set_value(
         &self,
-        hash: u64,
+        hash: HashPair,
         value: T,
+        dense_id: u32,
         locked_data: LockedData,
         hash_builder: &impl BuildHasher,
     ) -> bool {
-        let LockedData { pos, index, group_meta_data } = locked_data;
-        // SAFTY: as the index is caped by bucket_mask that is the size of buckets - 1
-        let bucket = unsafe { &*self.buckets.add(pos) };
+        crate::chaos::unlock_delay();
+        let LockedData { pos, distance, index, group_meta_data } = locked_data;
+        let bucket = self.bucket(pos);
+        if distance != 0 {
+            // published together with the value when the slot is marked valid
+            self.displacements[hash.h1 & self.bucket_mask]
+                .fetch_max(distance as u8, Ordering::Relaxed);
+        }
+        self.written.mark_written(pos, index);
         // SAFTY: as the index is caped
         unsafe { bucket.set_slot(index, value) };
+        if !self.fingerprints.is_empty() {
+            // published together with the value when the slot is marked valid
+            self.fingerprints[pos * BUCKET_SLOTS + index].store(hash.h2_ext(), Ordering::Relaxed);
+        }
+        if !self.dense_ids.is_empty() {
+            // published together with the value when the slot is marked valid
+            self.dense_ids[pos * BUCKET_SLOTS + index].store(dense_id, Ordering::Relaxed);
+        }
 
-        let h2 = h2(hash);
-        if bucket.set_valid_and_unpark(group_meta_data, h2, index) {
-            self.transfer_in_to(value, hash_builder);
+        let is_moved = bucket.set_valid_and_unpark(group_meta_data, hash.h2, index, &self.settings);
+        self.record(EventKind::Unlocked, hash, Some(pos), Some(index));
+        if is_moved {
+            self.transfer_in_to(hash, value, dense_id, hash_builder);
             self.to_be_moved.fetch_sub(1, Ordering::Relaxed) == 1
         } else {
             false
         }
     }
+
+    // gives up the slot locked by `lock_or_get_slot` without a value, the slot is poisoned and
+    // the threads waiting on it retry their inserts. returns true if this finished the move of the
+    // values to the next interner.
+    #[cold]
+    pub(crate) fn unlock_and_poison(&self, hash: HashPair, locked_data: LockedData) -> bool {
+        crate::chaos::unlock_delay();
+        let LockedData { pos, index, group_meta_data, .. } = locked_data;
+        let is_moved = self.bucket(pos).poison_and_unpark(group_meta_data, index, &self.settings);
+        self.record(EventKind::Poisoned, hash, Some(pos), Some(index));
+        // the slot was counted as locked when the bucket was moved
+        is_moved && self.to_be_moved.fetch_sub(1, Ordering::Relaxed) == 1
+    }
+
+    // same as `create_and_stor_next_raw_interner` but fails if the next interner is not created
+    // and creating it would pass the memory limit or resizes are denied.
+    #[cold]
+    pub(crate) fn try_create_and_stor_next_raw_interner(
+        &self,
+        hash_builder: &impl BuildHasher,
+    ) -> Result<bool, InternError> {
+        if self.settings.deny_resize && !self.next_raw_interner_lock.is_completed() {
+            return Err(InternError::ResizeDenied { buckets: self.buckets() });
+        }
+        if let Some(limit) = self.settings.max_memory_bytes {
+            if !self.next_raw_interner_lock.is_completed()
+                && self.memory_bytes
+                    + table_bytes::<T>(self.new_number_of_buckets(), &self.settings)
+                    > limit
+            {
+                return Err(InternError::MemoryLimit { used: self.memory_bytes, limit });
+            }
+        }
+        Ok(self.create_and_stor_next_raw_interner(hash_builder))
+    }
+
     #[cold]
     pub(crate) fn create_and_stor_next_raw_interner(
         &self,
         hash_builder: &impl BuildHasher,
     ) -> bool {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::debug_span!("interner_resize", buckets = self.bucket_mask + 1).entered();
+        self.create_next_raw_interner();
+        self.transfer(self.get_next_raw_interner(), hash_builder)
+    }
+
+    // returns the number of buckets of the next interner, chosen by the `ResizePolicy`
+    fn new_number_of_buckets(&self) -> usize {
+        let buckets = self.bucket_mask + 1;
+        let growth = match self.settings.resize_policy {
+            ResizePolicy::Double => {
+                return buckets.checked_mul(2).expect("Interner capacity overflow");
+            }
+            ResizePolicy::LiveCount => 2,
+            ResizePolicy::Contended => 4,
+        };
+        let live = self.valid_slots().checked_mul(growth).expect("Interner capacity overflow");
+        usize::max(
+            buckets.checked_mul(growth).expect("Interner capacity overflow"),
+            capacity_to_buckets(live),
+        )
+    }
+
+    // creates the next interner if it is not created, without moving any values to it
+    #[cold]
+    pub(crate) fn create_next_raw_interner(&self) {
+        self.create_next_raw_interner_with(|| self.new_number_of_buckets());
+    }
+
+    // same as `create_next_raw_interner` but the number of buckets is given by `buckets`
+    fn create_next_raw_interner_with(&self, buckets: impl FnOnce() -> usize) {
         self.next_raw_interner_lock.call_once(|| {
-            let new_number_of_buckets = (self.bucket_mask + 1) * 2;
-            let raw_interner = Box::new(Self::new_uninitialized(new_number_of_buckets));
+            let new_number_of_buckets = buckets();
+            // set the token before the settings are cloned so all tables shares it
+            self.settings.cancellation_token();
+            let mut raw_interner =
+                Box::new(Self::new_uninitialized(new_number_of_buckets, self.settings.clone()));
+            raw_interner.memory_bytes += self.memory_bytes;
             self.next_raw_interner.store(Box::into_raw(raw_interner), Ordering::Release);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(new_buckets = new_number_of_buckets, "created next interner table");
         });
-        self.transfer(self.get_next_raw_interner(), hash_builder)
     }
 
+    // moves all values to a new next table sized for `capacity` values. returns false if the
+    // table would not be smaller, resizes are denied or the memory limit would be passed, or the
+    // next table was already created by a resize.
+    #[cold]
+    pub(crate) fn compact(&self, capacity: usize, hash_builder: &impl BuildHasher) -> bool {
+        let buckets = capacity_to_buckets(capacity.max(1));
+        if buckets >= self.buckets() || self.settings.deny_resize {
+            return false;
+        }
+        if self.settings.max_memory_bytes.is_some_and(|limit| {
+            self.memory_bytes + table_bytes::<T>(buckets, &self.settings) > limit
+        }) {
+            return false;
+        }
+        let mut is_created = false;
+        self.create_next_raw_interner_with(|| {
+            is_created = true;
+            buckets
+        });
+        if is_created {
+            self.finish_transfer(hash_builder);
+        }
+        is_created
+    }
+
+    #[cold]
     fn transfer(&self, new_raw_interner: &Self, hash_builder: &impl BuildHasher) -> bool {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "interner_transfer",
+            buckets = self.bucket_mask + 1,
+            new_buckets = new_raw_interner.bucket_mask + 1
+        )
+        .entered();
+        let cancellation_token = self.settings.cancellation_token();
+        let _maintenance = cancellation_token.enter_maintenance();
+        if self.is_empty_table() {
+            // the empty table has nothing to move, only the first transfer marks it as done so the
+            // count stays at zero. a table with one bucket has values and is moved as any other.
+            return self
+                .to_be_moved
+                .compare_exchange(-1, 0, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok();
+        }
         let mut to_be_moved = 0;
-        if self.bucket_mask != 0 {
-            for pos in 0..self.bucket_mask + 1 {
-                let bucket = unsafe { &*self.buckets.add(pos) };
-                to_be_moved += bucket.transfer_bucket(new_raw_interner, hash_builder);
+        for pos in 0..self.bucket_mask + 1 {
+            if cancellation_token.is_cancelled() {
+                // the buckets that are not moved are moved by the next transfer
+                break;
             }
-        } else {
-            to_be_moved = 1;
+            to_be_moved += self.transfer_bucket(pos, new_raw_interner, hash_builder);
         }
-        self.to_be_moved.fetch_add(to_be_moved, Ordering::Relaxed) == -to_be_moved
+        let done = self.to_be_moved.fetch_add(to_be_moved, Ordering::Relaxed) == -to_be_moved;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(moved = to_be_moved, done, "transferred buckets");
+        done
+    }
+
+    // moves the values of the bucket at `pos` to the next interner if it is not moved, returns
+    // the count to subtract from `to_be_moved`
+    fn transfer_bucket(
+        &self,
+        pos: usize,
+        new_raw_interner: &Self,
+        hash_builder: &impl BuildHasher,
+    ) -> isize {
+        crate::chaos::transfer_stall();
+        let dense_ids = self.dense_ids.get(pos * BUCKET_SLOTS..(pos + 1) * BUCKET_SLOTS);
+        let to_be_moved = self.bucket(pos).transfer_bucket(
+            new_raw_interner,
+            dense_ids.unwrap_or_default(),
+            |index| self.written.assert_written(pos, index),
+            hash_builder,
+        );
+        if to_be_moved != 0 {
+            self.record(EventKind::BucketMoved, HashPair::new(0), Some(pos), None);
+        }
+        to_be_moved
+    }
+
+    // moves the buckets that are not moved to the next interner and waits until the values
+    // of the inserts in progress in this interner are moved, unless the transfer is cancelled.
+    #[cold]
+    pub(crate) fn finish_transfer(&self, hash_builder: &impl BuildHasher) {
+        self.transfer(self.get_next_raw_interner(), hash_builder);
+        let cancellation_token = self.settings.cancellation_token();
+        while !self.is_transfer_done() && !cancellation_token.is_cancelled() {
+            std::thread::yield_now();
+        }
+    }
+
+    // transfer at most `budget` buckets starting at the home bucket of `hash` to the next interner,
+    // used by lookups to help finishing a resize. returns true if the transfer is done.
+    #[cold]
+    pub(crate) fn help_transfer(
+        &self,
+        hash: u64,
+        budget: usize,
+        hash_builder: &impl BuildHasher,
+    ) -> bool {
+        let start = h1(hash);
+        let positions = (0..usize::min(budget, self.bucket_mask + 1))
+            .map(|offset| (start + offset) & self.bucket_mask);
+        self.transfer_buckets(positions, hash_builder)
+    }
+
+    // transfer the buckets at `positions` to the next interner if it is created. returns true if
+    // the transfer is done.
+    pub(crate) fn transfer_buckets(
+        &self,
+        positions: impl Iterator<Item = usize>,
+        hash_builder: &impl BuildHasher,
+    ) -> bool {
+        if self.is_empty_table() || !self.next_raw_interner_lock.is_completed() {
+            return false;
+        }
+        let new_raw_interner = self.get_next_raw_interner();
+        let cancellation_token = self.settings.cancellation_token();
+        let _maintenance = cancellation_token.enter_maintenance();
+        let mut to_be_moved = 0;
+        for pos in positions {
+            if cancellation_token.is_cancelled() {
+                break;
+            }
+            to_be_moved +=
+                self.transfer_bucket(pos & self.bucket_mask, new_raw_interner, hash_builder);
+        }
+        to_be_moved != 0
+            && self.to_be_moved.fetch_add(to_be_moved, Ordering::Relaxed) == -to_be_moved
     }
 
     // the value is not allowed to be in this instance of 'RawInterner' and no other thread is allowed to try to intern it
     // this function is used for resize and the value is then in the previous instance of 'RawInterner'.
-    pub(crate) fn transfer_in_to(&self, value: T, hash_builder: &impl BuildHasher) {
+    // `hash` is the hash of the value, it is passed along so the value is only hashed once even if it
+    // is moved through several tables.
+    #[cold]
+    pub(crate) fn transfer_in_to(
+        &self,
+        hash: HashPair,
+        value: T,
+        dense_id: u32,
+        hash_builder: &impl BuildHasher,
+    ) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("interner_transfer_in_to", buckets = self.bucket_mask + 1)
+            .entered();
         let mut raw_interner = self;
-        let hash = hash_builder.hash_one(&value);
-        let h2 = h2(hash);
         loop {
-            let lock_result = raw_interner.lock_slot_for_transfer(h2, hash);
+            let lock_result = raw_interner.lock_slot_for_transfer(hash);
             if let LockResult::Locked(locked_data) = lock_result {
-                raw_interner.unlock_and_set_value(hash, value, locked_data, hash_builder);
+                let LockedData { pos, index, .. } = locked_data;
+                raw_interner.record(EventKind::TransferLocked, hash, Some(pos), Some(index));
+                raw_interner.unlock_and_set_value(hash, value, dense_id, locked_data, hash_builder);
                 break;
             }
             if let LockResult::ResizeNeeded = lock_result {
                 raw_interner.create_and_stor_next_raw_interner(hash_builder);
             }
-            debug_assert!(!matches!(lock_result, LockResult::Found(_)));
+            debug_assert!(!matches!(lock_result, LockResult::Found(..)));
             raw_interner = raw_interner.get_next_raw_interner();
         }
     }
@@ -0,0 +1,62 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// One in this many lookups is sampled, picked by the hash bits above the bits used for h1.
+const SAMPLE_RATE: u64 = 64;
+/// The counts are halved when this many hits are sampled, so old samples fade out.
+const WINDOW: u64 = 1024;
+/// The number of sampled hits needed before the start table is changed.
+const MIN_HITS: u64 = 64;
+
+/// Samples in which table the successful lookups find their value, to pick the table the
+/// lookups start in during a resize.
+#[derive(Debug, Default)]
+pub(crate) struct ReadSampler {
+    // the sampled lookups that found the value
+    hits: AtomicU64,
+    // the number of tables after the current table that the sampled lookups checked
+    extra_tables: AtomicU64,
+}
+
+impl ReadSampler {
+    #[inline]
+    pub(crate) fn is_sampled(hash: u64) -> bool {
+        (hash >> 32).is_multiple_of(SAMPLE_RATE)
+    }
+
+    /// Records a lookup that found the value after checking `extra_tables` tables after the
+    /// current table.
+    #[cold]
+    pub(crate) fn record(&self, extra_tables: usize) {
+        let hits = self.hits.fetch_add(1, Ordering::Relaxed) + 1;
+        let extra_tables = self.extra_tables.fetch_add(extra_tables as u64, Ordering::Relaxed)
+            + extra_tables as u64;
+        if hits >= WINDOW {
+            // racing records can be lost, the counts are only an estimate
+            self.hits.store(hits / 2, Ordering::Relaxed);
+            self.extra_tables.store(extra_tables / 2, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the average number of tables after the current table that a lookup checks
+    /// before it finds the value.
+    pub(crate) fn estimate(&self) -> f64 {
+        let hits = self.hits.load(Ordering::Relaxed);
+        if hits == 0 {
+            return 0.0;
+        }
+        self.extra_tables.load(Ordering::Relaxed) as f64 / hits as f64
+    }
+
+    /// Returns true if most lookups find their value in a table after the current table.
+    #[inline]
+    pub(crate) fn starts_in_next(&self) -> bool {
+        let hits = self.hits.load(Ordering::Relaxed);
+        hits >= MIN_HITS && self.extra_tables.load(Ordering::Relaxed) * 2 > hits
+    }
+
+    /// Forgets the samples, called when the current table changes.
+    pub(crate) fn reset(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.extra_tables.store(0, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,239 @@
+//! A log of the outcomes of the table operations for reconstructing the interleaving of threads
+//! after a bug, enabled with the `replay` feature.
+//!
+//! Each thread records its operations in its own ring buffer of the last
+//! [`EVENTS_PER_THREAD`] events, the buffers are only written by the thread that owns them so
+//! recording takes no locks. All events get a number from a global counter so [`trace`] can
+//! merge the buffers of all threads in the order the operations happened. The buffer of a
+//! thread is freed when the thread exits, so its events are no longer in the trace. Without the
+//! feature the recording compiles to nothing.
+
+#[cfg(feature = "replay")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "replay")]
+use std::sync::{Arc, Mutex, Weak};
+
+/// The number of events kept for each thread, older events are overwritten.
+#[cfg(feature = "replay")]
+pub const EVENTS_PER_THREAD: usize = 4096;
+
+/// The outcome of an operation on a slot or bucket of a table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum EventKind {
+    /// An insert found the value in the slot.
+    Found,
+    /// An insert locked the free slot to insert the value in to it.
+    Locked,
+    /// An insert found the bucket moved to the next table.
+    Moved,
+    /// An insert found no free slot and needs the next table.
+    ResizeNeeded,
+    /// The move of a value from an older table locked the slot.
+    TransferLocked,
+    /// A locked slot was given its value and marked as valid.
+    Unlocked,
+    /// The values of the bucket were moved to the next table.
+    BucketMoved,
+    /// A lookup found the value in the slot.
+    GetHit,
+    /// A lookup did not find the value in the table.
+    GetMiss,
+    /// An insert with a deadline gave up waiting on the locked slot.
+    TimedOut,
+    /// An insert failed to make the value and poisoned the locked slot.
+    Poisoned,
+}
+
+#[cfg(feature = "replay")]
+impl EventKind {
+    const ALL: [EventKind; 11] = [
+        EventKind::Found,
+        EventKind::Locked,
+        EventKind::Moved,
+        EventKind::ResizeNeeded,
+        EventKind::TransferLocked,
+        EventKind::Unlocked,
+        EventKind::BucketMoved,
+        EventKind::GetHit,
+        EventKind::GetMiss,
+        EventKind::TimedOut,
+        EventKind::Poisoned,
+    ];
+}
+
+/// A recorded operation, see [`trace`].
+#[cfg(feature = "replay")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// The global order of the event, events with a lower number happened before.
+    pub seq: u64,
+    /// The thread that recorded the event, numbered in the order the threads recorded their
+    /// first event.
+    pub thread: u64,
+    /// The address of the table, to tell the tables and interners apart.
+    pub table: usize,
+    /// The log2 of the number of buckets of the table, the tables of an interner have
+    /// increasing generations.
+    pub generation: u32,
+    /// The hash of the value, 0 for [`EventKind::BucketMoved`].
+    pub hash: u64,
+    /// The bucket of the operation, `None` if the operation did not end at a bucket.
+    pub bucket: Option<usize>,
+    /// The slot of the operation, `None` if the operation did not end at a slot.
+    pub slot: Option<usize>,
+    /// The outcome of the operation.
+    pub kind: EventKind,
+}
+
+#[cfg(feature = "replay")]
+const NONE: u64 = u64::MAX;
+
+// the fields of an event, written by the owning thread and read by `trace`. `seq` is written
+// last with release ordering and is read before and after the other fields, an event that is
+// overwritten while it is read is skipped.
+#[cfg(feature = "replay")]
+#[derive(Default)]
+struct Slot {
+    seq: AtomicU64,
+    table: AtomicU64,
+    hash: AtomicU64,
+    bucket: AtomicU64,
+    slot: AtomicU64,
+    // the generation in the low 32 bits and the kind above them
+    generation_and_kind: AtomicU64,
+}
+
+#[cfg(feature = "replay")]
+struct Ring {
+    thread: u64,
+    // the number of events recorded by the thread
+    head: AtomicU64,
+    slots: Box<[Slot]>,
+}
+
+#[cfg(feature = "replay")]
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(1);
+#[cfg(feature = "replay")]
+static NEXT_THREAD: AtomicU64 = AtomicU64::new(0);
+// the rings are owned by the thread locals and dropped when their threads exit, the entries of
+// exited threads are removed when a ring is added and when a trace is taken
+#[cfg(feature = "replay")]
+static RINGS: Mutex<Vec<Weak<Ring>>> = Mutex::new(Vec::new());
+
+#[cfg(feature = "replay")]
+thread_local! {
+    static RING: Arc<Ring> = {
+        let ring = Arc::new(Ring {
+            thread: NEXT_THREAD.fetch_add(1, Ordering::Relaxed),
+            head: AtomicU64::new(0),
+            slots: (0..EVENTS_PER_THREAD).map(|_| Slot::default()).collect(),
+        });
+        let mut rings = RINGS.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        rings.retain(|ring| ring.strong_count() != 0);
+        rings.push(Arc::downgrade(&ring));
+        ring
+    };
+}
+
+/// Records an event in the ring buffer of the current thread.
+#[inline]
+pub(crate) fn record(
+    kind: EventKind,
+    table: *const (),
+    buckets: usize,
+    hash: u64,
+    bucket: Option<usize>,
+    slot: Option<usize>,
+) {
+    #[cfg(feature = "replay")]
+    {
+        let seq = NEXT_SEQ.fetch_add(1, Ordering::Relaxed);
+        let generation = buckets.max(1).trailing_zeros() as u64;
+        // a thread that is being torn down has no buffer, its event is dropped
+        let _ = RING.try_with(|ring| {
+            let head = ring.head.load(Ordering::Relaxed);
+            let event = &ring.slots[head as usize % EVENTS_PER_THREAD];
+            event.seq.store(0, Ordering::Relaxed);
+            std::sync::atomic::fence(Ordering::Release);
+            event.table.store(table as u64, Ordering::Relaxed);
+            event.hash.store(hash, Ordering::Relaxed);
+            event.bucket.store(bucket.map_or(NONE, |bucket| bucket as u64), Ordering::Relaxed);
+            event.slot.store(slot.map_or(NONE, |slot| slot as u64), Ordering::Relaxed);
+            event.generation_and_kind.store(generation | (kind as u64) << 32, Ordering::Relaxed);
+            event.seq.store(seq, Ordering::Release);
+            ring.head.store(head + 1, Ordering::Release);
+        });
+    }
+    #[cfg(not(feature = "replay"))]
+    let _ = (kind, table, buckets, hash, bucket, slot);
+}
+
+/// Returns the recorded events of all running threads in the order they happened.
+///
+/// The events that are recorded while the trace is taken can be missing from it, and so are the
+/// events of the threads that have exited.
+///
+/// # Examples
+///
+/// ```
+/// use interner::replay::{trace, EventKind};
+/// use interner::Interner;
+///
+/// let interner: Interner<i32> = Interner::new();
+/// interner.intern(1, |value| value);
+/// let events = trace();
+/// assert!(events.iter().any(|event| event.kind == EventKind::Unlocked));
+/// assert!(events.windows(2).all(|pair| pair[0].seq < pair[1].seq));
+/// ```
+#[cfg(feature = "replay")]
+pub fn trace() -> Vec<Event> {
+    let rings: Vec<Arc<Ring>> = {
+        let mut rings = RINGS.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        rings.retain(|ring| ring.strong_count() != 0);
+        rings.iter().filter_map(Weak::upgrade).collect()
+    };
+    let mut events = Vec::new();
+    for ring in rings.iter() {
+        let head = ring.head.load(Ordering::Acquire);
+        let first = head.saturating_sub(EVENTS_PER_THREAD as u64);
+        for index in first..head {
+            let slot = &ring.slots[index as usize % EVENTS_PER_THREAD];
+            let seq = slot.seq.load(Ordering::Acquire);
+            let generation_and_kind = slot.generation_and_kind.load(Ordering::Relaxed);
+            let event = Event {
+                seq,
+                thread: ring.thread,
+                table: slot.table.load(Ordering::Relaxed) as usize,
+                hash: slot.hash.load(Ordering::Relaxed),
+                bucket: Some(slot.bucket.load(Ordering::Relaxed))
+                    .filter(|bucket| *bucket != NONE)
+                    .map(|bucket| bucket as usize),
+                slot: Some(slot.slot.load(Ordering::Relaxed))
+                    .filter(|slot| *slot != NONE)
+                    .map(|slot| slot as usize),
+                generation: generation_and_kind as u32,
+                kind: EventKind::ALL[(generation_and_kind >> 32) as usize],
+            };
+            std::sync::atomic::fence(Ordering::Acquire);
+            if seq != 0 && slot.seq.load(Ordering::Relaxed) == seq {
+                events.push(event);
+            }
+        }
+    }
+    events.sort_unstable_by_key(|event| event.seq);
+    events
+}
+
+/// Forgets the recorded events of all threads, e.g. before the operations of a test.
+///
+/// Events that are recorded while the buffers are cleared can be kept.
+#[cfg(feature = "replay")]
+pub fn clear() {
+    let rings = RINGS.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+    for ring in rings.iter().filter_map(Weak::upgrade) {
+        for slot in ring.slots.iter() {
+            slot.seq.store(0, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use crate::frozen::FrozenInterner;
+use crate::interner::{DefaultHashBuilder, Interner};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, RwLock};
+
+/// The default of the number of inserts between the rebuilds of the replicas.
+const DEFAULT_REBUILD_THRESHOLD: usize = 1024;
+
+/// The replicas are not rebuilt before the inserts since the last rebuild are at least this
+/// fraction of the values, so the copies of the rebuilds are amortized over the inserts.
+const REBUILD_LEN_DIVISOR: usize = 8;
+
+/// The replica used by the next thread that does a lookup.
+static NEXT_REPLICA: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // the replica of the thread, the threads are spread over the replicas in the order they do
+    // their first lookup
+    static REPLICA: usize = NEXT_REPLICA.fetch_add(1, Ordering::Relaxed);
+}
+
+// a copy of the values for the threads that use the replica, aligned so the lock of one replica
+// never shares a cache line with another replica
+#[repr(align(128))]
+struct Replica<T, S> {
+    snapshot: RwLock<FrozenInterner<T, S>>,
+}
+
+/// An interner for lookups from many cores where almost all values are already interned.
+///
+/// Every replica holds a [`FrozenInterner`] copy of the values, a lookup first checks the
+/// replica of its thread and only checks the shared [`Interner`] when the value is not in the
+/// replica. The threads are spread over the replicas in the order of their first lookup, not by
+/// the core they run on. With at most one thread per replica the lookups of a thread only touch
+/// cache lines that no other core writes to, except while a replica is replaced, with more
+/// threads than replicas the threads that share a replica also share the cache line of its lock.
+///
+/// Inserts go to the shared interner, the replicas are rebuilt from it by the insert that passes
+/// the rebuild threshold, so new values are found in the shared interner until the next rebuild.
+/// The threshold grows to an eighth of the values, so a rebuild that copies all values to every
+/// replica is done at most once per that many inserts. The replicas use memory for one copy of
+/// the values each.
+///
+/// # Examples
+///
+/// ```
+/// use interner::ReplicatedInterner;
+///
+/// let values: Vec<u64> = (0..100).collect();
+/// let interner: ReplicatedInterner<&u64> = ReplicatedInterner::with_replicas(4, 10);
+/// for value in values.iter() {
+///     interner.intern_ref(value, || value);
+/// }
+/// assert_eq!(Some(&values[42]), interner.get(&42));
+/// assert_eq!(None, interner.get(&100));
+/// interner.refresh();
+/// assert_eq!(0, interner.pending_writes());
+/// ```
+pub struct ReplicatedInterner<T, S = DefaultHashBuilder> {
+    interner: Interner<T, S>,
+    replicas: Box<[Replica<T, S>]>,
+    rebuild_threshold: usize,
+    // the number of values inserted since the replicas were rebuilt
+    pending_writes: AtomicUsize,
+    // held while the replicas are rebuilt so only one insert rebuilds them
+    rebuild: Mutex<()>,
+}
+
+impl<T> ReplicatedInterner<T, DefaultHashBuilder> {
+    /// Creates an empty `ReplicatedInterner` with one replica per core as reported by
+    /// [`std::thread::available_parallelism`], rebuilt after 1024 inserts or an eighth of the
+    /// values, whichever is more.
+    #[inline]
+    pub fn new() -> Self {
+        let replicas = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+        Self::with_replicas(replicas, DEFAULT_REBUILD_THRESHOLD)
+    }
+
+    /// Creates an empty `ReplicatedInterner` with `replicas` replicas that are rebuilt after
+    /// `rebuild_threshold` inserts or an eighth of the values, whichever is more.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `replicas` is 0.
+    #[inline]
+    pub fn with_replicas(replicas: usize, rebuild_threshold: usize) -> Self {
+        Self::with_replicas_and_hasher(replicas, rebuild_threshold, DefaultHashBuilder::default())
+    }
+}
+
+impl<T> Default for ReplicatedInterner<T, DefaultHashBuilder> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, S> ReplicatedInterner<T, S> {
+    /// Returns the number of values in the shared interner.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.interner.len()
+    }
+
+    /// Returns `true` if no values are interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.interner.is_empty()
+    }
+
+    /// Returns the number of replicas.
+    #[inline]
+    pub fn replicas(&self) -> usize {
+        self.replicas.len()
+    }
+
+    /// Returns the number of values inserted since the replicas were rebuilt, the lookups of
+    /// those values check the shared interner.
+    #[inline]
+    pub fn pending_writes(&self) -> usize {
+        self.pending_writes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the shared interner that the inserts go to.
+    #[inline]
+    pub fn interner(&self) -> &Interner<T, S> {
+        &self.interner
+    }
+
+    #[inline]
+    fn replica(&self) -> &Replica<T, S> {
+        &self.replicas[REPLICA.with(|replica| *replica) % self.replicas.len()]
+    }
+}
+
+impl<T, S> ReplicatedInterner<T, S>
+where
+    S: Clone,
+{
+    /// Creates an empty `ReplicatedInterner` with `replicas` replicas that are rebuilt after
+    /// `rebuild_threshold` inserts or an eighth of the values, whichever is more, which will use
+    /// the given hash builder to hash the values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `replicas` is 0.
+    pub fn with_replicas_and_hasher(
+        replicas: usize,
+        rebuild_threshold: usize,
+        hash_builder: S,
+    ) -> Self {
+        assert!(replicas > 0, "a ReplicatedInterner needs at least one replica");
+        let replicas = (0..replicas)
+            .map(|_| Replica {
+                snapshot: RwLock::new(FrozenInterner::new(hash_builder.clone(), Vec::new())),
+            })
+            .collect();
+        Self {
+            interner: Interner::with_hasher(hash_builder),
+            replicas,
+            rebuild_threshold,
+            pending_writes: AtomicUsize::new(0),
+            rebuild: Mutex::new(()),
+        }
+    }
+}
+
+impl<T, S> ReplicatedInterner<T, S>
+where
+    T: Eq + Hash + Copy,
+    S: BuildHasher + Clone,
+{
+    /// Interns the value in the shared interner and returns the interned value, see
+    /// [`Interner::intern_ref`].
+    ///
+    /// The insert that passes the rebuild threshold rebuilds the replicas before it returns.
+    pub fn intern_ref<Q>(&self, value: &Q, make: impl FnOnce() -> T) -> T
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.interner.hasher().hash_one(value);
+        if let Some(result) = self.get_from_replica(hash, value) {
+            return result;
+        }
+        let interned = self.interner.intern_ref_entry(value, make);
+        if interned.was_new
+            && self.pending_writes.fetch_add(1, Ordering::Relaxed) + 1
+                >= self.rebuild_threshold.max(self.interner.len() / REBUILD_LEN_DIVISOR)
+        {
+            // another insert is already rebuilding the replicas
+            if let Ok(_rebuild) = self.rebuild.try_lock() {
+                self.rebuild_replicas();
+            }
+        }
+        interned.value
+    }
+
+    /// Returns the interned value equal to `value`, or `None` if it is not interned.
+    pub fn get<Q>(&self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.interner.hasher().hash_one(value);
+        self.get_from_replica(hash, value).or_else(|| {
+            self.interner.get_from_hash(hash, |result| value == result.borrow()).copied()
+        })
+    }
+
+    /// Rebuilds the replicas from the shared interner, the values inserted during the rebuild
+    /// may only be in the shared interner.
+    pub fn refresh(&self) {
+        let _rebuild = self.rebuild.lock().unwrap();
+        self.rebuild_replicas();
+    }
+
+    #[inline]
+    fn get_from_replica<Q>(&self, hash: u64, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let snapshot = self.replica().snapshot.read().unwrap();
+        snapshot.get_from_hash(hash, |result| value == result.borrow()).copied()
+    }
+
+    // the lock of `rebuild` must be held
+    #[cold]
+    fn rebuild_replicas(&self) {
+        // the inserts during the copy are counted for the next rebuild
+        self.pending_writes.store(0, Ordering::Relaxed);
+        let snapshot = self.interner.snapshot();
+        for replica in self.replicas.iter() {
+            let old = std::mem::replace(&mut *replica.snapshot.write().unwrap(), snapshot.clone());
+            // the old copy is freed after the lock is released
+            drop(old);
+        }
+    }
+}
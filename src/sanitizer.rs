@@ -0,0 +1,104 @@
+//! Blocking and happens-before annotations that the thread sanitizer understands.
+//!
+//! Without the thread sanitizer threads blocked on a locked slot are parked with
+//! `parking_lot_core` and the annotations are no-ops. With `-Zsanitizer=thread` the `sanitize`
+//! cfg is set and blocked threads waits on a condvar instead, and the acquire fence used when
+//! waiting on a slot is annotated as TSAN does not model fences.
+//!
+//! The `sanitize` cfg is only checked with the `nightly` feature as it is unstable, the
+//! `cfg_attr` on the items keeps stable from seeing it.
+
+#[cfg_attr(feature = "nightly", cfg(not(sanitize = "thread")))]
+use parking_lot_core::{self, DEFAULT_PARK_TOKEN, DEFAULT_UNPARK_TOKEN};
+#[cfg(feature = "nightly")]
+#[cfg_attr(feature = "nightly", cfg(sanitize = "thread"))]
+use std::sync::{Condvar, Mutex, PoisonError};
+use std::time::Instant;
+
+#[cfg(feature = "nightly")]
+#[cfg_attr(feature = "nightly", cfg(sanitize = "thread"))]
+extern "C" {
+    fn __tsan_acquire(addr: *mut std::ffi::c_void);
+    fn __tsan_release(addr: *mut std::ffi::c_void);
+}
+
+// all blocked threads waits on the same condvar, the waiters checks their own slot when woken up
+#[cfg(feature = "nightly")]
+#[cfg_attr(feature = "nightly", cfg(sanitize = "thread"))]
+static PARKED: (Mutex<()>, Condvar) = (Mutex::new(()), Condvar::new());
+
+/// Tells the sanitizer that the writes released at `addr` happens before the following reads.
+#[inline]
+pub(crate) fn acquire<A>(addr: &A) {
+    #[cfg(feature = "nightly")]
+    #[cfg_attr(feature = "nightly", cfg(sanitize = "thread"))]
+    // SAFETY: only records a synchronization edge in the sanitizer runtime
+    unsafe {
+        __tsan_acquire(addr as *const A as *mut std::ffi::c_void)
+    };
+    #[cfg_attr(feature = "nightly", cfg(not(sanitize = "thread")))]
+    let _ = addr;
+}
+
+/// Tells the sanitizer that the preceding writes are released at `addr`.
+#[inline]
+pub(crate) fn release<A>(addr: &A) {
+    #[cfg(feature = "nightly")]
+    #[cfg_attr(feature = "nightly", cfg(sanitize = "thread"))]
+    // SAFETY: only records a synchronization edge in the sanitizer runtime
+    unsafe {
+        __tsan_release(addr as *const A as *mut std::ffi::c_void)
+    };
+    #[cfg_attr(feature = "nightly", cfg(not(sanitize = "thread")))]
+    let _ = addr;
+}
+
+/// Blocks the thread on `addr` if `validate` returns true, at most until `deadline`. The thread
+/// can be woken up spuriously so the caller must check its condition again.
+#[inline]
+pub(crate) fn park(addr: usize, validate: impl FnOnce() -> bool, deadline: Option<Instant>) {
+    #[cfg(feature = "nightly")]
+    #[cfg_attr(feature = "nightly", cfg(sanitize = "thread"))]
+    {
+        let _ = addr;
+        let guard = PARKED.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if validate() {
+            match deadline {
+                Some(deadline) => drop(
+                    PARKED
+                        .1
+                        .wait_timeout(guard, deadline.saturating_duration_since(Instant::now()))
+                        .unwrap_or_else(PoisonError::into_inner),
+                ),
+                None => drop(PARKED.1.wait(guard).unwrap_or_else(PoisonError::into_inner)),
+            }
+        }
+    }
+    #[cfg_attr(feature = "nightly", cfg(not(sanitize = "thread")))]
+    // SAFETY:
+    //   * `addr` is an address we control.
+    //   * `validate`/`timed_out` does not panic or call into any function of `parking_lot`.
+    //   * `before_sleep` does not call `park`, nor does it panic.
+    unsafe {
+        parking_lot_core::park(addr, validate, || {}, |_, _| {}, DEFAULT_PARK_TOKEN, deadline);
+    }
+}
+
+/// Wakes up all threads blocked on `addr`.
+#[inline]
+pub(crate) fn unpark_all(addr: usize) {
+    #[cfg(feature = "nightly")]
+    #[cfg_attr(feature = "nightly", cfg(sanitize = "thread"))]
+    {
+        let _ = addr;
+        // the lock orders the notify after the waiters that have validated but not started to wait
+        let _guard = PARKED.0.lock().unwrap_or_else(PoisonError::into_inner);
+        PARKED.1.notify_all();
+    }
+    #[cfg_attr(feature = "nightly", cfg(not(sanitize = "thread")))]
+    // SAFETY:
+    //   * `addr` is an address we control.
+    unsafe {
+        parking_lot_core::unpark_all(addr, DEFAULT_UNPARK_TOKEN);
+    }
+}
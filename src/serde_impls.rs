@@ -0,0 +1,55 @@
+use crate::interner::Interner;
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+/// Serializes the interned values as a sequence, the values of the preset are included.
+///
+/// The values are copied out before they are serialized, values interned during the copy may be
+/// missing. The order of the values is unspecified.
+impl<T, S> Serialize for Interner<T, S>
+where
+    T: Eq + Hash + Copy + Serialize,
+    S: BuildHasher,
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_seq(self.values())
+    }
+}
+
+/// Deserializes a sequence of values in to an `Interner` that is sized for the values, see
+/// [`FromIterator`].
+impl<'de, T, S> Deserialize<'de> for Interner<T, S>
+where
+    T: Eq + Hash + Copy + Deserialize<'de>,
+    S: BuildHasher + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(InternerVisitor(PhantomData))
+    }
+}
+
+struct InternerVisitor<T, S>(PhantomData<fn() -> Interner<T, S>>);
+
+impl<'de, T, S> Visitor<'de> for InternerVisitor<T, S>
+where
+    T: Eq + Hash + Copy + Deserialize<'de>,
+    S: BuildHasher + Default,
+{
+    type Value = Interner<T, S>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of interned values")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        // the size hint is not trusted, the values are collected before the table is sized
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(values.into_iter().collect())
+    }
+}
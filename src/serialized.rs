@@ -0,0 +1,127 @@
+//! Interning of keys by their serialized form.
+//!
+//! Keys are serialized with a [`Codec`] and the bytes are interned, so two keys are equal when
+//! they serialize to the same bytes. This is useful for keys where `Hash` and `Eq` are hard to
+//! keep consistent, the codec can be implemented with e.g. bincode or postcard.
+
+use crate::arena::Arena;
+use crate::interner::{DefaultHashBuilder, Interner};
+use std::hash::{BuildHasher, Hash, Hasher};
+
+/// Serializes keys of type `K` to a canonical byte representation.
+///
+/// Keys that shall be considered equal must be encoded to the same bytes.
+pub trait Codec<K: ?Sized> {
+    /// Appends the serialized form of `key` to `buf`.
+    fn encode(&self, key: &K, buf: &mut Vec<u8>);
+}
+
+/// A handle to interned serialized bytes.
+///
+/// Two tokens from the same `SerializedInterner` are equal if the keys was serialized to the
+/// same bytes, comparing and hashing tokens only uses the address of the interned bytes.
+#[derive(Clone, Copy, Debug)]
+pub struct Token<'a>(&'a [u8]);
+
+impl<'a> Token<'a> {
+    /// Returns the interned serialized bytes.
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+}
+
+impl PartialEq for Token<'_> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for Token<'_> {}
+
+impl Hash for Token<'_> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_ptr().hash(state)
+    }
+}
+
+/// A concurrent interner of keys by their serialized form.
+///
+/// # Examples
+///
+/// ```
+/// use interner::serialized::{Codec, SerializedInterner};
+///
+/// struct Config {
+///     name: String,
+///     retries: u32,
+/// }
+///
+/// struct ConfigCodec;
+///
+/// impl Codec<Config> for ConfigCodec {
+///     fn encode(&self, key: &Config, buf: &mut Vec<u8>) {
+///         buf.extend_from_slice(&(key.name.len() as u64).to_le_bytes());
+///         buf.extend_from_slice(key.name.as_bytes());
+///         buf.extend_from_slice(&key.retries.to_le_bytes());
+///     }
+/// }
+///
+/// let interner = SerializedInterner::new(ConfigCodec);
+/// let token1 = interner.intern_serialized(&Config { name: "a".to_string(), retries: 3 });
+/// let token2 = interner.intern_serialized(&Config { name: "a".to_string(), retries: 3 });
+/// let token3 = interner.intern_serialized(&Config { name: "a".to_string(), retries: 4 });
+/// assert_eq!(token1, token2);
+/// assert_ne!(token1, token3);
+/// ```
+pub struct SerializedInterner<C, S = DefaultHashBuilder> {
+    codec: C,
+    // the references point in to `arena` and is only handed out with the lifetime of `self`
+    interner: Interner<&'static [u8], S>,
+    arena: Arena<[u8]>,
+}
+
+impl<C> SerializedInterner<C, DefaultHashBuilder> {
+    /// Creates an empty `SerializedInterner` that serializes keys with `codec`.
+    #[inline]
+    pub fn new(codec: C) -> Self {
+        Self::with_hasher(codec, DefaultHashBuilder::default())
+    }
+}
+
+impl<C, S> SerializedInterner<C, S> {
+    /// Creates an empty `SerializedInterner` that serializes keys with `codec` and hash the
+    /// serialized bytes with `hash_builder`.
+    #[inline]
+    pub const fn with_hasher(codec: C, hash_builder: S) -> Self {
+        Self { codec, interner: Interner::with_hasher(hash_builder), arena: Arena::new() }
+    }
+
+    /// Returns a reference to the codec.
+    #[inline]
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+}
+
+impl<C, S> SerializedInterner<C, S>
+where
+    S: BuildHasher,
+{
+    /// Serializes `key` and interns the bytes, the bytes are only copied in to the interner if
+    /// they was not already interned.
+    pub fn intern_serialized<K: ?Sized>(&self, key: &K) -> Token<'_>
+    where
+        C: Codec<K>,
+    {
+        let mut buf = Vec::new();
+        self.codec.encode(key, &mut buf);
+        // SAFTY: the bytes lives as long as the arena
+        Token(
+            self.interner
+                .intern_ref(&buf[..], || unsafe { self.arena.alloc(Box::from(&buf[..])).as_ref() }),
+        )
+    }
+}
@@ -0,0 +1,108 @@
+use crate::error::InternError;
+use crate::interner::Interner;
+use std::borrow::Borrow;
+use std::cell::Cell;
+use std::hash::{BuildHasher, Hash};
+
+/// Number of recently interned values cached by a `Session`.
+const SESSION_CACHE_SIZE: usize = 64;
+
+// the hash and value of a recently interned value
+type CacheEntry<T> = Cell<Option<(u64, T)>>;
+
+/// A handle to an [`Interner`] with a cache of the values recently interned through it.
+///
+/// A session is meant to be used by one thread, for parallel passes each task clones the
+/// session and gets its own empty cache. Values that are interned again soon, as is common when
+/// processing related data, are then found without probing the shared tables.
+///
+/// # Examples
+///
+/// ```
+/// use interner::Interner;
+///
+/// let values: Vec<u64> = (0..100).collect();
+/// let interner: Interner<&u64> = Interner::new();
+/// interner.in_scope(|session| {
+///     std::thread::scope(|scope| {
+///         for chunk in values.chunks(25) {
+///             let session = session.clone();
+///             scope.spawn(move || {
+///                 for value in chunk {
+///                     assert_eq!(value, session.intern_ref(value, || value));
+///                 }
+///             });
+///         }
+///     });
+/// });
+/// ```
+pub struct Session<'a, T, S> {
+    interner: &'a Interner<T, S>,
+    cache: Box<[CacheEntry<T>]>,
+}
+
+impl<'a, T, S> Session<'a, T, S> {
+    pub(crate) fn new(interner: &'a Interner<T, S>) -> Self {
+        Self { interner, cache: (0..SESSION_CACHE_SIZE).map(|_| Cell::new(None)).collect() }
+    }
+
+    /// Returns the interner of the session.
+    #[inline]
+    pub fn interner(&self) -> &'a Interner<T, S> {
+        self.interner
+    }
+}
+
+impl<T, S> Clone for Session<'_, T, S> {
+    /// Returns a session for the same interner with an empty cache.
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::new(self.interner)
+    }
+}
+
+impl<T, S> Session<'_, T, S>
+where
+    T: Eq + Hash + Copy,
+    S: BuildHasher,
+{
+    /// Interns the value and returns a reference to the interned value, see
+    /// [`Interner::intern_ref`].
+    pub fn intern_ref<Q>(&self, value: &Q, make: impl FnOnce() -> T) -> T
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.try_intern_ref(value, make).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Interns the value and returns a reference to the interned value or an error if the
+    /// interner is full, see [`Interner::try_intern_ref`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::MemoryLimit`] if the value is not interned and a resize is needed
+    /// that would pass the memory limit of the interner, or [`InternError::ResizeDenied`] if a
+    /// resize is needed and resizes are denied.
+    pub fn try_intern_ref<Q>(&self, value: &Q, make: impl FnOnce() -> T) -> Result<T, InternError>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.interner.hasher().hash_one(value);
+        let cached = &self.cache[hash as usize % SESSION_CACHE_SIZE];
+        if let Some((cached_hash, result)) = cached.get() {
+            if cached_hash == hash && value.eq(result.borrow()) {
+                return Ok(result);
+            }
+        }
+        let result = self.interner.intern_hashed(
+            hash,
+            value,
+            |value, result| (*value).eq(result.borrow()),
+            |_| make(),
+        )?;
+        cached.set(Some((hash, result)));
+        Ok(result)
+    }
+}
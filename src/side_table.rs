@@ -0,0 +1,119 @@
+use std::sync::OnceLock;
+
+/// The number of ids in the first segment, each following segment is twice as large.
+const FIRST_SEGMENT_LEN: usize = 64;
+
+/// The number of segments needed to cover every `u32` id.
+const SEGMENTS: usize = 27;
+
+// returns the segment of `id` and the index of `id` in the segment
+#[inline]
+fn segment_of(id: u32) -> (usize, usize) {
+    let shifted = u64::from(id) + FIRST_SEGMENT_LEN as u64;
+    let bits = u64::BITS - 1 - shifted.leading_zeros();
+    let segment = (bits - FIRST_SEGMENT_LEN.trailing_zeros()) as usize;
+    (segment, (shifted - (1 << bits)) as usize)
+}
+
+// returns the number of ids in `segment`
+#[inline]
+const fn segment_len(segment: usize) -> usize {
+    FIRST_SEGMENT_LEN << segment
+}
+
+/// A table of values indexed by the dense ids of an [`Interner`](crate::Interner), see
+/// [`Interner::dense_id`](crate::Interner::dense_id).
+///
+/// The table grows in segments that are twice as large as the previous segment and are never
+/// moved, so a reference to a value stays valid while the table grows and the values of other
+/// ids are used by other threads. The values are created with `Default` when the segment of
+/// their id is allocated, values that are written by several threads need interior mutability.
+///
+/// # Examples
+///
+/// ```
+/// use interner::{Interner, InternerBuilder, SideTable};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// let interner: Interner<&str> = InternerBuilder::new().dense_ids(true).build();
+/// let counts: SideTable<AtomicUsize> = SideTable::new();
+/// for word in ["a", "b", "a"] {
+///     interner.intern_ref(word, || word);
+///     let id = interner.dense_id(word).unwrap();
+///     counts.get(id).fetch_add(1, Ordering::Relaxed);
+/// }
+/// assert_eq!(2, counts.get(interner.dense_id("a").unwrap()).load(Ordering::Relaxed));
+/// assert_eq!(1, counts.get(interner.dense_id("b").unwrap()).load(Ordering::Relaxed));
+/// ```
+pub struct SideTable<V> {
+    segments: [OnceLock<Box<[V]>>; SEGMENTS],
+}
+
+impl<V> SideTable<V> {
+    /// Creates an empty `SideTable` without allocating.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { segments: [const { OnceLock::new() }; SEGMENTS] }
+    }
+
+    /// Returns the number of ids that the allocated segments cover, counted from id 0 up to the
+    /// first segment that is not allocated.
+    pub fn capacity(&self) -> usize {
+        (0..SEGMENTS)
+            .take_while(|&segment| self.segments[segment].get().is_some())
+            .map(segment_len)
+            .sum()
+    }
+
+    /// Returns the value of `id`, or `None` if the segment of `id` is not allocated.
+    #[inline]
+    pub fn try_get(&self, id: u32) -> Option<&V> {
+        let (segment, index) = segment_of(id);
+        self.segments[segment].get().map(|values| &values[index])
+    }
+
+    /// Returns the value of `id` mutably, or `None` if the segment of `id` is not allocated.
+    #[inline]
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut V> {
+        let (segment, index) = segment_of(id);
+        self.segments[segment].get_mut().map(|values| &mut values[index])
+    }
+}
+
+impl<V: Default> SideTable<V> {
+    /// Returns the value of `id`, the segment of `id` is allocated if it is not.
+    #[inline]
+    pub fn get(&self, id: u32) -> &V {
+        let (segment, index) = segment_of(id);
+        &self.segment(segment)[index]
+    }
+
+    /// Allocates the segments needed for the ids less than `len`, e.g. with the
+    /// [`Interner::len`](crate::Interner::len) of the interner that gave out the ids.
+    pub fn grow_to(&self, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let last = u32::try_from(len - 1).unwrap_or(u32::MAX);
+        for segment in 0..=segment_of(last).0 {
+            self.segment(segment);
+        }
+    }
+
+    #[cold]
+    fn allocate(segment: usize) -> Box<[V]> {
+        (0..segment_len(segment)).map(|_| V::default()).collect()
+    }
+
+    #[inline]
+    fn segment(&self, segment: usize) -> &[V] {
+        self.segments[segment].get_or_init(|| Self::allocate(segment))
+    }
+}
+
+impl<V> Default for SideTable<V> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
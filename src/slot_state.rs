@@ -0,0 +1,126 @@
+//! The state machine of one slot of a bucket, as pure functions on [`MetaData`].
+//!
+//! A slot goes from empty to locked by the insert that reserved it, optionally gets the park
+//! bit when other inserts of a value with the same h2 wait for it, and becomes valid when the
+//! value is written, or poisoned when the insert fails to make the value. A valid or poisoned
+//! slot never changes again. The group moved bit is shared by all
+//! slots of the bucket, it is set once when the bucket is moved to the next table and stops
+//! empty slots from being locked, slots that are already locked still become valid.
+//!
+//! Every transition is one compare exchange or fetch or of the meta data in `Bucket`, [`step`]
+//! computes the meta data after it with the same bit operations, so the tests can enumerate all
+//! sequences of transitions and check the invariants of the protocol on the real encoding.
+
+use crate::meta_data::MetaData;
+
+/// The state of one slot decoded from the meta data of its bucket.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SlotState {
+    /// Not reserved, can be locked if the bucket is not moved.
+    Empty,
+    /// Reserved by an insert that is writing the value, `h2` is the 6 bit h2 of the value and
+    /// `parked` is set when one or more threads are parked or about to park on the slot.
+    Locked { h2: u8, parked: bool },
+    /// The value is written and can be read, `h2` is the 8 bit h2 of the value.
+    Valid { h2: u8 },
+    /// The insert that locked the slot did not get a value, the slot is never used again.
+    Poisoned,
+}
+
+impl SlotState {
+    /// Decodes the state of slot `index`, returns `None` for bit patterns that no sequence of
+    /// transitions from an empty bucket reaches, i.e. a park bit without the lock bit and with
+    /// other bits set.
+    #[inline]
+    pub fn decode(meta_data: &MetaData, index: usize) -> Option<Self> {
+        let h2 = meta_data.h2_from_meta(index);
+        if meta_data.test_valid_bit(index) {
+            Some(Self::Valid { h2 })
+        } else if meta_data.test_lock_bit(index) {
+            Some(Self::Locked { h2: h2 & 0x3F, parked: meta_data.test_park_bit(index) })
+        } else if meta_data.test_poisoned(index) {
+            Some(Self::Poisoned)
+        } else if h2 == 0 {
+            Some(Self::Empty)
+        } else {
+            None
+        }
+    }
+}
+
+/// An operation on one slot of a bucket, or on the bucket for [`SlotEvent::MoveBucket`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SlotEvent {
+    /// An insert reserves the slot for a value with the h2, `Bucket::reserve`.
+    Lock { h2: u8 },
+    /// A thread waiting for the value sets the park bit before it parks,
+    /// `Bucket::wait_on_lock_release`.
+    Park,
+    /// The insert that locked the slot has written the value and marks it valid,
+    /// `Bucket::set_valid_and_unpark`.
+    Publish { h2: u8 },
+    /// The insert that locked the slot failed to make the value and gives up the slot,
+    /// `Bucket::poison_and_unpark`.
+    Poison,
+    /// The bucket is moved to the next table, `Bucket::transfer_bucket`.
+    MoveBucket,
+}
+
+/// The result of a [`step`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transition {
+    /// The meta data after the event.
+    pub meta_data: MetaData,
+    /// The threads parked on the slot must be unparked.
+    pub unpark: bool,
+}
+
+/// Returns the transition of `event` on slot `index`, or `None` if the event is not allowed in
+/// the current state.
+///
+/// * A slot is only locked when it is empty and the bucket is not moved.
+/// * The park bit is only set on a locked slot that does not have it.
+/// * A slot is only published by the insert that locked it, so with the same 6 bit h2, and the
+///   threads parked on it are unparked if the park bit was set.
+/// * A slot is only poisoned when it is locked, the threads parked on it are unparked if the
+///   park bit was set.
+/// * The bucket is only moved once.
+#[inline]
+pub fn step(meta_data: &MetaData, index: usize, event: SlotEvent) -> Option<Transition> {
+    let state = SlotState::decode(meta_data, index)?;
+    let (meta_data, unpark) = match (state, event) {
+        (SlotState::Empty, SlotEvent::Lock { h2 }) if !meta_data.bucket_moved() => {
+            (meta_data.lock(h2, index), false)
+        }
+        (SlotState::Locked { parked: false, .. }, SlotEvent::Park) => {
+            (meta_data.park(index), false)
+        }
+        (SlotState::Locked { h2: locked, parked }, SlotEvent::Publish { h2 })
+            if h2 & 0x3F == locked =>
+        {
+            (meta_data.unlock(h2, index), parked)
+        }
+        (SlotState::Locked { parked, .. }, SlotEvent::Poison) => (meta_data.poison(index), parked),
+        (_, SlotEvent::MoveBucket) if !meta_data.bucket_moved() => (meta_data.moved(), false),
+        _ => return None,
+    };
+    Some(Transition { meta_data, unpark })
+}
+
+// asserts that a transition written by `Bucket` is allowed by the model, used by the `checked`
+// feature
+#[cfg(feature = "checked")]
+#[track_caller]
+pub(crate) fn assert_step(
+    before: &MetaData,
+    index: usize,
+    event: SlotEvent,
+    after: &MetaData,
+    unpark: bool,
+) {
+    assert_eq!(
+        step(before, index, event),
+        Some(Transition { meta_data: *after, unpark }),
+        "interner slot {index} transition {event:?} from {before:?} does not follow the model"
+    );
+}
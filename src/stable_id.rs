@@ -0,0 +1,320 @@
+use crate::interner::{Cursor, DefaultHashBuilder, Interner};
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::io::{self, Read, Write};
+
+const FNV_OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+/// Number of entries written per chunk by [`StableIds::write_to`].
+const WRITE_CHUNK_SIZE: usize = 1024;
+
+/// A hasher with a fixed algorithm and no seed, so the hash of a value is the same in every
+/// process, used for the ids of [`StableIds`].
+///
+/// The hash is the 128 bit FNV-1a hash of the bytes written by the `Hash` implementation of the
+/// value, integers are written in little endian and `usize` and `isize` as 64 bits so the hash
+/// does not depend on the platform. The `Hash` implementations of the value must be stable, the
+/// implementations of the standard library can change between Rust versions.
+///
+/// # Examples
+///
+/// ```
+/// use interner::StableHasher;
+///
+/// assert_eq!(StableHasher::hash128("a"), StableHasher::hash128("a"));
+/// assert_ne!(StableHasher::hash128("a"), StableHasher::hash128("b"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct StableHasher {
+    state: u128,
+}
+
+impl StableHasher {
+    /// Creates a hasher with the fixed initial state.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { state: FNV_OFFSET_BASIS }
+    }
+
+    /// Returns the full 128 bit hash of the bytes written so far.
+    #[inline]
+    pub fn finish128(&self) -> u128 {
+        self.state
+    }
+
+    /// Returns the 128 bit hash of `value`.
+    #[inline]
+    pub fn hash128<T: ?Sized + Hash>(value: &T) -> u128 {
+        let mut hasher = Self::new();
+        value.hash(&mut hasher);
+        hasher.finish128()
+    }
+}
+
+impl Default for StableHasher {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for StableHasher {
+    /// Returns the 128 bit hash folded to 64 bits.
+    #[inline]
+    fn finish(&self) -> u64 {
+        fold(self.state)
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state = (self.state ^ u128::from(byte)).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.write(&[i]);
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    #[inline]
+    fn write_i8(&mut self, i: i8) {
+        self.write_u8(i as u8);
+    }
+
+    #[inline]
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16);
+    }
+
+    #[inline]
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32);
+    }
+
+    #[inline]
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64);
+    }
+
+    #[inline]
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128);
+    }
+
+    #[inline]
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as u64);
+    }
+}
+
+#[inline]
+fn fold(hash: u128) -> u64 {
+    (hash >> 64) as u64 ^ hash as u64
+}
+
+/// Two values with different content got the same id, returned by [`StableIds::id_of`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IdCollision {
+    /// The id of both values.
+    pub id: u64,
+}
+
+impl fmt::Display for IdCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "stable id {:#018x} is already used by a value with other content", self.id)
+    }
+}
+
+impl std::error::Error for IdCollision {}
+
+// the id and the full hash it was derived from, hashed and compared by the id only so an other
+// full hash with the same id is found as a collision
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    id: u64,
+    full: u128,
+}
+
+impl PartialEq for Entry {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Entry {}
+
+impl Hash for Entry {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+/// A registry of stable 64 bit ids derived from the content of values, for ids that stay valid
+/// across runs, e.g. in on disk caches, independent of the random seed of the interner's hasher.
+///
+/// The id of a value is the 128 bit [`StableHasher`] hash folded to 64 bits. The registry keeps
+/// the full hash of every assigned id in an [`Interner`], so a value with another content that
+/// gets an already assigned id is detected as a collision instead of silently sharing the id.
+/// The registry can be written to and read from a file to detect collisions with ids of earlier
+/// runs.
+///
+/// # Examples
+///
+/// ```
+/// use interner::StableIds;
+///
+/// let ids: StableIds = StableIds::new();
+/// let id = ids.id_of("hello").unwrap();
+/// assert_eq!(Ok(id), ids.id_of("hello"));
+///
+/// let mut file = Vec::new();
+/// ids.write_to(&mut file).unwrap();
+/// let ids: StableIds = StableIds::read_from(&file[..]).unwrap();
+/// assert_eq!(1, ids.len());
+/// assert_eq!(Ok(id), ids.id_of("hello"));
+/// ```
+pub struct StableIds<S = DefaultHashBuilder> {
+    registry: Interner<Entry, S>,
+}
+
+impl StableIds<DefaultHashBuilder> {
+    /// Creates an empty registry.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+
+    /// Reads a registry written by [`StableIds::write_to`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `reader`, and an error of kind [`io::ErrorKind::InvalidData`] if the
+    /// data is truncated or has two different hashes for the same id.
+    pub fn read_from(reader: impl Read) -> io::Result<Self> {
+        let ids = Self::new();
+        ids.extend_from(reader)?;
+        Ok(ids)
+    }
+}
+
+impl Default for StableIds<DefaultHashBuilder> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> StableIds<S> {
+    /// Creates an empty registry which will use the given hash builder for its table, the ids
+    /// do not depend on it.
+    #[inline]
+    pub const fn with_hasher(hash_builder: S) -> Self {
+        Self { registry: Interner::with_hasher(hash_builder) }
+    }
+
+    /// Returns the number of assigned ids.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.registry.len()
+    }
+
+    /// Returns `true` if no ids are assigned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.registry.is_empty()
+    }
+}
+
+impl<S: BuildHasher> StableIds<S> {
+    /// Returns the stable id of `value` and registers it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdCollision`] if the id is already registered for a value with other content.
+    pub fn id_of<T: ?Sized + Hash>(&self, value: &T) -> Result<u64, IdCollision> {
+        self.register(StableHasher::hash128(value))
+    }
+
+    fn register(&self, full: u128) -> Result<u64, IdCollision> {
+        let entry = Entry { id: fold(full), full };
+        let registered = self.registry.intern(entry, |entry| entry);
+        if registered.full == full { Ok(entry.id) } else { Err(IdCollision { id: entry.id }) }
+    }
+
+    /// Writes the assigned ids and their full hashes, 24 bytes per id.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `writer`.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        let mut cursor = Some(Cursor::default());
+        while let Some(current) = cursor {
+            let (chunk, next) = self.registry.iter_chunk(current, WRITE_CHUNK_SIZE);
+            for entry in chunk {
+                writer.write_all(&entry.id.to_le_bytes())?;
+                writer.write_all(&entry.full.to_le_bytes())?;
+            }
+            cursor = next;
+        }
+        Ok(())
+    }
+
+    /// Registers the ids written by [`StableIds::write_to`] in to this registry.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `reader`, and an error of kind [`io::ErrorKind::InvalidData`] if the
+    /// data is truncated, or has an id that does not match its hash or that collides with a
+    /// registered id.
+    pub fn extend_from(&self, mut reader: impl Read) -> io::Result<()> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let chunks = bytes.chunks_exact(24);
+        if !chunks.remainder().is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated stable id registry"));
+        }
+        for chunk in chunks {
+            let (id, full) = chunk.split_at(8);
+            let id = u64::from_le_bytes(id.try_into().expect("split at 8 bytes"));
+            let full = u128::from_le_bytes(full.try_into().expect("16 bytes left"));
+            if fold(full) != id {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "stable id does not match its hash",
+                ));
+            }
+            self.register(full)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        }
+        Ok(())
+    }
+}
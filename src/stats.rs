@@ -0,0 +1,199 @@
+//! Statistics and diagnostics about the tables of an [`Interner`](crate::Interner).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// The number of classes the waits are counted in, one for each value of the 6 bits of h2 that
+/// are stored in a locked slot.
+pub const WAIT_CLASSES: usize = 64;
+
+static PARKS: [AtomicU64; WAIT_CLASSES] = [const { AtomicU64::new(0) }; WAIT_CLASSES];
+static SPURIOUS_WAKEUPS: [AtomicU64; WAIT_CLASSES] = [const { AtomicU64::new(0) }; WAIT_CLASSES];
+
+/// The tables of an `Interner` and how much memory is held by tables that are not current.
+///
+/// This `struct` is created by [`Interner::fragmentation_report`](crate::Interner::fragmentation_report).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FragmentationReport {
+    /// All tables from the oldest to the newest.
+    pub tables: Vec<TableReport>,
+    /// The bytes of bucket memory held by the tables that are older than the current table.
+    pub non_current_bytes: usize,
+}
+
+/// The size and load of the tables of an `Interner`, to tune the initial capacity and observe
+/// resizes.
+///
+/// This `struct` is created by [`Interner::stats`](crate::Interner::stats).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InternerStats {
+    /// The number of interned values, see [`Interner::len`](crate::Interner::len).
+    pub len: usize,
+    /// The capacity of the newest table, see [`Interner::capacity`](crate::Interner::capacity).
+    pub capacity: usize,
+    /// The share of the slots of the newest table that contains a value, 0 for the empty table.
+    pub occupancy: f64,
+    /// The number of tables that are kept by the interner, more than 1 after a resize.
+    pub tables: usize,
+    /// The moves to the next table that are not done in all tables, the buckets that are not
+    /// moved and the locked slots of moved buckets, see
+    /// [`Interner::to_be_moved`](crate::Interner::to_be_moved).
+    pub pending_moves: usize,
+}
+
+/// A single table in the chain of tables of an `Interner`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TableReport {
+    /// The number of buckets in the table.
+    pub buckets: usize,
+    /// The number of buckets that have not been moved to the next table.
+    pub unmoved_buckets: usize,
+    /// The number of values in the buckets that have not been moved to the next table.
+    pub entries: usize,
+    /// The bytes of bucket memory held by the table, including the padding of the buckets to
+    /// the cache line size and the per bucket hints.
+    pub bytes: usize,
+    /// The bytes of one bucket, including its padding to the cache line size and its hint,
+    /// without the per slot fingerprints and dense ids that some settings add to `bytes`.
+    pub bucket_bytes: usize,
+    /// The table is the current table, new inserts start the search in this table.
+    pub is_current: bool,
+}
+
+/// The state of a bucket on the probe sequence of a hash.
+///
+/// This `struct` is created by [`Interner::visit_bucket_of`](crate::Interner::visit_bucket_of).
+#[derive(Debug)]
+pub struct BucketInfo<'a, T> {
+    /// The table the bucket is in, 0 is the current table and higher numbers are newer tables.
+    pub table: usize,
+    /// The position of the bucket in the table.
+    pub pos: usize,
+    /// The bucket has been moved to the next table.
+    pub moved: bool,
+    /// The slots of the bucket.
+    pub slots: [SlotInfo<'a, T>; 7],
+}
+
+/// The state of a slot in a bucket.
+#[derive(Debug)]
+pub enum SlotInfo<'a, T> {
+    /// The slot is free.
+    Empty,
+    /// A value is being inserted in to the slot.
+    Locked {
+        /// The 6 bits of h2 that are stored while the slot is locked.
+        h2: u8,
+        /// Other threads are waiting for the value.
+        parked: bool,
+    },
+    /// The insert that locked the slot failed to make its value, the slot is never used again.
+    Poisoned,
+    /// The slot contains a value.
+    Valid {
+        /// The h2 hash of the value.
+        h2: u8,
+        /// The value in the slot.
+        value: &'a T,
+    },
+}
+
+/// The number of times threads blocked on a locked slot, counted by the 6 bits of h2 that are
+/// stored in the locked slot.
+///
+/// This `struct` is created by [`wait_stats`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WaitStats {
+    /// The number of times a thread was parked waiting for the value of a locked slot.
+    pub parks: [u64; WAIT_CLASSES],
+    /// The number of times a parked thread was woken up and the slot was still locked, e.g. as
+    /// the wakeup was meant for another slot that is parked on the same address or condvar.
+    pub spurious_wakeups: [u64; WAIT_CLASSES],
+}
+
+impl WaitStats {
+    /// Returns the number of parks in all classes.
+    pub fn total_parks(&self) -> u64 {
+        self.parks.iter().sum()
+    }
+
+    /// Returns the number of spurious wakeups in all classes.
+    pub fn total_spurious_wakeups(&self) -> u64 {
+        self.spurious_wakeups.iter().sum()
+    }
+}
+
+/// Returns the number of times threads have waited on a locked slot in any interner in the
+/// process since it started.
+///
+/// Blocked threads are parked in a table that is shared by all interners, keyed by the address
+/// of the slot, or on a single condvar when built with the thread sanitizer. A high rate of
+/// spurious wakeups compared to parks shows that waiters on different slots wake each other up.
+///
+/// # Examples
+///
+/// ```
+/// use interner::stats::wait_stats;
+///
+/// let stats = wait_stats();
+/// assert!(stats.total_spurious_wakeups() <= stats.total_parks());
+/// ```
+pub fn wait_stats() -> WaitStats {
+    WaitStats {
+        parks: std::array::from_fn(|class| PARKS[class].load(Ordering::Relaxed)),
+        spurious_wakeups: std::array::from_fn(|class| {
+            SPURIOUS_WAKEUPS[class].load(Ordering::Relaxed)
+        }),
+    }
+}
+
+// counts a park of a thread on a slot locked with `h2`
+#[cold]
+pub(crate) fn record_park(h2: u8) {
+    PARKS[(h2 & 0x3F) as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+// counts a wakeup of a thread parked on a slot locked with `h2` that is still locked
+#[cold]
+pub(crate) fn record_spurious_wakeup(h2: u8) {
+    SPURIOUS_WAKEUPS[(h2 & 0x3F) as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+/// A call of `make` that took longer than the threshold set with
+/// [`InternerBuilder::slow_make_threshold`](crate::InternerBuilder::slow_make_threshold).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlowMake {
+    /// The hash of the value that was created.
+    pub hash: u64,
+    /// The time `make` took, the slot of the value was locked during this time.
+    pub duration: Duration,
+}
+
+/// A table whose values were not all moved to the next table within the timeout set with
+/// [`InternerBuilder::stuck_transfer_alert`](crate::InternerBuilder::stuck_transfer_alert).
+///
+/// The move of a bucket waits for the values of its locked slots, so a transfer that never
+/// finishes usually means that a thread died or is blocked while it held a locked slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StuckTransfer {
+    /// The number of buckets of the table.
+    pub buckets: usize,
+    /// The count of the moves that are not done, see
+    /// [`Interner::to_be_moved`](crate::Interner::to_be_moved).
+    pub to_be_moved: isize,
+    /// The time since the next table was created.
+    pub elapsed: Duration,
+}
+
+/// The calls of `make` that took longer than the threshold.
+///
+/// This `struct` is created by [`Interner::slow_make_report`](crate::Interner::slow_make_report).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SlowMakeReport {
+    /// The number of slow calls.
+    pub count: usize,
+    /// The longest call.
+    pub max: Duration,
+    /// The most recent slow calls, oldest first.
+    pub recent: Vec<SlowMake>,
+}
@@ -0,0 +1,172 @@
+use crate::arena::Arena;
+use crate::interner::{DefaultHashBuilder, Interner};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::RwLock;
+
+/// The index of a string interned in a [`StringInterner`].
+///
+/// The symbols are given out in the order the strings are first interned, starting at 0, so they
+/// can index side tables. A symbol is only meaningful for the interner that returned it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Returns the index of the symbol.
+    #[inline]
+    pub const fn as_u32(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the index of the symbol as a `usize`.
+    #[inline]
+    pub const fn as_usize(self) -> usize {
+        self.0 as usize
+    }
+}
+
+// the string in the arena and its symbol, hashed and compared by the string so the interner can
+// be looked up by `&str`
+#[derive(Clone, Copy)]
+struct Entry {
+    string: &'static str,
+    symbol: Symbol,
+}
+
+impl PartialEq for Entry {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.string == other.string
+    }
+}
+
+impl Eq for Entry {}
+
+impl Hash for Entry {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.string.hash(state)
+    }
+}
+
+impl Borrow<str> for Entry {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self.string
+    }
+}
+
+/// A concurrent interner of strings that returns [`Symbol`]s.
+///
+/// The strings are copied in to an internal arena the first time they are interned, a symbol is
+/// resolved back to the string with [`StringInterner::resolve`].
+///
+/// # Examples
+///
+/// ```
+/// use interner::StringInterner;
+///
+/// let interner = StringInterner::new();
+/// let hello = interner.intern("hello");
+/// let world = interner.intern("world");
+/// assert_eq!(hello, interner.intern("hello"));
+/// assert_ne!(hello, world);
+/// assert_eq!(0, hello.as_u32());
+/// assert_eq!("world", interner.resolve(world));
+/// ```
+pub struct StringInterner<S = DefaultHashBuilder> {
+    // the strings point in to `arena` and are only handed out with the lifetime of `self`
+    interner: Interner<Entry, S>,
+    // the strings indexed by symbol
+    strings: RwLock<Vec<&'static str>>,
+    arena: Arena<str>,
+}
+
+impl StringInterner<DefaultHashBuilder> {
+    /// Creates an empty `StringInterner`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl Default for StringInterner<DefaultHashBuilder> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> StringInterner<S> {
+    /// Creates an empty `StringInterner` which will use the given hash builder to hash the
+    /// strings.
+    #[inline]
+    pub const fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            interner: Interner::with_hasher(hash_builder),
+            strings: RwLock::new(Vec::new()),
+            arena: Arena::new(),
+        }
+    }
+
+    /// Returns the number of interned strings.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.strings.read().unwrap().len()
+    }
+
+    /// Returns `true` if no strings are interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the string of `symbol`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `symbol` was not returned by this interner.
+    #[inline]
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        self.try_resolve(symbol)
+            .unwrap_or_else(|| panic!("symbol {} is not interned", symbol.as_u32()))
+    }
+
+    /// Returns the string of `symbol`, or `None` if no string with that symbol is interned.
+    #[inline]
+    pub fn try_resolve(&self, symbol: Symbol) -> Option<&str> {
+        self.strings.read().unwrap().get(symbol.as_usize()).copied()
+    }
+}
+
+impl<S> StringInterner<S>
+where
+    S: BuildHasher,
+{
+    /// Interns the string and returns its symbol, the string is only copied in to the arena if
+    /// it was not already interned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `u32::MAX` strings are interned.
+    pub fn intern(&self, value: &str) -> Symbol {
+        self.interner
+            .intern_ref(value, || {
+                // SAFTY: the string lives as long as the arena
+                let string: &'static str = unsafe { self.arena.alloc(Box::from(value)).as_ref() };
+                let mut strings = self.strings.write().unwrap();
+                let symbol = Symbol(
+                    u32::try_from(strings.len()).expect("more than u32::MAX strings interned"),
+                );
+                strings.push(string);
+                Entry { string, symbol }
+            })
+            .symbol
+    }
+
+    /// Returns the symbol of `value`, or `None` if it is not interned.
+    #[inline]
+    pub fn get(&self, value: &str) -> Option<Symbol> {
+        self.interner.get(value).map(|entry| entry.symbol)
+    }
+}
@@ -0,0 +1,37 @@
+use crate::stats::StuckTransfer;
+use std::fmt;
+use std::time::Duration;
+
+/// Calls the alert of a `InternerBuilder::stuck_transfer_alert` when the values of a table are
+/// not moved to the next table within the timeout.
+pub(crate) struct StuckTransferAlert {
+    pub(crate) timeout: Duration,
+    alert: Box<dyn Fn(StuckTransfer) + Send + Sync>,
+}
+
+impl StuckTransferAlert {
+    pub(crate) fn new(
+        timeout: Duration,
+        alert: impl Fn(StuckTransfer) + Send + Sync + 'static,
+    ) -> Self {
+        Self { timeout, alert: Box::new(alert) }
+    }
+
+    #[cold]
+    pub(crate) fn fire(&self, stuck_transfer: StuckTransfer) {
+        #[cfg(feature = "tracing")]
+        tracing::error!(
+            buckets = stuck_transfer.buckets,
+            to_be_moved = stuck_transfer.to_be_moved,
+            elapsed_ms = stuck_transfer.elapsed.as_millis() as u64,
+            "interner transfer stuck"
+        );
+        (self.alert)(stuck_transfer);
+    }
+}
+
+impl fmt::Debug for StuckTransferAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StuckTransferAlert").field("timeout", &self.timeout).finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,150 @@
+use crate::arena::Arena;
+use crate::interner::{DefaultHashBuilder, Interner};
+use crate::memoizer::{Entry, EntryRef};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::sync::RwLock;
+use std::time::Instant;
+
+/// When a value was first interned in a [`TimestampedInterner`].
+///
+/// `order` is the number of values that were interned before the value, so the values of an
+/// interner have distinct orders starting at 0 and a later `order` is never seen earlier. `at`
+/// is read from the clock when the value is inserted and is only as precise as [`Instant`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FirstSeen {
+    pub order: u64,
+    pub at: Instant,
+}
+
+/// An interner that records when each value is first interned.
+///
+/// The values are looked up by the value or anything it borrows as, like with the [`Interner`],
+/// the [`FirstSeen`] of a value is kept next to it and is not part of its hash or equality.
+///
+/// # Examples
+///
+/// ```
+/// use interner::TimestampedInterner;
+///
+/// let interner: TimestampedInterner<String> = TimestampedInterner::new();
+/// interner.intern("b".to_string());
+/// interner.intern("a".to_string());
+/// interner.intern("b".to_string());
+/// assert_eq!(0, interner.first_seen("b").unwrap().order);
+/// assert_eq!(1, interner.first_seen("a").unwrap().order);
+/// assert_eq!(None, interner.first_seen("c"));
+/// let arrivals: Vec<&str> = interner.iter_by_arrival().map(|(value, _)| value.as_str()).collect();
+/// assert_eq!(vec!["b", "a"], arrivals);
+/// ```
+pub struct TimestampedInterner<T, S = DefaultHashBuilder> {
+    // the references point in to `arena` and is only handed out with the lifetime of `self`
+    interner: Interner<EntryRef<T, FirstSeen>, S>,
+    // the entries indexed by `FirstSeen::order`
+    arrivals: RwLock<Vec<EntryRef<T, FirstSeen>>>,
+    arena: Arena<Entry<T, FirstSeen>>,
+}
+
+impl<T> TimestampedInterner<T, DefaultHashBuilder> {
+    /// Creates an empty `TimestampedInterner`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl<T> Default for TimestampedInterner<T, DefaultHashBuilder> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, S> TimestampedInterner<T, S> {
+    /// Creates an empty `TimestampedInterner` which will use the given hash builder to hash the
+    /// values.
+    #[inline]
+    pub const fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            interner: Interner::with_hasher(hash_builder),
+            arrivals: RwLock::new(Vec::new()),
+            arena: Arena::new(),
+        }
+    }
+
+    /// Returns the number of interned values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.arrivals.read().unwrap().len()
+    }
+
+    /// Returns `true` if no values are interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the interned values with their [`FirstSeen`] in the order they were first
+    /// interned.
+    ///
+    /// The values interned when the iterator is created are returned, the inserts are not
+    /// blocked while the iterator is used.
+    pub fn iter_by_arrival(&self) -> impl Iterator<Item = (&T, FirstSeen)> + '_ {
+        let arrivals = self.arrivals.read().unwrap().clone();
+        arrivals.into_iter().map(|entry| {
+            // SAFTY: the entry lives as long as `self`
+            let entry = unsafe { &*entry.0.as_ptr() };
+            (&entry.key, entry.value)
+        })
+    }
+}
+
+impl<T, S> TimestampedInterner<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Interns the value and returns a reference to the interned value, the first time a value
+    /// is interned its [`FirstSeen`] is recorded.
+    pub fn intern(&self, value: T) -> &T {
+        let entry = self.interner.intern(value, |key| {
+            let mut arrivals = self.arrivals.write().unwrap();
+            let value = FirstSeen { order: arrivals.len() as u64, at: Instant::now() };
+            let entry = EntryRef(self.arena.alloc(Box::new(Entry { key, value })));
+            arrivals.push(entry);
+            entry
+        });
+        // SAFTY: the entry lives as long as `self`
+        unsafe { &(*entry.0.as_ptr()).key }
+    }
+
+    /// Returns the interned value equal to `value`, or `None` if it is not interned.
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        // SAFTY: the entry lives as long as `self`
+        self.entry(value).map(|entry| unsafe { &(*entry.0.as_ptr()).key })
+    }
+
+    /// Returns when the value equal to `value` was first interned, or `None` if it is not
+    /// interned.
+    pub fn first_seen<Q>(&self, value: &Q) -> Option<FirstSeen>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entry(value).map(|entry| entry.entry().value)
+    }
+
+    #[inline]
+    fn entry<Q>(&self, value: &Q) -> Option<EntryRef<T, FirstSeen>>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.interner.hasher().hash_one(value);
+        self.interner.get_from_hash(hash, |entry| entry.entry().key.borrow() == value).copied()
+    }
+}
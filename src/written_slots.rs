@@ -0,0 +1,62 @@
+#[cfg(any(debug_assertions, feature = "checked"))]
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// The slots of a table that have been written, recorded in debug builds and with the `checked`
+/// feature and compiled out otherwise.
+///
+/// A slot is written before it is marked valid, so a read of a slot that is valid in meta data
+/// read with acquire ordering must find the slot recorded as written. The bits are written and
+/// read with relaxed ordering so the recording adds no synchronization that could hide a write
+/// that is elided or ordered after the valid bit.
+pub(crate) struct WrittenSlots {
+    // one bit per slot for each bucket
+    #[cfg(any(debug_assertions, feature = "checked"))]
+    buckets: Vec<AtomicU8>,
+}
+
+impl WrittenSlots {
+    /// Creates the record of a table without buckets.
+    #[inline]
+    pub(crate) const fn empty() -> Self {
+        Self {
+            #[cfg(any(debug_assertions, feature = "checked"))]
+            buckets: Vec::new(),
+        }
+    }
+
+    /// Creates the record of a table with `buckets` buckets where no slot is written.
+    #[inline]
+    pub(crate) fn new(buckets: usize) -> Self {
+        #[cfg(not(any(debug_assertions, feature = "checked")))]
+        let _ = buckets;
+        Self {
+            #[cfg(any(debug_assertions, feature = "checked"))]
+            buckets: (0..buckets).map(|_| AtomicU8::new(0)).collect(),
+        }
+    }
+
+    /// Records that the slot `index` of bucket `pos` is written, before the value is written and
+    /// the slot is marked valid.
+    #[inline]
+    pub(crate) fn mark_written(&self, pos: usize, index: usize) {
+        #[cfg(any(debug_assertions, feature = "checked"))]
+        assert!(
+            self.buckets[pos].fetch_or(1 << index, Ordering::Relaxed) & (1 << index) == 0,
+            "interner slot {index} of bucket {pos} written twice"
+        );
+        #[cfg(not(any(debug_assertions, feature = "checked")))]
+        let _ = (pos, index);
+    }
+
+    /// Asserts that the slot `index` of bucket `pos`, that is valid in the meta data, is written.
+    #[inline]
+    pub(crate) fn assert_written(&self, pos: usize, index: usize) {
+        #[cfg(any(debug_assertions, feature = "checked"))]
+        assert!(
+            self.buckets[pos].load(Ordering::Relaxed) & (1 << index) != 0,
+            "interner slot {index} of bucket {pos} is valid but was not written"
+        );
+        #[cfg(not(any(debug_assertions, feature = "checked")))]
+        let _ = (pos, index);
+    }
+}
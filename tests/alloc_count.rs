@@ -0,0 +1,87 @@
+//! Counts the allocations of the intern methods that take owned values, with a global allocator
+//! that counts the allocations of the current thread.
+
+use interner::Interner;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// interns a value on the current thread so the thread local state of the crate, like the ring
+// buffer of the `replay` feature, is allocated before the allocations are counted
+fn warm_up() {
+    let interner: Interner<u32> = Interner::with_capacity(16);
+    interner.intern(0, |value| value);
+}
+
+fn allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[test]
+fn intern_owned_string_reuses_the_allocation() {
+    warm_up();
+    let interner: Interner<&str> = Interner::with_capacity(16);
+    let owned = String::from("value");
+    let ptr = owned.as_ptr();
+    let (result, count) =
+        allocations(|| interner.intern_owned::<_, str>(owned, |owned| owned.leak()));
+    assert_eq!(0, count);
+    assert_eq!(ptr, result.as_ptr());
+    let owned = String::from("value");
+    let (again, count) = allocations(|| interner.intern_owned::<_, str>(owned, |_| unreachable!()));
+    assert_eq!(0, count);
+    assert!(std::ptr::eq(result, again));
+}
+
+#[test]
+fn intern_owned_bytes_reuses_the_allocation() {
+    warm_up();
+    let interner: Interner<&[u8]> = Interner::with_capacity(16);
+    let owned = vec![1u8, 2, 3];
+    let ptr = owned.as_ptr();
+    let (result, count) =
+        allocations(|| interner.intern_owned::<_, [u8]>(owned, |owned| owned.leak()));
+    assert_eq!(0, count);
+    assert_eq!(ptr, result.as_ptr());
+    let (again, count) =
+        allocations(|| interner.intern_owned::<_, [u8]>(vec![1u8, 2, 3], |owned| owned.leak()));
+    // only the allocation of the owned value that is dropped
+    assert_eq!(1, count);
+    assert!(std::ptr::eq(result, again));
+}
+
+#[test]
+fn intern_ref_of_interned_str_does_not_allocate() {
+    let interner: Interner<&str> = Interner::with_capacity(16);
+    interner.intern_str("value", || "value");
+    let (_, count) = allocations(|| interner.intern_ref("value", || unreachable!()));
+    assert_eq!(0, count);
+    let (_, count) = allocations(|| interner.intern_str("value", || unreachable!()));
+    assert_eq!(0, count);
+}
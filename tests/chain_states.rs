@@ -0,0 +1,200 @@
+//! Runs the public API against chains of partially moved tables that are built with the test
+//! hooks in `internals`, the states are otherwise only seen when inserts race with resizes.
+#![cfg(feature = "unstable-internals")]
+
+use interner::internals::{grow, move_buckets};
+use interner::interner::Cursor;
+use interner::{Interner, MergeError};
+use std::collections::HashSet;
+use std::hash::BuildHasher;
+
+const VALUES: u64 = 120;
+
+// a chain of 3 tables: table 0 with 16 buckets where the first half is moved, table 1 with 32
+// buckets where the first quarter is moved and the newest table 2 with 64 buckets. values are
+// interned before, between and after the moves so all tables have values.
+fn three_table_chain(values: &[u64]) -> Interner<&u64> {
+    let interner: Interner<&u64> = Interner::with_capacity(96);
+    let (first, rest) = values.split_at(values.len() / 4);
+    let (second, third) = rest.split_at(rest.len() / 2);
+    for value in first {
+        interner.intern_ref(value, || value);
+    }
+    grow(&interner);
+    assert!(!move_buckets(&interner, 0, 0..8));
+    for value in second {
+        interner.intern_ref(value, || value);
+    }
+    grow(&interner);
+    assert!(!move_buckets(&interner, 1, 0..8));
+    for value in third {
+        interner.intern_ref(value, || value);
+    }
+    let report = interner.fragmentation_report();
+    assert_eq!(3, report.tables.len());
+    assert_eq!(
+        vec![16, 32, 64],
+        report.tables.iter().map(|table| table.buckets).collect::<Vec<_>>()
+    );
+    assert!(report.tables[0].unmoved_buckets == 8 && report.tables[1].unmoved_buckets == 24);
+    assert!(report.tables.iter().all(|table| table.entries != 0));
+    interner
+}
+
+fn values() -> Vec<u64> {
+    (0..VALUES).collect()
+}
+
+#[test]
+fn intern_finds_all_values() {
+    let values = values();
+    let interner = three_table_chain(&values);
+    for value in values.iter() {
+        assert!(std::ptr::eq(value, interner.intern_ref(value, || unreachable!())));
+    }
+    assert_eq!(3, interner.fragmentation_report().tables.len());
+    assert_eq!(values.len(), interner.len());
+    assert!(interner.deep_slot_count() > values.len());
+}
+
+#[test]
+fn get_finds_all_values() {
+    let values = values();
+    let interner = three_table_chain(&values);
+    for value in values.iter() {
+        let hash = interner.hasher().hash_one(value);
+        assert_eq!(Some(&value), interner.get_from_hash(hash, |result| *result == value));
+    }
+    let missing = VALUES + 1;
+    let hash = interner.hasher().hash_one(missing);
+    assert_eq!(None, interner.get_from_hash(hash, |result| **result == missing));
+    let keys: Vec<u64> = (VALUES - 2..VALUES + 2).collect();
+    let result = interner.get_many(&keys);
+    assert_eq!(vec![2, 3], result.missing().collect::<Vec<_>>());
+}
+
+#[test]
+fn insert_new_fails_for_all_values() {
+    let values = values();
+    let interner = three_table_chain(&values);
+    for value in values.iter() {
+        assert!(interner.insert_new(value).is_err());
+    }
+}
+
+#[test]
+fn iter_chunk_returns_all_values_once() {
+    let values = values();
+    let interner = three_table_chain(&values);
+    let mut result = Vec::new();
+    let mut cursor = Some(Cursor::default());
+    while let Some(current) = cursor {
+        let (chunk, next) = interner.iter_chunk(current, 7);
+        result.extend(chunk.into_iter().copied());
+        cursor = next;
+    }
+    result.sort_unstable();
+    assert_eq!(values, result);
+}
+
+#[test]
+fn visit_bucket_of_finds_all_values() {
+    use interner::stats::SlotInfo;
+    let values = values();
+    let interner = three_table_chain(&values);
+    for value in values.iter() {
+        let mut found = 0;
+        interner.visit_bucket_of(interner.hasher().hash_one(value), |bucket| {
+            found += bucket
+                .slots
+                .iter()
+                .filter(|slot| matches!(slot, SlotInfo::Valid { value: result, .. } if **result == value))
+                .count();
+        });
+        assert!(found >= 1);
+    }
+}
+
+#[test]
+fn sample_returns_interned_values() {
+    let values = values();
+    let interner = three_table_chain(&values);
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let sample = interner.sample(32, || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    });
+    assert!(!sample.is_empty());
+    assert!(sample.iter().all(|value| **value < VALUES));
+}
+
+#[test]
+fn merge_and_set_operations() {
+    let values = values();
+    let interner = three_table_chain(&values);
+    let child = interner.child();
+    let even: Vec<&u64> = values.iter().filter(|value| *value % 2 == 0).collect();
+    for value in even.iter() {
+        child.intern_ref(*value, || value);
+    }
+    let both: HashSet<&u64> = interner.intersection(&child).unwrap().collect();
+    assert_eq!(even.iter().copied().collect::<HashSet<_>>(), both);
+    assert_eq!(values.len() - even.len(), interner.difference(&child).unwrap().count());
+    assert_eq!(0, child.difference(&interner).unwrap().count());
+    assert_eq!(Ok(0), interner.merge(&child));
+    assert_eq!(Ok(values.len() - even.len()), child.merge(&interner));
+    assert!(matches!(interner.merge(&Interner::new()), Err(MergeError::HasherMismatch)));
+}
+
+#[test]
+fn finishing_the_moves_keeps_all_values() {
+    let values = values();
+    let interner = three_table_chain(&values);
+    assert!(move_buckets(&interner, 0, 8..16));
+    assert!(move_buckets(&interner, 1, 8..32));
+    let report = interner.fragmentation_report();
+    assert_eq!(0, report.tables[0].unmoved_buckets);
+    assert_eq!(0, report.tables[1].unmoved_buckets);
+    assert_eq!(values.len(), report.tables[2].entries);
+    for value in values.iter() {
+        assert!(std::ptr::eq(value, interner.intern_ref(value, || unreachable!())));
+    }
+}
+
+#[test]
+fn adaptive_get_start_finds_values_in_both_tables() {
+    use interner::InternerBuilder;
+    let values: Vec<u64> = (0..2000).collect();
+    let (old, new) = values.split_at(values.len() / 2);
+    let interner: Interner<&u64> =
+        InternerBuilder::new().capacity(3000).adaptive_get_start(true).build();
+    for value in old {
+        interner.intern_ref(value, || value);
+    }
+    grow(&interner);
+    let buckets = interner.fragmentation_report().tables[0].buckets;
+    // all but the last bucket are moved so the first table stays the current table, the values
+    // in the moved buckets are still found in it but the new values are only in the next table
+    assert!(!move_buckets(&interner, 0, 0..buckets - 1));
+    for value in new {
+        interner.intern_ref(value, || value);
+    }
+    let get = |value: &u64| {
+        let hash = interner.hasher().hash_one(value);
+        interner.get_from_hash(hash, |result| *result == value).copied()
+    };
+    for _ in 0..100 {
+        for value in new {
+            assert_eq!(Some(value), get(value));
+        }
+    }
+    let penalty = interner.estimated_chain_read_penalty().unwrap();
+    assert!(penalty > 0.5, "{penalty}");
+    // the lookups start in the next table now and still find the values of the first table
+    for value in values.iter() {
+        assert_eq!(Some(value), get(value));
+    }
+    assert_eq!(None, get(&(values.len() as u64)));
+}
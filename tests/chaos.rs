@@ -0,0 +1,43 @@
+//! Stress tests with the faults of the `chaos` feature injected.
+#![cfg(feature = "chaos")]
+
+use interner::chaos::{self, Chaos};
+use interner::Interner;
+use std::time::Duration;
+
+#[test]
+fn chained_resizes_with_faults() {
+    let values: Vec<u64> = (0..4096).collect();
+    let interner: Interner<&u64> = Interner::new();
+    let interned: Vec<Vec<usize>> = std::thread::scope(|scope| {
+        let threads: Vec<_> = (0..8u64)
+            .map(|thread| {
+                let values = &values;
+                let interner = &interner;
+                scope.spawn(move || {
+                    chaos::set(Chaos {
+                        unlock_delay: if thread % 2 == 0 { 0.01 } else { 0.0 },
+                        cas_failure: 0.3,
+                        transfer_stall: if thread % 2 == 1 { 0.05 } else { 0.0 },
+                        delay: Duration::from_micros(20),
+                        seed: thread,
+                    });
+                    let interned = values
+                        .iter()
+                        .map(|value| *interner.intern_ref(value, || value) as *const u64 as usize)
+                        .collect();
+                    chaos::clear();
+                    interned
+                })
+            })
+            .collect();
+        threads.into_iter().map(|thread| thread.join().unwrap()).collect()
+    });
+    assert_eq!(values.len(), interner.len());
+    for thread in interned.iter() {
+        assert_eq!(&interned[0], thread);
+    }
+    for value in values.iter() {
+        assert_eq!(Some(value), interner.get(value));
+    }
+}
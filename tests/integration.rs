@@ -1,5 +1,5 @@
 use fxhash::FxBuildHasher;
-use interner::Interner;
+use interner::{Interner, InternerBuilder};
 use rayon::prelude::*;
 use std::sync::Arc;
 
@@ -10,6 +10,38 @@ fn drop_empty() {
     let interner: Interner<&i32> = Interner::new();
     drop(interner);
 }
+
+#[test]
+fn empty_table() {
+    use std::hash::BuildHasher;
+    let values: Vec<u64> = (0..ITER).collect();
+    let interners: [Interner<&u64>; 2] = [Interner::new(), Interner::with_capacity(0)];
+    for interner in interners {
+        assert_eq!(0, interner.len());
+        assert!(interner.is_empty());
+        assert_eq!(None, interner.get(&values[1]));
+        let hash = interner.hasher().hash_one(values[1]);
+        assert_eq!(None, interner.get_from_hash(hash, |_| true));
+        let mut visited = 0;
+        interner.visit_bucket_of(hash, |_| visited += 1);
+        assert_eq!(0, visited);
+        assert_eq!(0, interner.fragmentation_report().tables[0].buckets);
+
+        assert_eq!(&values[1], interner.intern_ref(&values[1], || &values[1]));
+        assert_eq!(1, interner.len());
+        assert_eq!(Some(&values[1]), interner.get(&values[1]));
+        for value in values.iter() {
+            assert_eq!(value, interner.intern_ref(value, || value));
+        }
+        assert_eq!(ITER as usize, interner.len());
+    }
+}
+
+#[test]
+#[should_panic(expected = "Interner capacity overflow")]
+fn capacity_overflow() {
+    let _interner: Interner<&u64> = Interner::with_capacity(usize::MAX);
+}
 #[test]
 fn intern_ref() {
     let value1: i32 = 42;
@@ -42,13 +74,11 @@ fn intern_ref() {
 
 #[test]
 fn intern_ref_array() {
-    use std::hash::{BuildHasher, Hash, Hasher};
+    use std::hash::BuildHasher;
     let array = [42, 2, 3, 4, 5, 6, 7, 8, 9];
     let interner: Interner<&[i32]> = Interner::new();
     let slice = &array[..];
-    let mut state = interner.hasher().build_hasher();
-    slice.hash(&mut state);
-    let hash = state.finish();
+    let hash = interner.hasher().hash_one(slice);
     let is_match = |val: &&[i32]| *val == slice;
 
     let result = interner.intern_ref(slice, || slice);
@@ -94,12 +124,12 @@ fn single_threaded_intern_ref3() {
     let values = values.into_boxed_slice();
 
     let interner = Interner::with_capacity_and_hasher(ITER as usize, FxBuildHasher::default());
-    (1..ITER).into_iter().for_each(|i: u64| {
+    (1..ITER).for_each(|i: u64| {
         interner.intern_ref(&i, || values.get(i as usize).unwrap());
         interner.intern_ref(&i, || unimplemented!("value: {}", i));
     });
 
-    (1..ITER).into_iter().for_each(|i: u64| {
+    (1..ITER).for_each(|i: u64| {
         interner.intern_ref(&i, || unimplemented!("value: {}", i));
     });
 }
@@ -116,7 +146,7 @@ fn multi_threaded_intern_ref3() {
         assert_eq!(i, *result);
     });
 
-    (1..ITER).into_iter().for_each(|i: u64| {
+    (1..ITER).for_each(|i: u64| {
         let result = interner.intern_ref(&i, || panic!("value {}", i));
         assert_eq!(i, *result);
     });
@@ -128,12 +158,12 @@ fn single_threaded_resize() {
     let values = values.into_boxed_slice();
 
     let interner = Interner::with_hasher(FxBuildHasher::default());
-    (1..ITER).into_iter().for_each(|i: u64| {
+    (1..ITER).for_each(|i: u64| {
         interner.intern_ref(&i, || values.get(i as usize).unwrap());
         interner.intern_ref(&i, || unimplemented!("value: {}", i));
     });
 
-    (1..ITER).into_iter().for_each(|i: u64| {
+    (1..ITER).for_each(|i: u64| {
         interner.intern_ref(&i, || unimplemented!("value: {}", i));
     });
 }
@@ -150,7 +180,46 @@ fn multi_threaded_resize() {
         assert_eq!(i, *result);
     });
 
-    (1..ITER).into_iter().for_each(|i: u64| {
+    (1..ITER).for_each(|i: u64| {
+        let result = interner.intern_ref(&i, || unimplemented!());
+        assert_eq!(i, *result);
+    });
+}
+
+#[test]
+fn multi_threaded_resize_seq_cst() {
+    let values: Arc<Vec<u64>> = Arc::new((0..ITER).collect());
+
+    let interner: Arc<Interner<&u64, FxBuildHasher>> =
+        Arc::new(InternerBuilder::new().hasher(FxBuildHasher::default()).seq_cst(true).build());
+    (1..ITER).into_par_iter().for_each(|i: u64| {
+        interner.intern_ref(&i, || (*values).get(i as usize).unwrap());
+        let result = interner.intern_ref(&i, || unimplemented!());
+        assert_eq!(i, *result);
+    });
+
+    (1..ITER).for_each(|i: u64| {
+        let result = interner.intern_ref(&i, || unimplemented!());
+        assert_eq!(i, *result);
+    });
+}
+
+#[test]
+fn multi_threaded_resize_with_read_transfer_budget() {
+    use std::hash::BuildHasher;
+    let values: Arc<Vec<u64>> = Arc::new((0..ITER).collect());
+
+    let interner: Arc<Interner<&u64, FxBuildHasher>> = Arc::new(
+        InternerBuilder::new().hasher(FxBuildHasher::default()).read_transfer_budget(4).build(),
+    );
+    (1..ITER).into_par_iter().for_each(|i: u64| {
+        interner.intern_ref(&i, || (*values).get(i as usize).unwrap());
+        let hash = interner.hasher().hash_one(i);
+        let result = interner.get_from_hash(hash, |val| **val == i);
+        assert_eq!(i, **result.unwrap());
+    });
+
+    (1..ITER).for_each(|i: u64| {
         let result = interner.intern_ref(&i, || unimplemented!());
         assert_eq!(i, *result);
     });
@@ -173,3 +242,1619 @@ fn intern_same_value_no_initial_capacity() {
     assert_eq!(&42, result);
     thread.join().unwrap();
 }
+
+#[test]
+fn multi_threaded_intern_same_values() {
+    let values: Vec<Vec<u64>> = (0..4).map(|_| (0..ITER).collect()).collect();
+
+    let interner: Interner<&u64, FxBuildHasher> =
+        Interner::with_capacity_and_hasher(ITER as usize, FxBuildHasher::default());
+    let results: Vec<Vec<&u64>> = values
+        .par_iter()
+        .map(|values| values.iter().map(|i| interner.intern_ref(i, || i)).collect())
+        .collect();
+
+    for i in 0..ITER as usize {
+        assert_eq!(i as u64, *results[0][i]);
+        for result in &results[1..] {
+            assert!(std::ptr::eq(results[0][i], result[i]));
+        }
+    }
+}
+
+#[test]
+fn intern_with_ascii_case_insensitive() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
+
+    #[derive(Default)]
+    struct AsciiCaseInsensitiveHasher(DefaultHasher);
+
+    impl Hasher for AsciiCaseInsensitiveHasher {
+        fn finish(&self) -> u64 {
+            self.0.finish()
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            for byte in bytes {
+                self.0.write_u8(byte.to_ascii_lowercase());
+            }
+        }
+    }
+
+    let values: Vec<String> = (0..ITER).map(|i| format!("Value{}", i)).collect();
+    let interner: Interner<&str, BuildHasherDefault<AsciiCaseInsensitiveHasher>> =
+        Interner::with_hasher(Default::default());
+    let eq = |val: &&str, value: &str| val.eq_ignore_ascii_case(value);
+
+    for value in values.iter() {
+        let hash = interner.hasher().hash_one(value.as_str());
+        let result = interner.intern_with(hash, value.as_str(), eq, || value.as_str());
+        assert!(std::ptr::eq(value.as_str(), result));
+    }
+    for value in values.iter() {
+        let upper = value.to_ascii_uppercase();
+        let hash = interner.hasher().hash_one(upper.as_str());
+        let result = interner.intern_with(hash, upper.as_str(), eq, || unimplemented!());
+        assert!(std::ptr::eq(value.as_str(), result));
+    }
+}
+
+#[test]
+fn dedup_stream() {
+    let values: Vec<String> = (0..ITER).map(|i| format!("value{}", i % 1000)).collect();
+    let interner: Interner<&str> = Interner::new();
+
+    let interned: Vec<&str> =
+        interner.dedup_stream(values.iter().map(|value| value.as_str()), |val| val).collect();
+    assert_eq!(values.len(), interned.len());
+    for (i, (value, result)) in values.iter().zip(interned.iter()).enumerate() {
+        assert_eq!(value, result);
+        assert!(std::ptr::eq(values[i % 1000].as_str(), *result));
+    }
+}
+
+#[test]
+fn sample() {
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::new();
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let mut rng = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    assert!(interner.sample(10, &mut rng).is_empty());
+    for value in values.iter() {
+        interner.intern_ref(value, || value);
+    }
+    let sample = interner.sample(1000, &mut rng);
+    assert_eq!(1000, sample.len());
+    for value in sample {
+        assert!(std::ptr::eq(&values[*value as usize], value));
+    }
+}
+
+#[test]
+fn fragmentation_report() {
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::with_capacity(16);
+    for value in values.iter() {
+        interner.intern_ref(value, || value);
+    }
+
+    let report = interner.fragmentation_report();
+    assert!(report.tables.len() > 1);
+    let current = report.tables.iter().position(|table| table.is_current).unwrap();
+    let non_current_bytes: usize = report.tables[..current].iter().map(|table| table.bytes).sum();
+    assert_eq!(non_current_bytes, report.non_current_bytes);
+    for table in report.tables[..current].iter() {
+        assert_eq!(0, table.unmoved_buckets);
+    }
+    let newest = report.tables.last().unwrap();
+    assert_eq!(newest.buckets, newest.unmoved_buckets);
+}
+
+#[test]
+fn multi_threaded_intern_serialized() {
+    use interner::serialized::Token;
+    use interner::{Codec, SerializedInterner};
+
+    struct PairCodec;
+
+    impl Codec<(u64, String)> for PairCodec {
+        fn encode(&self, key: &(u64, String), buf: &mut Vec<u8>) {
+            buf.extend_from_slice(&key.0.to_le_bytes());
+            buf.extend_from_slice(key.1.as_bytes());
+        }
+    }
+
+    let interner = SerializedInterner::new(PairCodec);
+    let tokens: Vec<Vec<Token>> = (0..4)
+        .into_par_iter()
+        .map(|_| {
+            (0..ITER).map(|i| interner.intern_serialized(&(i, format!("{}", i % 7)))).collect()
+        })
+        .collect();
+    for i in 0..ITER as usize {
+        assert!(tokens.iter().all(|thread_tokens| thread_tokens[i] == tokens[0][i]));
+        let mut bytes = (i as u64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(format!("{}", i % 7).as_bytes());
+        assert_eq!(&bytes[..], tokens[0][i].as_bytes());
+    }
+}
+
+#[test]
+fn max_memory_bytes() {
+    use interner::InternError;
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = InternerBuilder::new().max_memory_bytes(64 * 1024).build();
+
+    let mut interned = 0;
+    let error = loop {
+        let value = &values[interned];
+        match interner.try_intern_ref(value, || value) {
+            Ok(result) => assert!(std::ptr::eq(value, result)),
+            Err(error) => break error,
+        }
+        interned += 1;
+    };
+    let InternError::MemoryLimit { used, limit } = error else { unreachable!() };
+    assert_eq!(64 * 1024, limit);
+    assert!(used <= limit);
+    for value in values[..interned].iter() {
+        assert!(std::ptr::eq(value, interner.try_intern_ref(value, || unimplemented!()).unwrap()));
+    }
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        interner.intern_ref(&values[interned], || unreachable!())
+    }));
+    assert!(result.is_err());
+}
+
+#[test]
+fn visit_bucket_of() {
+    use interner::stats::SlotInfo;
+    use std::hash::BuildHasher;
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::new();
+    for value in values.iter() {
+        interner.intern_ref(value, || value);
+    }
+
+    for value in values.iter().step_by(97) {
+        let hash = interner.hasher().hash_one(value);
+        let mut found = 0;
+        interner.visit_bucket_of(hash, |bucket| {
+            for slot in bucket.slots.iter() {
+                if let SlotInfo::Valid { value: slot_value, .. } = slot {
+                    if std::ptr::eq(**slot_value, value) {
+                        found += 1;
+                    }
+                }
+            }
+        });
+        assert_eq!(1, found);
+    }
+}
+
+#[test]
+fn capacity_policy() {
+    use interner::CapacityPolicy;
+    let values: Vec<u64> = (0..ITER).collect();
+    let policy = Arc::new(CapacityPolicy::new(0));
+
+    for _ in 0..3 {
+        let interner: Interner<&u64> =
+            InternerBuilder::new().capacity_policy(Arc::clone(&policy)).build();
+        for value in values.iter() {
+            interner.intern_ref(value, || value);
+        }
+    }
+    assert_eq!(3, policy.recorded_interners());
+    assert!(policy.recommended_capacity() >= ITER as usize);
+    let resizes = policy.recorded_resizes();
+    assert!(resizes > 0);
+
+    // the recommended capacity is big enough so no more resizes are needed
+    let interner: Interner<&u64> =
+        InternerBuilder::new().capacity_policy(Arc::clone(&policy)).build();
+    for value in values.iter() {
+        interner.intern_ref(value, || value);
+    }
+    assert_eq!(1, interner.fragmentation_report().tables.len());
+    drop(interner);
+    assert_eq!(resizes, policy.recorded_resizes());
+}
+
+#[test]
+fn multi_threaded_resize_wide_h2() {
+    use std::hash::BuildHasher;
+    let values: Arc<Vec<u64>> = Arc::new((0..ITER).collect());
+
+    let interner: Arc<Interner<&u64>> = Arc::new(InternerBuilder::new().wide_h2(true).build());
+    (1..ITER).into_par_iter().for_each(|i: u64| {
+        interner.intern_ref(&i, || (*values).get(i as usize).unwrap());
+        let result = interner.intern_ref(&i, || unimplemented!());
+        assert_eq!(i, *result);
+    });
+
+    (1..ITER).for_each(|i: u64| {
+        let hash = interner.hasher().hash_one(i);
+        let result = interner.get_from_hash(hash, |val| **val == i);
+        assert_eq!(Some(&&values[i as usize]), result);
+    });
+}
+
+#[test]
+fn multi_threaded_resize_with_cancelled_token() {
+    use interner::CancellationToken;
+    use std::hash::BuildHasher;
+    let values: Arc<Vec<u64>> = Arc::new((0..ITER).collect());
+    let cancellation_token = CancellationToken::new();
+
+    let interner: Arc<Interner<&u64>> =
+        Arc::new(InternerBuilder::new().cancellation_token(cancellation_token.clone()).build());
+    (1..ITER / 2).into_par_iter().for_each(|i: u64| {
+        interner.intern_ref(&i, || (*values).get(i as usize).unwrap());
+    });
+    cancellation_token.cancel_and_wait();
+    (1..ITER).into_par_iter().for_each(|i: u64| {
+        interner.intern_ref(&i, || (*values).get(i as usize).unwrap());
+        let result = interner.intern_ref(&i, || unimplemented!());
+        assert_eq!(i, *result);
+    });
+
+    (1..ITER).for_each(|i: u64| {
+        let hash = interner.hasher().hash_one(i);
+        let result = interner.get_from_hash(hash, |val| **val == i);
+        assert_eq!(Some(&&values[i as usize]), result);
+    });
+}
+
+#[test]
+fn multi_threaded_memoizer_computes_once() {
+    use interner::Memoizer;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let memoizer: Memoizer<u64, String> = Memoizer::new();
+    let computed = AtomicUsize::new(0);
+
+    (0..4 * ITER).into_par_iter().for_each(|i: u64| {
+        let key = i % ITER;
+        let value = memoizer.get_or_compute(key, |key| {
+            computed.fetch_add(1, Ordering::Relaxed);
+            key.to_string()
+        });
+        assert_eq!(&key.to_string(), value);
+    });
+    assert_eq!(ITER as usize, computed.load(Ordering::Relaxed));
+}
+
+#[test]
+fn multi_threaded_payload_initialized_once() {
+    use interner::InternerWithPayload;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let interner: InternerWithPayload<u64, String> = InternerWithPayload::new();
+    let initialized = AtomicUsize::new(0);
+
+    (0..4 * ITER).into_par_iter().for_each(|i: u64| {
+        let key = i % ITER;
+        if i.is_multiple_of(3) {
+            assert_eq!(&key, interner.intern(key));
+        }
+        let (canonical, payload) = interner.get_or_init(key, |key| {
+            initialized.fetch_add(1, Ordering::Relaxed);
+            key.to_string()
+        });
+        assert_eq!(&key, canonical);
+        assert_eq!(&key.to_string(), payload);
+        assert!(std::ptr::eq(canonical, interner.intern(key)));
+    });
+    assert_eq!(ITER as usize, initialized.load(Ordering::Relaxed));
+    assert_eq!(ITER as usize, interner.len());
+    assert_eq!(Some(&"7".to_string()), interner.payload(&7));
+}
+
+#[test]
+fn multi_threaded_keyed_interner_made_once() {
+    use interner::KeyedInterner;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let keys: Vec<String> = (0..ITER).map(|key| key.to_string()).collect();
+    let interner: KeyedInterner<&str, (u64, usize)> = KeyedInterner::new();
+    let made = AtomicUsize::new(0);
+
+    (0..4 * ITER).into_par_iter().for_each(|i: u64| {
+        let key = keys[(i % ITER) as usize].as_str();
+        let value = interner.intern_with(key, || {
+            made.fetch_add(1, Ordering::Relaxed);
+            (i % ITER, key.len())
+        });
+        assert_eq!((i % ITER, key.len()), value);
+        assert_eq!(Some(value), interner.get(key));
+    });
+    assert_eq!(ITER as usize, made.load(Ordering::Relaxed));
+    assert_eq!(ITER as usize, interner.len());
+    assert_eq!(Some((7, 1)), interner.get("7"));
+    assert_eq!(None, interner.get("x"));
+}
+
+#[test]
+fn get_from_hash_with_displaced_values() {
+    use std::hash::BuildHasher;
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner = Interner::<&u64, FxBuildHasher>::with_capacity_and_hasher(
+        ITER as usize,
+        FxBuildHasher::default(),
+    );
+    for value in values.iter().step_by(2) {
+        interner.intern_ref(value, || value);
+    }
+
+    for value in values.iter() {
+        let hash = interner.hasher().hash_one(value);
+        let result = interner.get_from_hash(hash, |val| *val == value);
+        if value % 2 == 0 {
+            assert!(std::ptr::eq(value, *result.unwrap()));
+        } else {
+            assert!(result.is_none());
+        }
+    }
+}
+
+#[test]
+fn approx_memory_per_entry() {
+    use std::alloc::Layout;
+    use std::mem::size_of;
+
+    fn check<T: Copy + Eq + std::hash::Hash>(values: &[T]) {
+        let interner: Interner<T> = Interner::with_capacity(6);
+        for value in values {
+            interner.intern(*value, |value| value);
+        }
+        let report = interner.fragmentation_report();
+        assert_eq!(1, report.tables.len());
+        assert_eq!(1, report.tables[0].buckets);
+        // a bucket is at least 8 bytes of meta data and 7 slots, padded to a cache line, plus
+        // one byte of displacement hint, features can add debug state to the bucket
+        let bucket_bytes = report.tables[0].bucket_bytes;
+        let min_bucket_bytes = Layout::from_size_align(8 + 7 * size_of::<T>(), 64).unwrap();
+        assert!(bucket_bytes > min_bucket_bytes.pad_to_align().size());
+        assert_eq!(1, bucket_bytes % 64);
+        assert_eq!(bucket_bytes, report.tables[0].bytes);
+        let expected = bucket_bytes as f64 / values.len() as f64;
+        assert_eq!(Some(expected), interner.approx_memory_per_entry());
+    }
+
+    check(&[1u8, 2, 3]);
+    check(&[1u64, 2, 3, 4]);
+    check(&[[1u64; 8], [2u64; 8]]);
+    check(&[[1u64; 20]]);
+}
+
+#[test]
+fn iter_chunk() {
+    use interner::interner::Cursor;
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::new();
+    for value in values.iter() {
+        interner.intern_ref(value, || value);
+    }
+
+    let mut cursor = Some(Cursor::default());
+    let mut result: Vec<&u64> = Vec::new();
+    while let Some(current) = cursor {
+        let (chunk, next) = interner.iter_chunk(current, 1000);
+        assert!(next.is_none() || chunk.len() == 1000);
+        result.extend(chunk);
+        cursor = next;
+    }
+    result.sort();
+    assert_eq!(values.iter().collect::<Vec<_>>(), result);
+}
+
+#[test]
+fn multi_threaded_session() {
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::new();
+
+    interner.in_scope(|session| {
+        let sessions: Vec<_> = (0..4).map(|_| session.clone()).collect();
+        rayon::scope(|scope| {
+            for session in sessions {
+                let values = &values;
+                scope.spawn(move |_| {
+                    for value in values.iter().chain(values.iter()) {
+                        let result = session.intern_ref(value, || value);
+                        assert!(std::ptr::eq(value, result));
+                    }
+                });
+            }
+        });
+    });
+}
+
+#[test]
+fn weak_interner_in_cache() {
+    use interner::WeakInterner;
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Arc<Interner<&u64>> = Arc::new(Interner::new());
+    let cache: Vec<WeakInterner<&u64>> = (0..4).map(|_| Interner::downgrade(&interner)).collect();
+
+    cache.par_iter().for_each(|weak| {
+        let interner = weak.upgrade().unwrap();
+        for value in values.iter() {
+            assert!(std::ptr::eq(value, interner.intern_ref(value, || value)));
+        }
+    });
+    drop(interner);
+    assert!(cache.iter().all(|weak| weak.upgrade().is_none()));
+}
+
+#[test]
+fn multi_threaded_sharded_interners() {
+    use std::hash::{BuildHasher, BuildHasherDefault};
+    const SHARDS: usize = 5;
+    let hash_builder = BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default();
+    let values: Vec<u64> = (0..ITER).collect();
+    let shards: Vec<Interner<&u64, _>> =
+        (0..SHARDS).map(|_| Interner::with_hasher(hash_builder.clone())).collect();
+
+    values.par_iter().for_each(|value| {
+        let hash = hash_builder.hash_one(value);
+        let shard = Interner::<&u64>::shard_hint(hash, SHARDS);
+        assert!(std::ptr::eq(value, shards[shard].intern_ref(value, || value)));
+    });
+    for shard in shards.iter() {
+        let entries: usize =
+            shard.fragmentation_report().tables.iter().map(|table| table.entries).sum();
+        assert!(entries > ITER as usize / SHARDS / 2);
+    }
+}
+
+#[test]
+fn multi_threaded_resize_static_interner() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::BuildHasherDefault;
+    static INTERNER: Interner<u64, BuildHasherDefault<DefaultHasher>> =
+        Interner::with_hasher(BuildHasherDefault::new());
+
+    (1..ITER).into_par_iter().for_each(|i: u64| {
+        assert_eq!(i, INTERNER.intern(i, |i| i));
+    });
+    (1..ITER).for_each(|i: u64| {
+        assert_eq!(i, INTERNER.intern(i, |_| unimplemented!()));
+    });
+    assert!(INTERNER.fragmentation_report().tables.len() > 1);
+}
+
+#[test]
+fn multi_threaded_intern_normalized() {
+    use interner::NormalizingInterner;
+    use std::borrow::Cow;
+    let values: Vec<String> = (0..ITER).map(|i| format!("Value{i}")).collect();
+    let lowercase: Vec<String> = values.iter().map(|value| value.to_lowercase()).collect();
+    let interner: NormalizingInterner<&str, str> =
+        InternerBuilder::new().build_normalizing(|value: &str| {
+            if value.chars().any(char::is_uppercase) {
+                Cow::Owned(value.to_lowercase())
+            } else {
+                Cow::Borrowed(value)
+            }
+        });
+
+    values.par_iter().zip(lowercase.par_iter()).for_each(|(value, lowercase)| {
+        let result = interner.intern_ref(value, |_| lowercase);
+        assert!(std::ptr::eq(result, lowercase.as_str()));
+    });
+    values.iter().zip(lowercase.iter()).for_each(|(value, lowercase)| {
+        let upper = value.to_uppercase();
+        assert!(std::ptr::eq(interner.get(&upper).unwrap(), lowercase.as_str()));
+        assert!(std::ptr::eq(interner.intern_ref(value, |_| unimplemented!()), lowercase.as_str()));
+    });
+    assert!(interner.get("missing").is_none());
+}
+
+#[test]
+fn multi_threaded_intern_in_arena() {
+    use interner::{Arena, ArenaInterner};
+    let values: Vec<String> = (0..ITER).map(|i| i.to_string()).collect();
+    let arena: Arena<str> = Arena::new();
+    let interned: Vec<&str> = {
+        let interner: ArenaInterner<'_, str, FxBuildHasher> =
+            ArenaInterner::with_hasher(&arena, FxBuildHasher::default());
+        values.par_iter().for_each(|value| {
+            interner.intern_ref(value, |value| value.into());
+        });
+        values.iter().map(|value| interner.intern_ref(value, |_| unimplemented!())).collect()
+    };
+    assert_eq!(ITER as usize, arena.len());
+    for (value, interned) in values.iter().zip(interned) {
+        assert_eq!(value, interned);
+    }
+}
+
+#[test]
+fn get_many() {
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64, FxBuildHasher> = Interner::with_hasher(FxBuildHasher::default());
+    values.par_iter().filter(|value| *value % 3 == 0).for_each(|value| {
+        interner.intern_ref(value, || value);
+    });
+
+    let result = interner.get_many(&values);
+    assert_eq!(values.len(), result.values.len());
+    for (value, result) in values.iter().zip(result.values.iter()) {
+        assert_eq!(value % 3 == 0, *result == Some(value));
+    }
+    let missing: Vec<usize> = result.missing().collect();
+    let expected: Vec<usize> = (0..ITER as usize).filter(|i| i % 3 != 0).collect();
+    assert_eq!(expected, missing);
+    assert!(interner.get_many::<u64>(&[]).values.is_empty());
+}
+
+#[test]
+fn multi_threaded_collision_detection_without_collisions() {
+    use interner::CollisionThresholds;
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = InternerBuilder::new()
+        .collision_detection(CollisionThresholds::default(), |report| {
+            panic!("unexpected collisions: {report:?}")
+        })
+        .build();
+    values.par_iter().for_each(|value| {
+        interner.intern_ref(value, || value);
+    });
+    let report = interner.collision_report().unwrap();
+    assert!(report.inserts >= ITER as usize);
+    assert!(report.max_probe_length <= CollisionThresholds::default().max_probe_length);
+}
+
+#[test]
+fn multi_threaded_insert_new() {
+    use interner::AlreadyPresent;
+    let interner: Interner<u64, FxBuildHasher> = Interner::with_hasher(FxBuildHasher::default());
+    // every value is inserted by two threads, only one of them may succeed
+    let inserted: usize = (0..2 * ITER)
+        .into_par_iter()
+        .map(|i| match interner.insert_new(i / 2) {
+            Ok(value) => {
+                assert_eq!(i / 2, value);
+                1
+            }
+            Err(AlreadyPresent(value)) => {
+                assert_eq!(i / 2, value);
+                0
+            }
+        })
+        .sum();
+    assert_eq!(ITER as usize, inserted);
+}
+
+#[test]
+fn multi_threaded_intern_by_content() {
+    use interner::{ContentInterner, Verification};
+    let blobs: Vec<Vec<u8>> = (0..ITER).map(|i| vec![i as u8; 64 + (i % 256) as usize]).collect();
+    for verification in [Verification::VerifyWithEq, Verification::TrustHash] {
+        let interner: ContentInterner<'_, [u8], FxBuildHasher> =
+            ContentInterner::with_hasher(verification, FxBuildHasher::default());
+        let interned: Vec<&[u8]> =
+            blobs.par_iter().map(|blob| interner.intern_ref(blob, || blob)).collect();
+        // blob `i` has the same content as blob `i % 256`
+        for (i, (blob, result)) in blobs.iter().zip(interned.iter()).enumerate() {
+            assert_eq!(blob, result);
+            assert!(std::ptr::eq(interned[i % 256], *result));
+        }
+    }
+}
+
+#[test]
+fn multi_threaded_resize_strict_two_table() {
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64, FxBuildHasher> =
+        InternerBuilder::new().hasher(FxBuildHasher::default()).strict_two_table(true).build();
+    values.par_iter().for_each(|value| {
+        assert!(std::ptr::eq(value, interner.intern_ref(value, || value)));
+        assert!(interner.max_chain_depth() <= 2);
+    });
+    values.iter().for_each(|value| {
+        assert!(std::ptr::eq(value, interner.intern_ref(value, || unimplemented!())));
+    });
+    assert!(interner.fragmentation_report().tables.len() > 2);
+}
+
+#[test]
+fn multi_threaded_intern_with_preset() {
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64, FxBuildHasher> = InternerBuilder::new()
+        .hasher(FxBuildHasher::default())
+        .build_with_preset(values.iter().step_by(2).chain(values.iter().step_by(4)));
+    assert_eq!(ITER as usize / 2, interner.preset_len());
+
+    values.par_iter().for_each(|value| {
+        let result = interner.intern_ref(value, || {
+            assert!(value % 2 == 1, "preset value {value} created");
+            value
+        });
+        assert!(std::ptr::eq(value, result));
+    });
+    let entries: usize =
+        interner.fragmentation_report().tables.iter().map(|table| table.entries).sum();
+    assert_eq!(ITER as usize / 2, entries);
+    let result = interner.get_many(&values);
+    assert_eq!(0, result.missing().count());
+}
+
+#[test]
+fn multi_threaded_slow_make_report() {
+    use std::time::Duration;
+    let values: Vec<u64> = (0..64).collect();
+    let interner: Interner<&u64> =
+        InternerBuilder::new().slow_make_threshold(Duration::from_millis(2)).build();
+    values.par_iter().for_each(|value| {
+        interner.intern_ref(value, || {
+            if value % 8 == 0 {
+                std::thread::sleep(Duration::from_millis(4));
+            }
+            value
+        });
+    });
+    let report = interner.slow_make_report().unwrap();
+    assert!(report.count >= 8);
+    assert!(report.max >= Duration::from_millis(4));
+    use std::hash::BuildHasher;
+    let slow_hash = interner.hasher().hash_one(0u64);
+    assert!(report.recent.iter().any(|slow_make| slow_make.hash == slow_hash));
+}
+
+#[test]
+fn multi_threaded_resize_secure_wipe() {
+    let values: Vec<[u64; 4]> = (0..1000).map(|i| [i, i + 1, i + 2, i + 3]).collect();
+    let interner: Interner<[u64; 4]> = InternerBuilder::new().secure_wipe(true).build();
+    values.par_iter().for_each(|value| {
+        assert_eq!(*value, interner.intern_ref(value, || *value));
+    });
+    values.par_iter().for_each(|value| {
+        assert_eq!(*value, interner.intern_ref(value, || unreachable!()));
+    });
+    drop(interner);
+}
+
+#[test]
+fn multi_threaded_merge_child() {
+    use interner::MergeError;
+    use std::hash::BuildHasher;
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::new();
+    let children: Vec<Interner<&u64>> = (0..4).map(|_| interner.child()).collect();
+    values.par_iter().for_each(|value| {
+        children[*value as usize % 4].intern_ref(value, || value);
+        if value % 2 == 0 {
+            interner.intern_ref(value, || value);
+        }
+    });
+    let merged: usize = children.par_iter().map(|child| interner.merge(child).unwrap()).sum();
+    assert_eq!(values.len() / 2, merged);
+    values.par_iter().for_each(|value| {
+        let hash = children[0].hasher().hash_one(value);
+        assert_eq!(Some(&value), interner.get_from_hash(hash, |result| *result == value));
+    });
+    let other: Interner<&u64> = Interner::new();
+    assert_eq!(Err(MergeError::HasherMismatch), other.merge(&interner));
+}
+
+#[test]
+fn intersection_and_difference_of_children() {
+    use interner::MergeError;
+    use std::collections::HashSet;
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::new();
+    let other = interner.child();
+    values.par_iter().for_each(|value| {
+        if value % 2 == 0 {
+            interner.intern_ref(value, || value);
+        }
+        if value % 3 == 0 {
+            other.intern_ref(value, || value);
+        }
+    });
+    let both: HashSet<u64> = interner.intersection(&other).unwrap().copied().collect();
+    let expected: HashSet<u64> = values.iter().copied().filter(|value| value % 6 == 0).collect();
+    assert_eq!(expected, both);
+    let only_interner: Vec<u64> = interner.difference(&other).unwrap().copied().collect();
+    assert_eq!(
+        values.iter().filter(|value| *value % 2 == 0 && *value % 3 != 0).count(),
+        only_interner.len()
+    );
+    assert!(only_interner.iter().all(|value| value % 2 == 0 && value % 3 != 0));
+    let unrelated: Interner<&u64> = Interner::new();
+    assert!(matches!(interner.intersection(&unrelated), Err(MergeError::HasherMismatch)));
+}
+
+#[test]
+fn multi_threaded_len_with_resize() {
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::new();
+    assert!(interner.is_empty());
+    (0..4).into_par_iter().for_each(|_| {
+        values.iter().for_each(|value| {
+            interner.intern_ref(value, || value);
+        });
+    });
+    assert_eq!(values.len(), interner.len());
+    assert!(interner.deep_slot_count() > interner.len());
+    let preset: Interner<&u64> = InternerBuilder::new().build_with_preset(values.iter().step_by(2));
+    values.par_iter().for_each(|value| {
+        preset.intern_ref(value, || value);
+    });
+    assert_eq!(values.len() / 2, preset.len());
+}
+
+#[test]
+fn multi_threaded_intern_str_and_bytes() {
+    let strings: Vec<String> = (0..ITER).map(|value| value.to_string()).collect();
+    let interner: Interner<&str> = Interner::new();
+    let bytes: Interner<&[u8]> = Interner::new();
+    strings.par_iter().for_each(|value| {
+        let owned = value.clone();
+        let result = interner.intern_str(&owned, || value);
+        assert!(std::ptr::eq(value.as_str(), result));
+        let result = bytes.intern_bytes(owned.as_bytes(), || value.as_bytes());
+        assert!(std::ptr::eq(value.as_bytes(), result));
+    });
+    strings.par_iter().for_each(|value| {
+        assert!(std::ptr::eq(
+            value.as_str(),
+            interner.intern_ref(value.as_str(), || unreachable!())
+        ));
+        let result = bytes.intern_bytes(&value.clone().into_bytes(), || unreachable!());
+        assert!(std::ptr::eq(value.as_bytes(), result));
+    });
+    assert_eq!(strings.len(), interner.len());
+}
+
+#[test]
+fn wait_stats_counts_parks() {
+    use interner::stats::wait_stats;
+    use std::sync::Barrier;
+    use std::time::Duration;
+    let before = wait_stats();
+    let value = 42u64;
+    let interner: Interner<&u64> = Interner::new();
+    let making = Barrier::new(2);
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            interner.intern_ref(&value, || {
+                making.wait();
+                std::thread::sleep(Duration::from_millis(50));
+                &value
+            });
+        });
+        making.wait();
+        assert_eq!(&value, interner.intern_ref(&value, || unreachable!()));
+    });
+    let after = wait_stats();
+    assert!(after.total_parks() > before.total_parks());
+    assert!(after.parks.iter().zip(before.parks.iter()).all(|(after, before)| after >= before));
+}
+
+#[test]
+fn move_interner_between_threads_after_resize() {
+    struct Session<'a> {
+        name: String,
+        interner: Interner<&'a u64>,
+    }
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    let values: Vec<u64> = (0..ITER).collect();
+    let values = values.as_slice();
+    let session = Session { name: String::from("session"), interner: Interner::new() };
+    values.par_iter().for_each(|value| {
+        session.interner.intern_ref(value, || value);
+    });
+    assert_send_sync(&session.interner);
+    let moved = std::thread::scope(|scope| {
+        scope
+            .spawn(move || {
+                let session = Box::new(session);
+                values.par_iter().for_each(|value| {
+                    assert!(std::ptr::eq(
+                        value,
+                        session.interner.intern_ref(value, || unreachable!())
+                    ));
+                });
+                *session
+            })
+            .join()
+            .unwrap()
+    });
+    assert_eq!("session", moved.name);
+    for value in values.iter() {
+        assert!(std::ptr::eq(value, moved.interner.intern_ref(value, || unreachable!())));
+    }
+}
+
+#[test]
+fn multi_threaded_intern_f64() {
+    use interner::CanonicalF64;
+    let values: Vec<f64> = (0..ITER).map(|value| value as f64 / 8.0).collect();
+    let interner: Interner<CanonicalF64> = Interner::new();
+    values.par_iter().for_each(|value| {
+        assert_eq!(value.to_bits(), interner.intern_f64(*value).get().to_bits());
+        interner.intern_f64(-value);
+        interner.intern_f64(f64::from_bits(f64::NAN.to_bits() | (value.to_bits() & 0xFFFF)));
+    });
+    // the values, their negations except -0.0 and one NaN
+    assert_eq!(values.len() + (values.len() - 1) + 1, interner.len());
+    assert_eq!(0.0f64.to_bits(), interner.intern_f64(-0.0).get().to_bits());
+}
+
+#[test]
+fn multi_threaded_adaptive_get_start() {
+    use std::hash::BuildHasher;
+    let values: Vec<u64> = (0..ITER).collect();
+    // the values inserted after a resize started are only in the new table
+    let new_values: Vec<u64> = (ITER..4 * ITER).collect();
+    let interner: Interner<&u64> =
+        InternerBuilder::new().capacity(ITER as usize).adaptive_get_start(true).build();
+    values.par_iter().for_each(|value| {
+        interner.intern_ref(value, || value);
+    });
+    values.par_iter().for_each(|value| {
+        let hash = interner.hasher().hash_one(value);
+        assert_eq!(Some(&value), interner.get_from_hash(hash, |result| *result == value));
+    });
+    assert_eq!(Some(0.0), interner.estimated_chain_read_penalty());
+    new_values.par_iter().for_each(|value| {
+        interner.intern_ref(value, || value);
+    });
+    values.par_iter().chain(new_values.par_iter()).for_each(|value| {
+        let hash = interner.hasher().hash_one(value);
+        assert_eq!(Some(&value), interner.get_from_hash(hash, |result| *result == value));
+    });
+    let penalty = interner.estimated_chain_read_penalty().unwrap();
+    assert!((0.0..=2.0).contains(&penalty), "{penalty}");
+    assert_eq!(None, Interner::<&u64>::new().estimated_chain_read_penalty());
+}
+
+#[test]
+fn freeze_after_multi_threaded_intern() {
+    use std::hash::BuildHasher;
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> =
+        InternerBuilder::new().build_with_preset(values.iter().take(100));
+    values.par_iter().for_each(|value| {
+        interner.intern_ref(value, || value);
+    });
+    let frozen = interner.freeze();
+    assert_eq!(values.len(), frozen.len());
+    values.par_iter().for_each(|value| {
+        assert!(std::ptr::eq(value, *frozen.get(value).unwrap()));
+    });
+    let missing = ITER + 1;
+    let hash = frozen.hasher().hash_one(missing);
+    assert_eq!(None, frozen.get_from_hash(hash, |result| **result == missing));
+    assert!(frozen
+        .values()
+        .windows(2)
+        .all(|pair| { frozen.hasher().hash_one(pair[0]) <= frozen.hasher().hash_one(pair[1]) }));
+    let empty: Interner<&u64> = Interner::new();
+    assert!(empty.freeze().get(&0).is_none());
+}
+
+#[test]
+fn ptr_eq_fast_path_skips_eq() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static EQ_CALLS: AtomicUsize = AtomicUsize::new(0);
+    struct Tree(Vec<u64>);
+    impl std::hash::Hash for Tree {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            self.0.hash(state)
+        }
+    }
+    impl PartialEq for Tree {
+        fn eq(&self, other: &Self) -> bool {
+            EQ_CALLS.fetch_add(1, Ordering::Relaxed);
+            self.0 == other.0
+        }
+    }
+    impl Eq for Tree {}
+
+    let trees: Vec<Tree> = (0..ITER).map(|i| Tree(vec![i; 4])).collect();
+    for ptr_eq_fast_path in [false, true] {
+        let interner: Interner<&Tree> =
+            InternerBuilder::new().ptr_eq_fast_path(ptr_eq_fast_path).build();
+        let interned: Vec<&Tree> =
+            trees.par_iter().map(|tree| interner.intern_ref(tree, || tree)).collect();
+        EQ_CALLS.store(0, Ordering::Relaxed);
+        interned.par_iter().for_each(|tree| {
+            assert!(std::ptr::eq(*tree, interner.intern_ref(*tree, || unreachable!())));
+        });
+        let eq_calls = EQ_CALLS.load(Ordering::Relaxed);
+        if ptr_eq_fast_path {
+            // only the candidates with the same h2 but another value are compared with `Eq`
+            assert!(eq_calls < ITER as usize / 16);
+        } else {
+            assert!(eq_calls >= ITER as usize);
+        }
+        // an equal value at another address is still found with `Eq`
+        let copy = Tree(vec![7; 4]);
+        assert!(std::ptr::eq(&trees[7], interner.intern_ref(&copy, || unreachable!())));
+    }
+}
+
+#[test]
+fn multi_threaded_deny_resize() {
+    use interner::InternError;
+    let values: Vec<u64> = (0..ITER).collect();
+    let more: Vec<u64> = (ITER..4 * ITER).collect();
+    let interner: Interner<&u64> =
+        InternerBuilder::new().capacity(ITER as usize).deny_resize(true).build();
+    values.par_iter().for_each(|value| {
+        assert_eq!(value, interner.try_intern_ref(value, || value).unwrap());
+    });
+    assert_eq!(1, interner.max_chain_depth());
+
+    let denied = more
+        .par_iter()
+        .filter(|value| {
+            matches!(
+                interner.try_intern_ref(*value, || *value),
+                Err(InternError::ResizeDenied { .. })
+            )
+        })
+        .count();
+    assert!(denied > 0);
+    assert_eq!(1, interner.max_chain_depth());
+    assert_eq!(4 * ITER as usize - denied, interner.len());
+}
+
+#[test]
+fn intern_ref_with_deadline_gives_up_on_slow_make() {
+    use interner::InternError;
+    use std::sync::Barrier;
+    use std::time::{Duration, Instant};
+    let slow = 42u64;
+    let other = 42u64;
+    let interner: Interner<&u64> = Interner::new();
+    let locked = Barrier::new(2);
+    std::thread::scope(|scope| {
+        let maker = scope.spawn(|| {
+            interner.intern_ref(&slow, || {
+                locked.wait();
+                std::thread::sleep(Duration::from_millis(200));
+                &slow
+            })
+        });
+        locked.wait();
+        let start = Instant::now();
+        let deadline = start + Duration::from_millis(20);
+        assert_eq!(
+            Err(InternError::DeadlineExceeded),
+            interner.intern_ref_with_deadline(&other, || unreachable!(), deadline)
+        );
+        let result = interner.intern_ref_with_deadline_or_make(&other, || &other, deadline);
+        assert!(std::ptr::eq(&other, result.unwrap_err()));
+        assert!(start.elapsed() < Duration::from_millis(200));
+        assert!(std::ptr::eq(&slow, maker.join().unwrap()));
+    });
+    let deadline = Instant::now() + Duration::from_millis(20);
+    let result = interner.intern_ref_with_deadline(&other, || unreachable!(), deadline);
+    assert!(std::ptr::eq(&slow, result.unwrap()));
+    assert_eq!(1, interner.len());
+}
+
+#[test]
+fn fingerprint_and_bucket_index_match_placement() {
+    use interner::stats::SlotInfo;
+    use interner::{bucket_index, fingerprint};
+    use std::hash::BuildHasher;
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::with_capacity(ITER as usize);
+    values.par_iter().for_each(|value| {
+        interner.intern_ref(value, || value);
+    });
+    let buckets = interner.fragmentation_report().tables.last().unwrap().buckets;
+    for value in values.iter() {
+        let hash = interner.hasher().hash_one(value);
+        let mut first = true;
+        let mut found = false;
+        interner.visit_bucket_of(hash, |bucket| {
+            if first {
+                assert_eq!(bucket_index(hash, buckets), bucket.pos);
+                first = false;
+            }
+            for slot in bucket.slots.iter() {
+                if let SlotInfo::Valid { h2, value: slot_value } = slot {
+                    if std::ptr::eq(**slot_value, value) {
+                        assert_eq!(fingerprint(hash), *h2);
+                        found = true;
+                    }
+                }
+            }
+        });
+        assert!(found);
+    }
+}
+
+#[test]
+fn multi_threaded_get_during_resize() {
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::with_capacity(1);
+    values.par_iter().for_each(|value| {
+        assert_eq!(None, interner.get(&(value + ITER)));
+        let result = interner.intern_ref(value, || value);
+        assert!(std::ptr::eq(result, interner.get(value).unwrap()));
+    });
+    values.par_iter().for_each(|value| {
+        assert!(std::ptr::eq(value, interner.get(value).unwrap()));
+    });
+}
+
+#[test]
+fn stuck_transfer_alert_on_locked_slot() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+    let stuck = u64::MAX;
+    let values: Vec<u64> = (0..ITER).collect();
+    let alerts = Arc::new(AtomicUsize::new(0));
+    let interner: Interner<&u64> = InternerBuilder::new()
+        .capacity(16)
+        .stuck_transfer_alert(Duration::from_millis(10), {
+            let alerts = alerts.clone();
+            move |stuck| {
+                assert_ne!(0, stuck.to_be_moved);
+                assert!(stuck.elapsed > Duration::from_millis(10));
+                alerts.fetch_add(1, Ordering::Relaxed);
+            }
+        })
+        .build();
+    let (locked_tx, locked_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    // the locked slot is released before the asserts so a failure does not hang the scope
+    let (to_be_moved, alerted) = std::thread::scope(|scope| {
+        let (interner, stuck) = (&interner, &stuck);
+        scope.spawn(move || {
+            interner.intern_ref(stuck, || {
+                locked_tx.send(()).unwrap();
+                release_rx.recv().unwrap();
+                stuck
+            })
+        });
+        locked_rx.recv().unwrap();
+        // a value with the same h2 in the bucket of the locked slot would wait on it
+        let deadline = || Instant::now() + Duration::from_millis(1);
+        for value in values.iter() {
+            let _ = interner.intern_ref_with_deadline(value, || value, deadline());
+        }
+        let to_be_moved = interner.to_be_moved()[0];
+        std::thread::sleep(Duration::from_millis(20));
+        for value in values.iter() {
+            let _ = interner.intern_ref_with_deadline(value, || value, deadline());
+        }
+        interner.to_be_moved();
+        let alerted = alerts.load(Ordering::Relaxed);
+        release_tx.send(()).unwrap();
+        (to_be_moved, alerted)
+    });
+    assert_ne!(0, to_be_moved);
+    assert_eq!(1, alerted);
+    assert!(interner.to_be_moved().iter().all(|&to_be_moved| to_be_moved == 0));
+    assert_eq!(1, alerts.load(Ordering::Relaxed));
+}
+
+#[test]
+fn multi_threaded_arc_interner_releases_values() {
+    use interner::ArcInterner;
+    let interner: ArcInterner<String> = ArcInterner::with_hasher(Default::default());
+    let arcs: Vec<Arc<String>> = (0..4 * ITER)
+        .into_par_iter()
+        .map(|i| {
+            let arc = interner.intern((i % ITER).to_string());
+            assert_eq!(&(i % ITER).to_string(), arc.as_ref());
+            arc
+        })
+        .collect();
+    assert_eq!(ITER as usize, interner.len());
+    for (i, arc) in arcs.iter().enumerate().take(ITER as usize) {
+        assert!(Arc::ptr_eq(arc, &arcs[i + ITER as usize]));
+        assert!(Arc::ptr_eq(arc, &interner.get(arc.as_str()).unwrap()));
+    }
+    // the interner, the 4 interns and the get above
+    assert_eq!(5, Arc::strong_count(&arcs[0]));
+    drop(interner);
+    // the 4 interns
+    assert_eq!(4, Arc::strong_count(&arcs[0]));
+    assert!(arcs.iter().all(|arc| Arc::strong_count(arc) == 4));
+}
+
+#[test]
+fn multi_threaded_stable_ids() {
+    use interner::{IdCollision, StableHasher, StableIds};
+    use std::hash::Hasher;
+    // the FNV-1a 128 bit test vector pins the algorithm
+    let mut hasher = StableHasher::new();
+    hasher.write(b"a");
+    assert_eq!(0xd228cb696f1a8caf78912b704e4a8964, hasher.finish128());
+
+    let ids: StableIds = StableIds::new();
+    let first: Vec<u64> =
+        (0..ITER).into_par_iter().map(|i| ids.id_of(&i.to_string()).unwrap()).collect();
+    let again: Vec<u64> =
+        (0..ITER).into_par_iter().map(|i| ids.id_of(&i.to_string()).unwrap()).collect();
+    assert_eq!(first, again);
+    assert_eq!(ITER as usize, ids.len());
+
+    let mut file = Vec::new();
+    ids.write_to(&mut file).unwrap();
+    assert_eq!(24 * ITER as usize, file.len());
+    let read = StableIds::read_from(&file[..]).unwrap();
+    assert_eq!(ITER as usize, read.len());
+    assert_eq!(first[7], read.id_of("7").unwrap());
+
+    // an other hash that folds to the id of "hello"
+    let hash = StableHasher::hash128("hello");
+    let other = hash ^ (1 << 64 | 1);
+    let id = hasher_fold(other);
+    let mut file = Vec::new();
+    file.extend_from_slice(&id.to_le_bytes());
+    file.extend_from_slice(&other.to_le_bytes());
+    let ids = StableIds::read_from(&file[..]).unwrap();
+    assert_eq!(Err(IdCollision { id }), ids.id_of("hello"));
+    file.extend_from_slice(&id.to_le_bytes());
+    file.extend_from_slice(&hash.to_le_bytes());
+    assert!(StableIds::read_from(&file[..]).is_err());
+    assert!(StableIds::read_from(&file[..30]).is_err());
+
+    fn hasher_fold(hash: u128) -> u64 {
+        (hash >> 64) as u64 ^ hash as u64
+    }
+}
+
+#[test]
+fn multi_threaded_string_interner() {
+    use interner::{StringInterner, Symbol};
+
+    let interner = StringInterner::new();
+    let symbols: Vec<Vec<Symbol>> = (0..4)
+        .into_par_iter()
+        .map(|_| (0..ITER).map(|i| interner.intern(&i.to_string())).collect())
+        .collect();
+    assert_eq!(ITER as usize, interner.len());
+    let mut indexes: Vec<u32> = symbols[0].iter().map(|symbol| symbol.as_u32()).collect();
+    for i in 0..ITER as usize {
+        assert!(symbols.iter().all(|thread_symbols| thread_symbols[i] == symbols[0][i]));
+        assert_eq!(i.to_string(), interner.resolve(symbols[0][i]));
+        assert_eq!(Some(symbols[0][i]), interner.get(&i.to_string()));
+    }
+    indexes.sort_unstable();
+    assert_eq!((0..ITER as u32).collect::<Vec<_>>(), indexes);
+    assert_eq!(None, interner.get("a"));
+}
+
+#[test]
+fn multi_threaded_owning_interner() {
+    use interner::OwningInterner;
+
+    let interner: OwningInterner<[u64]> = OwningInterner::new();
+    let values: Vec<&[u64]> = (0..4 * ITER)
+        .into_par_iter()
+        .map(|i| {
+            let value = [i % ITER, i % 3];
+            let interned = interner.intern_owned(&value[..]);
+            assert_eq!(&value[..], interned);
+            interned
+        })
+        .collect();
+    assert_eq!(3 * ITER as usize, interner.len());
+    for (i, value) in values.iter().enumerate().take(ITER as usize) {
+        assert!(std::ptr::eq(*value, values[i + 3 * ITER as usize]));
+        assert!(std::ptr::eq(*value, interner.get(value).unwrap()));
+    }
+    assert_eq!(None, interner.get(&[ITER, 0][..]));
+}
+
+#[test]
+fn multi_threaded_on_new_value() {
+    use std::sync::mpsc;
+
+    let (sender, receiver) = mpsc::channel();
+    let interner: Interner<u64> = InternerBuilder::new().build_with_on_new_value(move |value| {
+        sender.send(value).unwrap();
+    });
+    (0..4 * ITER).into_par_iter().for_each(|i| {
+        interner.intern(i % ITER, |value| value);
+    });
+    drop(interner);
+    let mut values: Vec<u64> = receiver.iter().collect();
+    values.sort_unstable();
+    assert_eq!((0..ITER).collect::<Vec<_>>(), values);
+}
+
+#[test]
+fn multi_threaded_stats() {
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::with_capacity(16);
+    let initial = interner.stats();
+    assert_eq!((0, 1, 0), (initial.len, initial.tables, initial.pending_moves));
+    assert_eq!(interner.capacity(), initial.capacity);
+    values.par_iter().for_each(|value| {
+        interner.intern_ref(value, || value);
+    });
+    let stats = interner.stats();
+    assert_eq!(ITER as usize, stats.len);
+    assert!(stats.capacity >= ITER as usize / 2, "{stats:?}");
+    assert_eq!(interner.capacity(), stats.capacity);
+    assert_eq!(interner.fragmentation_report().tables.len(), stats.tables);
+    assert!(stats.tables > 1);
+    assert_eq!(
+        interner.to_be_moved().iter().map(|to_be_moved| to_be_moved.unsigned_abs()).sum::<usize>(),
+        stats.pending_moves
+    );
+    assert!(stats.occupancy > 0.0 && stats.occupancy <= 1.0, "{stats:?}");
+}
+
+#[cfg(feature = "timestamps")]
+#[test]
+fn multi_threaded_timestamped_interner() {
+    use interner::TimestampedInterner;
+    let interner: TimestampedInterner<u64> = TimestampedInterner::new();
+    (0..ITER).into_par_iter().for_each(|value| {
+        assert_eq!(&value, interner.intern(value));
+        assert_eq!(&value, interner.intern(value));
+    });
+    assert_eq!(ITER as usize, interner.len());
+    let mut last = None;
+    for (order, (value, first_seen)) in interner.iter_by_arrival().enumerate() {
+        assert_eq!(order as u64, first_seen.order);
+        assert_eq!(Some(first_seen), interner.first_seen(value));
+        assert!(last.is_none_or(|last| last <= first_seen.at));
+        last = Some(first_seen.at);
+    }
+    assert_eq!(None, interner.first_seen(&ITER));
+}
+
+#[test]
+fn multi_threaded_resize_policy() {
+    use interner::ResizePolicy;
+    let values: Vec<u64> = (0..ITER).collect();
+    for (resize_policy, growth) in
+        [(ResizePolicy::Double, 2), (ResizePolicy::LiveCount, 2), (ResizePolicy::Contended, 4)]
+    {
+        let interner: Interner<&u64> =
+            InternerBuilder::new().capacity(16).resize_policy(resize_policy).build();
+        values.par_iter().for_each(|value| {
+            assert_eq!(value, interner.intern_ref(value, || value));
+        });
+        values.par_iter().for_each(|value| {
+            assert!(std::ptr::eq(value, interner.intern_ref(value, || unreachable!())));
+        });
+        assert_eq!(ITER as usize, interner.len());
+        let tables = interner.fragmentation_report().tables;
+        assert!(tables.len() > 1);
+        assert!(tables.windows(2).all(|tables| tables[1].buckets >= growth * tables[0].buckets));
+        if resize_policy == ResizePolicy::Double {
+            assert!(tables.windows(2).all(|tables| tables[1].buckets == 2 * tables[0].buckets));
+        }
+    }
+}
+
+#[test]
+fn multi_threaded_intern_ref_checked() {
+    let buffers: Vec<String> = (0..ITER).map(|value| value.to_string()).collect();
+    let interner: Interner<&'static str> = Interner::new();
+    buffers.par_iter().for_each(|buffer| {
+        let result = interner.intern_ref_checked(buffer.as_str(), || buffer.clone().leak());
+        assert_eq!(buffer, result);
+        let interned = interner.intern_ref_checked(buffer.as_str(), || unreachable!());
+        assert!(std::ptr::eq(result, interned));
+    });
+    assert_eq!(ITER as usize, interner.len());
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "borrows the key")]
+fn intern_ref_checked_returns_key() {
+    static BUFFER: &str = "a key in a longer buffer";
+    let interner: Interner<&'static str> = Interner::new();
+    let key = &BUFFER[2..5];
+    interner.intern_ref_checked(key, || key);
+}
+
+#[test]
+fn multi_threaded_intern_ref_entry() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::with_capacity(16);
+    let new_values = AtomicUsize::new(0);
+    // every value is interned by several threads at the same time, only one of them inserts it
+    (0..4 * ITER).into_par_iter().for_each(|index| {
+        let value = &values[(index / 4) as usize];
+        let interned = interner.intern_ref_entry(value, || value);
+        assert!(std::ptr::eq(value, interned.value));
+        if interned.was_new {
+            new_values.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+    assert_eq!(ITER as usize, new_values.into_inner());
+    assert_eq!(ITER as usize, interner.len());
+}
+
+#[test]
+fn multi_threaded_make_called_once_with_token() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    let interner: Interner<&'static u64> = Interner::with_capacity(16);
+    let makes = AtomicUsize::new(0);
+    let hashes = Mutex::new(std::collections::HashSet::new());
+    (0..4 * ITER).into_par_iter().for_each(|index| {
+        let value = index / 4;
+        let result = interner.intern_ref_with_token(&value, |token| {
+            makes.fetch_add(1, Ordering::Relaxed);
+            assert!(hashes.lock().unwrap().insert(token.hash()));
+            Box::leak(Box::new(value))
+        });
+        assert_eq!(&value, result);
+    });
+    assert_eq!(ITER as usize, makes.into_inner());
+    assert_eq!(ITER as usize, interner.len());
+}
+
+#[test]
+fn multi_threaded_try_make_intern_ref() {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    let values: Vec<u64> = (0..ITER).collect();
+    let failed: Vec<AtomicBool> = values.iter().map(|_| AtomicBool::new(false)).collect();
+    let makes = AtomicUsize::new(0);
+    let interner: Interner<&u64> = Interner::with_capacity(16);
+    // every value is interned by several threads, the first make of each value fails so the
+    // threads waiting on the poisoned slot must retry
+    (0..4 * ITER).into_par_iter().for_each(|index| {
+        let index = (index / 4) as usize;
+        let value = &values[index];
+        let result = loop {
+            let result = interner.try_make_intern_ref(value, || {
+                makes.fetch_add(1, Ordering::Relaxed);
+                if failed[index].swap(true, Ordering::Relaxed) { Ok(value) } else { Err(index) }
+            });
+            match result {
+                Ok(result) => break result,
+                Err(error) => assert_eq!(index, error),
+            }
+        };
+        assert!(std::ptr::eq(value, result));
+    });
+    assert_eq!(2 * ITER as usize, makes.into_inner());
+    assert_eq!(ITER as usize, interner.len());
+    assert!(interner.to_be_moved().iter().all(|&to_be_moved| to_be_moved == 0));
+    values.par_iter().for_each(|value| {
+        assert!(std::ptr::eq(value, interner.intern_ref(value, || unreachable!())));
+    });
+}
+
+#[test]
+fn multi_threaded_panicking_make() {
+    use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    let values: Vec<u64> = (0..ITER).collect();
+    let panicked: Vec<AtomicBool> = values.iter().map(|_| AtomicBool::new(false)).collect();
+    let makes = AtomicUsize::new(0);
+    let interner: Interner<&u64> = Interner::with_capacity(16);
+    // the first make of each value panics, the threads waiting on the locked slot must wake up
+    // and retry. `resume_unwind` does not call the panic hook so the output is not flooded
+    (0..4 * ITER).into_par_iter().for_each(|index| {
+        let index = (index / 4) as usize;
+        let value = &values[index];
+        let result = loop {
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                interner.intern_ref(value, || {
+                    makes.fetch_add(1, Ordering::Relaxed);
+                    if !panicked[index].swap(true, Ordering::Relaxed) {
+                        resume_unwind(Box::new(index));
+                    }
+                    value
+                })
+            }));
+            match result {
+                Ok(result) => break result,
+                Err(payload) => assert_eq!(index, *payload.downcast::<usize>().unwrap()),
+            }
+        };
+        assert!(std::ptr::eq(value, result));
+    });
+    assert_eq!(2 * ITER as usize, makes.into_inner());
+    assert_eq!(ITER as usize, interner.len());
+    assert!(interner.to_be_moved().iter().all(|&to_be_moved| to_be_moved == 0));
+    values.par_iter().for_each(|value| {
+        assert!(std::ptr::eq(value, interner.intern_ref(value, || unreachable!())));
+    });
+}
+
+#[test]
+fn multi_threaded_reclaim_moved_tables() {
+    let values: Vec<u64> = (0..ITER).collect();
+    let mut interner: Interner<&u64> = Interner::with_capacity(16);
+    values[..ITER as usize / 2].par_iter().for_each(|value| {
+        assert!(std::ptr::eq(value, interner.intern_ref(value, || value)));
+    });
+    let report = interner.fragmentation_report();
+    let bytes: usize = report.tables.iter().map(|table| table.bytes).sum();
+    let freed = interner.reclaim_moved_tables();
+    let report = interner.fragmentation_report();
+    assert_eq!(1, report.tables.len());
+    assert_eq!(bytes, freed + report.tables[0].bytes);
+    assert_eq!(ITER as usize / 2, interner.len());
+    // the interner keeps working and resizing after the old tables are freed
+    values.par_iter().for_each(|value| {
+        assert!(std::ptr::eq(value, interner.intern_ref(value, || value)));
+    });
+    assert_eq!(ITER as usize, interner.len());
+    interner.reclaim_moved_tables();
+    assert_eq!(0, interner.fragmentation_report().non_current_bytes);
+    values.par_iter().for_each(|value| {
+        assert!(std::ptr::eq(value, interner.intern_ref(value, || unreachable!())));
+    });
+}
+
+#[test]
+fn multi_threaded_compact() {
+    use interner::InternerBuilder;
+    let values: Vec<u64> = (0..ITER).collect();
+    let mut interner: Interner<&u64> = InternerBuilder::new().capacity(64 * ITER as usize).build();
+    values[..ITER as usize / 2].par_iter().for_each(|value| {
+        assert!(std::ptr::eq(value, interner.intern_ref(value, || value)));
+    });
+    let capacity = interner.capacity();
+    // inserts and lookups continue while the values are moved to the smaller table
+    rayon::join(
+        || assert!(interner.compact()),
+        || {
+            values.par_iter().for_each(|value| {
+                assert!(std::ptr::eq(value, interner.intern_ref(value, || value)));
+            })
+        },
+    );
+    assert!(interner.capacity() < capacity);
+    assert_eq!(ITER as usize, interner.len());
+    assert!(interner.reclaim_moved_tables() > 0);
+    values.par_iter().for_each(|value| {
+        assert!(std::ptr::eq(value, interner.intern_ref(value, || unreachable!())));
+    });
+}
+
+#[test]
+fn multi_threaded_dense_ids() {
+    use interner::{InternerBuilder, SideTable};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    let values: Vec<u64> = (0..ITER).collect();
+    let mut interner: Interner<&u64> = InternerBuilder::new().capacity(16).dense_ids(true).build();
+    let side_table: SideTable<AtomicU64> = SideTable::new();
+    values.par_iter().for_each(|value| {
+        assert!(std::ptr::eq(value, interner.intern_ref(value, || value)));
+        let id = interner.dense_id(value).unwrap();
+        assert!((id as usize) < interner.len());
+        side_table.get(id).store(*value, Ordering::Relaxed);
+    });
+    assert!(interner.stats().tables > 1);
+    let mut ids: Vec<u32> = values.iter().map(|value| interner.dense_id(value).unwrap()).collect();
+    // the ids are kept when the values are moved to a new table
+    values.par_iter().for_each(|value| {
+        let id = interner.dense_id(value).unwrap();
+        assert_eq!(*value, side_table.try_get(id).unwrap().load(Ordering::Relaxed));
+    });
+    interner.reclaim_moved_tables();
+    assert!(values.iter().zip(ids.iter()).all(|(value, id)| interner.dense_id(value) == Some(*id)));
+    ids.sort_unstable();
+    assert!(ids.iter().copied().eq(0..ITER as u32));
+    assert_eq!(None, interner.dense_id(&ITER));
+    assert!(side_table.capacity() >= ITER as usize);
+}
+
+#[test]
+fn multi_threaded_intern_many() {
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::with_capacity(16);
+    values.par_chunks(1024).for_each(|chunk| {
+        // every chunk is interned twice, so the values are both inserted and found
+        let interned = interner.intern_many(chunk.iter().chain(values.iter().take(512)), |val| val);
+        assert_eq!(chunk.len() + 512, interned.len());
+        assert!(chunk.iter().zip(interned.iter()).all(|(value, result)| value == *result));
+        assert!(values.iter().take(512).zip(&interned[chunk.len()..]).all(|(a, b)| a == *b));
+        let interned = interner.intern_many(chunk, |_| unreachable!());
+        assert!(chunk.iter().zip(interned).all(|(value, result)| std::ptr::eq(value, result)));
+    });
+    assert!(interner.stats().tables > 1);
+    assert_eq!(ITER as usize, interner.len());
+    assert!(interner.intern_many(Vec::<&u64>::new(), |val| val).is_empty());
+}
+
+#[test]
+fn multi_threaded_histogram() {
+    use interner::{Histogram, InternerBuilder};
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> =
+        InternerBuilder::new().capacity(16).dense_ids(true).build_with_preset(&values[..16]);
+    let histogram = Histogram::new(&interner);
+    // value `i` is added `i % 4 + 1` times
+    (0..4).into_par_iter().for_each(|round| {
+        values.par_iter().filter(|value| *value % 4 >= round).for_each(|value| {
+            assert!(std::ptr::eq(value, histogram.add(value)));
+        });
+    });
+    let counts = histogram.into_vec();
+    assert_eq!(ITER as usize, counts.len());
+    assert!(counts.iter().all(|(value, count)| **value % 4 + 1 == *count));
+    assert!(counts[counts.len() - 16..].iter().all(|(value, _)| **value < 16));
+    assert_eq!(ITER as usize - 16, interner.len());
+}
+
+#[test]
+fn multi_threaded_debug_validate() {
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::with_capacity(16);
+    for round in 0..4 {
+        // a quarter of the values of each round fail their first make, so poisoned slots are
+        // validated
+        values.par_iter().skip(round).step_by(4).for_each(|value| {
+            if value % 16 < 4 {
+                assert_eq!(Err(()), interner.try_make_intern_ref(value, || Err(())));
+            }
+            assert!(std::ptr::eq(value, interner.intern_ref(value, || value)));
+        });
+        assert_eq!(Ok(()), interner.debug_validate());
+    }
+    assert!(interner.stats().tables > 1);
+    assert_eq!(ITER as usize, interner.len());
+}
+
+#[test]
+fn multi_threaded_map_intern() {
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: Interner<&u64> = Interner::with_capacity(16);
+    values.par_chunks(1000).for_each(|chunk| {
+        // the items are owned copies, the interned values are references in to `values`
+        let items = chunk.iter().chain(chunk.iter().rev()).copied();
+        let mapped = interner.map_intern(items, |value: &u64| &values[*value as usize]);
+        assert_eq!((2 * chunk.len(), Some(2 * chunk.len())), mapped.size_hint());
+        let mapped: Vec<&u64> = mapped.collect();
+        assert!(chunk.iter().zip(&mapped).all(|(value, result)| std::ptr::eq(value, *result)));
+        assert!(chunk.iter().rev().zip(&mapped[chunk.len()..]).all(|(a, b)| std::ptr::eq(a, *b)));
+    });
+    assert!(interner.stats().tables > 1);
+    assert_eq!(ITER as usize, interner.len());
+}
+
+#[test]
+fn multi_threaded_replicated_interner() {
+    use interner::ReplicatedInterner;
+    let values: Vec<u64> = (0..ITER).collect();
+    let interner: ReplicatedInterner<&u64> = ReplicatedInterner::with_replicas(8, 1000);
+    values.par_iter().for_each(|value| {
+        assert!(std::ptr::eq(value, interner.intern_ref(value, || value)));
+        assert!(std::ptr::eq(value, interner.get(value).unwrap()));
+    });
+    assert_eq!(ITER as usize, interner.len());
+    assert!(interner.pending_writes() < ITER as usize);
+    interner.refresh();
+    assert_eq!(0, interner.pending_writes());
+    values.par_iter().for_each(|value| {
+        assert!(std::ptr::eq(value, interner.get(value).unwrap()));
+        assert!(std::ptr::eq(value, interner.intern_ref(value, || unreachable!())));
+    });
+    assert_eq!(None, interner.get(&ITER));
+}
+
+#[test]
+fn replicated_interner_rebuild_threshold_grows_with_len() {
+    use interner::ReplicatedInterner;
+    let values: Vec<u64> = (0..10_000).collect();
+    let interner: ReplicatedInterner<&u64> = ReplicatedInterner::with_replicas(2, 1);
+    for value in values.iter() {
+        interner.intern_ref(value, || value);
+        assert!(interner.pending_writes() <= interner.len() / 8);
+    }
+    // not rebuilt by every insert
+    assert!(interner.pending_writes() > 0);
+}
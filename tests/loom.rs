@@ -0,0 +1,253 @@
+//! Loom models of the slot publication protocol used by the buckets and of the publication of
+//! new tables.
+//!
+//! A slot value is only allowed to be read when the valid bit is set in meta data read with
+//! acquire ordering, these models check that every way a reader gets hold of the meta data
+//! makes the write of the value visible. A table is only allowed to be read through a pointer
+//! that was loaded in a way that makes the initialization of the table visible.
+//!
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --test loom --release`, the model of the slot
+//! state machine also needs `--features unstable-internals`.
+#![cfg(loom)]
+
+use loom::cell::UnsafeCell;
+use loom::sync::atomic::{fence, AtomicPtr, AtomicU64, Ordering};
+use loom::sync::Arc;
+use loom::thread;
+
+const H2: u64 = 0x2A;
+const LOCKED_BIT: u64 = 0x80;
+const VALID_BIT: u64 = 1 << (64 - 7);
+
+struct Slot {
+    meta_data: AtomicU64,
+    value: UnsafeCell<u64>,
+}
+
+impl Slot {
+    fn new() -> Self {
+        Self { meta_data: AtomicU64::new(0), value: UnsafeCell::new(0) }
+    }
+
+    // same orderings as `Bucket::reserve`
+    fn reserve(&self, current: &mut u64) -> bool {
+        if *current != 0 {
+            return false;
+        }
+        match self.meta_data.compare_exchange(
+            *current,
+            H2 & 0x3F | LOCKED_BIT,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => true,
+            Err(meta_data) => {
+                *current = meta_data;
+                false
+            }
+        }
+    }
+
+    // same orderings as `Bucket::set_slot` followed by `Bucket::set_valid_and_unpark`
+    fn unlock_and_set_value(&self, value: u64) {
+        self.value.with_mut(|ptr| unsafe { *ptr = value });
+        let mut current = self.meta_data.load(Ordering::Relaxed);
+        loop {
+            match self.meta_data.compare_exchange_weak(
+                current,
+                (current & !0xFF) | VALID_BIT | H2,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(meta_data) => current = meta_data,
+            }
+        }
+    }
+
+    fn read(&self, meta_data: u64) -> Option<u64> {
+        if meta_data & VALID_BIT != 0 {
+            Some(self.value.with(|ptr| unsafe { *ptr }))
+        } else {
+            None
+        }
+    }
+}
+
+// runs an insert of the value 42 and `read` in separate threads
+fn insert_and_read(read: impl Fn(&Slot) + Send + Sync + 'static) {
+    let slot = Arc::new(Slot::new());
+    let writer_slot = Arc::clone(&slot);
+    let writer = thread::spawn(move || {
+        let mut current = writer_slot.meta_data.load(Ordering::Acquire);
+        if writer_slot.reserve(&mut current) {
+            writer_slot.unlock_and_set_value(42);
+        }
+    });
+    let reader = thread::spawn(move || read(&slot));
+    writer.join().unwrap();
+    reader.join().unwrap();
+}
+
+#[test]
+fn acquire_load_sees_value() {
+    loom::model(|| {
+        insert_and_read(|slot| {
+            // `Bucket::get_metadata_acquire`
+            let meta_data = slot.meta_data.load(Ordering::Acquire);
+            if let Some(value) = slot.read(meta_data) {
+                assert_eq!(42, value);
+            }
+        })
+    });
+}
+
+#[test]
+fn failed_reserve_sees_value() {
+    loom::model(|| {
+        insert_and_read(|slot| {
+            // a stale snapshot from before the other thread locked the slot, the failed compare
+            // exchange gives the `OccupiedWithSameH2` path its meta data
+            let mut current = 0;
+            if !slot.reserve(&mut current) {
+                if let Some(value) = slot.read(current) {
+                    assert_eq!(42, value);
+                }
+            }
+        })
+    });
+}
+
+#[test]
+fn relaxed_load_and_fence_sees_value() {
+    loom::model(|| {
+        insert_and_read(|slot| {
+            // `Bucket::wait_on_lock_release` without the parking
+            let meta_data = slot.meta_data.load(Ordering::Relaxed);
+            if meta_data & VALID_BIT != 0 {
+                fence(Ordering::Acquire);
+                assert_eq!(Some(42), slot.read(meta_data));
+            }
+        })
+    });
+}
+
+// a table with the fields that are written before it is published and never written again
+struct Table {
+    buckets: UnsafeCell<u64>,
+    next: AtomicPtr<Table>,
+}
+
+impl Table {
+    fn new(buckets: u64) -> Self {
+        Self { buckets: UnsafeCell::new(buckets), next: AtomicPtr::new(std::ptr::null_mut()) }
+    }
+}
+
+struct Chain {
+    first: Table,
+    // `Interner::current_raw_interner`, null for the first table
+    current: AtomicPtr<Table>,
+}
+
+// one thread creates the next table, one thread moves the current table to it and one thread
+// reads the current table with the given orderings of `Interner::current_raw_interner`
+fn publish_and_read(store: Ordering, load: Ordering) {
+    let chain =
+        Arc::new(Chain { first: Table::new(1), current: AtomicPtr::new(std::ptr::null_mut()) });
+    let creator_chain = Arc::clone(&chain);
+    let creator = thread::spawn(move || {
+        // `RawInterner::create_next_raw_interner`
+        let next = Box::into_raw(Box::new(Table::new(2)));
+        creator_chain.first.next.store(next, Ordering::Release);
+    });
+    let mover_chain = Arc::clone(&chain);
+    let mover = thread::spawn(move || {
+        // `RawInterner::get_next_moved_raw_interner_ptr` and `Interner::move_current_raw_interner`
+        let next = mover_chain.first.next.load(Ordering::Acquire);
+        if !next.is_null() {
+            mover_chain.current.store(next, store);
+        }
+    });
+    let reader_chain = Arc::clone(&chain);
+    let reader = thread::spawn(move || {
+        let current = reader_chain.current.load(load);
+        if !current.is_null() {
+            let buckets = unsafe { &*current }.buckets.with(|ptr| unsafe { *ptr });
+            assert_eq!(2, buckets);
+        }
+    });
+    creator.join().unwrap();
+    mover.join().unwrap();
+    reader.join().unwrap();
+    let next = chain.first.next.load(Ordering::Relaxed);
+    if !next.is_null() {
+        drop(unsafe { Box::from_raw(next) });
+    }
+}
+
+#[test]
+fn current_table_publication_sees_table() {
+    loom::model(|| publish_and_read(Ordering::Release, Ordering::Acquire));
+}
+
+#[test]
+#[should_panic]
+fn relaxed_current_table_publication_is_a_race() {
+    // the relaxed load does not synchronize with the thread that created the table, the
+    // address dependency orders the reads on the hardware but not in the memory model
+    loom::model(|| publish_and_read(Ordering::Relaxed, Ordering::Relaxed));
+}
+
+// applies `event` to slot 0 with a compare exchange loop like `Bucket`, returns `None` if the slot
+// state machine does not allow it in the current state
+#[cfg(feature = "unstable-internals")]
+fn apply(
+    meta_data: &AtomicU64,
+    event: interner::internals::SlotEvent,
+) -> Option<interner::internals::Transition> {
+    use interner::internals::{step, MetaData};
+
+    let mut current = meta_data.load(Ordering::Relaxed);
+    loop {
+        let transition = step(&MetaData::new(current), 0, event)?;
+        match meta_data.compare_exchange(
+            current,
+            transition.meta_data.bits(),
+            Ordering::AcqRel,
+            Ordering::Relaxed,
+        ) {
+            Ok(_) => return Some(transition),
+            Err(meta_data) => current = meta_data,
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "unstable-internals")]
+fn parked_waiter_is_unparked() {
+    use interner::internals::SlotEvent;
+    use loom::sync::atomic::AtomicBool;
+
+    loom::model(|| {
+        let meta_data = Arc::new(AtomicU64::new(0));
+        let parked = Arc::new(AtomicBool::new(false));
+        let writer_meta_data = Arc::clone(&meta_data);
+        let writer = thread::spawn(move || {
+            apply(&writer_meta_data, SlotEvent::Lock { h2: H2 as u8 }).unwrap();
+            apply(&writer_meta_data, SlotEvent::Publish { h2: H2 as u8 }).unwrap().unpark
+        });
+        let waiter_meta_data = Arc::clone(&meta_data);
+        let waiter_parked = Arc::clone(&parked);
+        let waiter = thread::spawn(move || {
+            // `Bucket::wait_on_lock_release` only waits on a locked slot, the park fails when the
+            // slot is empty or already valid
+            if apply(&waiter_meta_data, SlotEvent::Park).is_some() {
+                waiter_parked.store(true, Ordering::Relaxed);
+            }
+        });
+        let unpark = writer.join().unwrap();
+        waiter.join().unwrap();
+        assert_eq!(parked.load(Ordering::Relaxed), unpark);
+    });
+}
@@ -0,0 +1,81 @@
+//! Checks the meta data operations against fixed bit patterns, so a byte order dependent
+//! implementation fails on big-endian targets.
+#![cfg(feature = "unstable-internals")]
+
+use interner::internals::MetaData;
+
+// valid h2 0x12 in slot 0 and 2, slot 1 locked by 0x05 with a parked thread, slot 3 locked by
+// 0x12 and slot 5 valid with h2 0x33
+const PATTERN: u64 = 0x4A00_3300_9212_C512;
+
+fn indexes(iter: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut indexes: Vec<usize> = iter.collect();
+    indexes.sort_unstable();
+    indexes
+}
+
+#[test]
+fn h2_from_meta() {
+    let meta_data = MetaData::new(PATTERN);
+    assert_eq!(
+        [0x12, 0xC5, 0x12, 0x92, 0x00, 0x33, 0x00],
+        std::array::from_fn(|index| meta_data.h2_from_meta(index))
+    );
+}
+
+#[test]
+fn valid_bits() {
+    let meta_data = MetaData::new(PATTERN);
+    assert_eq!(0b010_0101, meta_data.get_valid_bits());
+    assert_eq!(vec![0, 2, 5], indexes(meta_data.valid_indexes_iter()));
+    assert_eq!(vec![1, 3, 4, 6], indexes(meta_data.not_valid_indexes_iter_from(3)));
+    assert!(!meta_data.bucket_moved());
+    assert!(!meta_data.bucket_full());
+}
+
+#[test]
+fn match_indexes() {
+    let meta_data = MetaData::new(PATTERN);
+    assert_eq!(vec![0, 2], indexes(meta_data.match_indexes_iter(0x12)));
+    assert_eq!(vec![5], indexes(meta_data.match_indexes_iter(0x33)));
+    assert!(meta_data.match_indexes_iter(0xC5).next().is_none());
+}
+
+#[test]
+fn match_locked_indexes() {
+    let meta_data = MetaData::new(PATTERN);
+    assert_eq!(vec![1], indexes(meta_data.match_locked_indexes_iter(0x05)));
+    assert_eq!(vec![3], indexes(meta_data.match_locked_indexes_iter(0x12)));
+    assert!(meta_data.test_lock_bit(1) && meta_data.test_park_bit(1));
+    assert!(meta_data.test_lock_bit(3) && !meta_data.test_park_bit(3));
+    assert_eq!(2, meta_data.count_locked_slots());
+}
+
+#[test]
+fn lock_and_unlock_each_slot() {
+    for index in 0..7 {
+        let locked = MetaData::new(0).lock(0x5A, index);
+        assert_eq!(vec![index], indexes(locked.match_locked_indexes_iter(0x5A)));
+        assert_eq!(1, locked.count_locked_slots());
+        let valid = locked.park(index).unlock(0x5A, index);
+        assert_eq!(0x5A, valid.h2_from_meta(index));
+        assert_eq!(vec![index], indexes(valid.match_indexes_iter(0x5A)));
+        assert_eq!(0, valid.count_locked_slots());
+    }
+}
+
+#[test]
+fn poison_each_slot() {
+    for index in 0..7 {
+        let poisoned = MetaData::new(0).lock(0x5A, index).park(index).poison(index);
+        assert!(poisoned.test_poisoned(index));
+        assert!(!poisoned.test_lock_bit(index) && !poisoned.test_valid_bit(index));
+        assert!(poisoned.match_locked_indexes_iter(0x5A).next().is_none());
+        assert!(poisoned.match_indexes_iter(0x40).next().is_none());
+        assert_eq!(0, poisoned.count_locked_slots());
+        // a poisoned slot is not empty, lookups continue past it
+        let other_slots_valid = MetaData::new(0x7F << 57 & !(1 << (57 + index)));
+        assert!(other_slots_valid.has_empty_slot());
+        assert!(!other_slots_valid.poison(index).has_empty_slot());
+    }
+}
@@ -0,0 +1,151 @@
+//! Property tests that run random sequences of operations on an `Interner` from several threads
+//! and compare the results with a `Mutex<HashSet>` oracle.
+
+use fxhash::FxBuildHasher;
+use interner::interner::Cursor;
+use interner::{Interner, InternerBuilder};
+use proptest::prelude::*;
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+const THREADS: usize = 4;
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Intern(u32),
+    Get(u32),
+}
+
+// a hasher that only keeps the low 8 bits, so all values have the same h2 and about two values
+// have the same hash
+#[derive(Default)]
+struct LowBitsHasher(u64);
+
+impl Hasher for LowBitsHasher {
+    fn finish(&self) -> u64 {
+        self.0 & 0xFF
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = self.0.wrapping_mul(31).wrapping_add(u64::from(*byte));
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum HasherKind {
+    Random,
+    Fx,
+    LowBits,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    // a small key space so the threads intern and look up the same values
+    prop_oneof![3 => (0..512u32).prop_map(Op::Intern), 1 => (0..512u32).prop_map(Op::Get)]
+}
+
+fn hasher_kind() -> impl Strategy<Value = HasherKind> {
+    prop_oneof![Just(HasherKind::Random), Just(HasherKind::Fx), Just(HasherKind::LowBits)]
+}
+
+fn all_values<S: BuildHasher>(interner: &Interner<u32, S>) -> Vec<u32> {
+    let mut values = Vec::new();
+    let mut cursor = Some(Cursor::default());
+    while let Some(current) = cursor {
+        let (chunk, next) = interner.iter_chunk(current, 64);
+        values.extend(chunk);
+        cursor = next;
+    }
+    values
+}
+
+fn check<S>(capacity: usize, hash_builder: S, ops: &[Vec<Op>]) -> Result<(), TestCaseError>
+where
+    S: BuildHasher + Sync,
+{
+    let interner: Interner<u32, S> =
+        InternerBuilder::new().capacity(capacity).hasher(hash_builder).build();
+    let oracle = Mutex::new(HashSet::new());
+    let makes = AtomicUsize::new(0);
+    let interned: Vec<u32> = ops
+        .iter()
+        .flatten()
+        .filter_map(|op| match op {
+            Op::Intern(value) => Some(*value),
+            Op::Get(_) => None,
+        })
+        .collect();
+    std::thread::scope(|scope| {
+        let threads: Vec<_> = ops
+            .iter()
+            .map(|ops| {
+                let (interner, oracle, makes, interned) = (&interner, &oracle, &makes, &interned);
+                scope.spawn(move || -> Result<(), TestCaseError> {
+                    let mut own = HashSet::new();
+                    for op in ops {
+                        match *op {
+                            Op::Intern(value) => {
+                                let result = interner.intern_ref(&value, || {
+                                    makes.fetch_add(1, Ordering::Relaxed);
+                                    value
+                                });
+                                prop_assert_eq!(value, result);
+                                oracle.lock().unwrap().insert(value);
+                                own.insert(value);
+                            }
+                            Op::Get(value) => {
+                                let hash = interner.hasher().hash_one(value);
+                                let result = interner.get_from_hash(hash, |found| *found == value);
+                                if own.contains(&value) {
+                                    // interned by this thread before the lookup
+                                    prop_assert_eq!(Some(&value), result);
+                                } else if !interned.contains(&value) {
+                                    // never interned by any thread
+                                    prop_assert_eq!(None, result);
+                                } else if let Some(result) = result {
+                                    prop_assert_eq!(value, *result);
+                                }
+                            }
+                        }
+                    }
+                    Ok(())
+                })
+            })
+            .collect();
+        threads.into_iter().try_for_each(|thread| thread.join().unwrap())
+    })?;
+
+    let oracle = oracle.into_inner().unwrap();
+    prop_assert_eq!(oracle.len(), makes.load(Ordering::Relaxed));
+    let mut values = all_values(&interner);
+    values.sort_unstable();
+    let mut expected: Vec<u32> = oracle.iter().copied().collect();
+    expected.sort_unstable();
+    prop_assert_eq!(expected, values);
+    for value in oracle {
+        let hash = interner.hasher().hash_one(value);
+        prop_assert_eq!(Some(&value), interner.get_from_hash(hash, |found| *found == value));
+        prop_assert_eq!(value, interner.intern_ref(&value, || unreachable!()));
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn interner_matches_hash_set_oracle(
+        capacity in prop_oneof![Just(0usize), 1..64usize, 64..1024usize],
+        hasher_kind in hasher_kind(),
+        ops in prop::collection::vec(prop::collection::vec(op(), 0..256), THREADS),
+    ) {
+        match hasher_kind {
+            HasherKind::Random => check(capacity, RandomState::new(), &ops)?,
+            HasherKind::Fx => check(capacity, FxBuildHasher::default(), &ops)?,
+            HasherKind::LowBits => {
+                check(capacity, BuildHasherDefault::<LowBitsHasher>::default(), &ops)?
+            }
+        }
+    }
+}
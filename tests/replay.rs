@@ -0,0 +1,48 @@
+//! Checks the trace of the `replay` feature against the operations that produced it.
+#![cfg(feature = "replay")]
+
+use interner::replay::{clear, trace, EventKind};
+use interner::Interner;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::sync::Barrier;
+
+#[test]
+fn trace_has_one_insert_per_value() {
+    let values: Vec<u64> = (0..500).collect();
+    let interner: Interner<&u64> = Interner::new();
+    clear();
+    // the events of a thread are dropped when it exits, so the threads wait for the trace
+    let barrier = Barrier::new(5);
+    let events = std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for value in values.iter() {
+                    interner.intern_ref(value, || value);
+                }
+                barrier.wait();
+                barrier.wait();
+            });
+        }
+        barrier.wait();
+        let events = trace();
+        barrier.wait();
+        events
+    });
+    assert!(events.windows(2).all(|pair| pair[0].seq < pair[1].seq));
+    let mut inserts: HashMap<u64, usize> = HashMap::new();
+    for event in events.iter().filter(|event| event.kind == EventKind::Locked) {
+        assert!(event.bucket.is_some() && event.slot.is_some());
+        *inserts.entry(event.hash).or_default() += 1;
+    }
+    assert_eq!(values.len(), inserts.len());
+    for value in values.iter() {
+        let hash = interner.hasher().hash_one(value);
+        assert_eq!(Some(&1), inserts.get(&hash), "value {value} locked more than once");
+    }
+    // the first table is the empty table, all values were moved at least once
+    assert!(events.iter().any(|event| event.kind == EventKind::TransferLocked));
+    assert!(events.iter().any(|event| event.kind == EventKind::BucketMoved));
+    let generations: Vec<u32> = events.iter().map(|event| event.generation).collect();
+    assert!(generations.iter().max() > generations.iter().min());
+}
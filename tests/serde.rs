@@ -0,0 +1,37 @@
+//! Round trips of the interner contents with the `serde` feature.
+#![cfg(feature = "serde")]
+
+use interner::{Interner, InternerBuilder};
+use rayon::prelude::*;
+
+#[test]
+fn round_trip() {
+    let values: Vec<String> = (0..10_000).map(|value| value.to_string()).collect();
+    let interner: Interner<&str> = Interner::with_capacity(16);
+    values.par_iter().for_each(|value| {
+        interner.intern_ref(value.as_str(), || value.as_str());
+    });
+    let json = serde_json::to_string(&interner).unwrap();
+    let restored: Interner<&str> = serde_json::from_str(&json).unwrap();
+    assert_eq!(values.len(), restored.len());
+    // the restored table is sized for the values, so no resize was needed
+    assert_eq!(1, restored.stats().tables);
+    assert!(values.iter().all(|value| restored.get(value.as_str()) == Some(value.as_str())));
+    // the restored values borrow from the serialized data
+    let restored_value = restored.get("42").unwrap();
+    assert!(json.as_bytes().as_ptr_range().contains(&restored_value.as_ptr()));
+}
+
+#[test]
+fn round_trip_preset_and_duplicates() {
+    let interner: Interner<u32> = InternerBuilder::new().build_with_preset([1, 2]);
+    interner.intern(3, |value| value);
+    let mut serialized: Vec<u32> =
+        serde_json::from_str(&serde_json::to_string(&interner).unwrap()).unwrap();
+    serialized.sort_unstable();
+    assert_eq!(vec![1, 2, 3], serialized);
+    let restored: Interner<u32> = serde_json::from_str("[5, 4, 5, 4, 6]").unwrap();
+    assert_eq!(3, restored.len());
+    assert_eq!(Some(6), restored.get(&6));
+    assert!(serde_json::from_str::<Interner<u32>>("{}").is_err());
+}
@@ -0,0 +1,72 @@
+//! Differential tests of the slot matching of the meta data. The matching is done with SIMD with
+//! the `nightly` feature and one slot at a time without it, both are compared with a reference
+//! that checks the slots one by one with `h2_from_meta` and `test_valid_bit`. Run with and
+//! without `--no-default-features` so the two implementations are checked against the same
+//! reference.
+#![cfg(feature = "unstable-internals")]
+
+use interner::internals::MetaData;
+use proptest::prelude::*;
+
+const PARK_BIT: u8 = 0x40;
+
+fn indexes(iter: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut indexes: Vec<usize> = iter.collect();
+    indexes.sort_unstable();
+    indexes
+}
+
+// the slots where `is_match` is true for the valid bit and the h2 byte of the slot
+fn reference(meta_data: &MetaData, mut is_match: impl FnMut(bool, u8) -> bool) -> Vec<usize> {
+    (0..7)
+        .filter(|&index| is_match(meta_data.test_valid_bit(index), meta_data.h2_from_meta(index)))
+        .collect()
+}
+
+fn meta_data() -> impl Strategy<Value = MetaData> {
+    // mostly slots in reachable states, empty, locked, parked, poisoned or valid, and some random
+    // words
+    let slot = prop_oneof![
+        Just(0u8),
+        (0..0x40u8).prop_map(|h2| h2 | MetaData::LOCKED_BIT),
+        (0..0x40u8).prop_map(|h2| h2 | MetaData::LOCKED_BIT | PARK_BIT),
+        Just(PARK_BIT),
+        any::<u8>(),
+    ];
+    prop_oneof![
+        (prop::array::uniform7(slot), any::<u8>()).prop_map(|(slots, top)| {
+            let mut bytes = [0; 8];
+            bytes[..7].copy_from_slice(&slots);
+            bytes[7] = top;
+            MetaData::new(u64::from_le_bytes(bytes))
+        }),
+        any::<u64>().prop_map(MetaData::new),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn match_indexes_matches_reference(meta_data in meta_data(), h2 in any::<u8>()) {
+        prop_assert_eq!(
+            reference(&meta_data, |valid, byte| valid && byte == h2),
+            indexes(meta_data.match_indexes_iter(h2))
+        );
+    }
+
+    #[test]
+    fn match_locked_indexes_matches_reference(meta_data in meta_data(), h2 in any::<u8>()) {
+        let locked = h2 & 0x3F | MetaData::LOCKED_BIT;
+        prop_assert_eq!(
+            reference(&meta_data, |valid, byte| !valid && byte & !PARK_BIT == locked),
+            indexes(meta_data.match_locked_indexes_iter(h2))
+        );
+    }
+
+    #[test]
+    fn empty_and_locked_slots_match_reference(meta_data in meta_data()) {
+        let empty = reference(&meta_data, |valid, byte| !valid && byte == 0);
+        let locked = reference(&meta_data, |valid, byte| !valid && byte != 0 && byte != PARK_BIT);
+        prop_assert_eq!(!empty.is_empty(), meta_data.has_empty_slot());
+        prop_assert_eq!(locked.len() as isize, meta_data.count_locked_slots());
+    }
+}
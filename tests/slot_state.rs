@@ -0,0 +1,177 @@
+//! Exhaustive checks of the slot state machine in `internals`, the executable specification of
+//! the meta data protocol of the buckets.
+#![cfg(feature = "unstable-internals")]
+
+use interner::internals::{step, MetaData, SlotEvent, SlotState};
+use std::collections::{HashSet, VecDeque};
+
+const SLOTS: usize = 7;
+const MOVED_BIT: u64 = 1 << 56;
+
+// the other slots: slot 1 locked by 0x05 with a parked thread, slot 3 locked by 0x12, slot 2 and
+// 5 valid and slot 4 empty
+const BACKGROUND: u64 = 0x4800_3300_9212_C500;
+
+fn valid_bit(index: usize) -> u64 {
+    1 << (64 - 7 + index)
+}
+
+fn events() -> impl Iterator<Item = SlotEvent> {
+    let events = [SlotEvent::Park, SlotEvent::Poison, SlotEvent::MoveBucket];
+    (0..=u8::MAX).flat_map(|h2| [SlotEvent::Lock { h2 }, SlotEvent::Publish { h2 }]).chain(events)
+}
+
+#[test]
+fn every_bit_pattern_of_a_slot() {
+    for index in 0..SLOTS {
+        let background = BACKGROUND & !(0xFF << (8 * index)) & !valid_bit(index);
+        for byte in 0..=u8::MAX {
+            for valid in [0, valid_bit(index)] {
+                for moved in [0, MOVED_BIT] {
+                    let before =
+                        MetaData::new(background | (byte as u64) << (8 * index) | valid | moved);
+                    let state = SlotState::decode(&before, index);
+                    assert_eq!(state.is_none(), before.unreachable_slots() & (1 << index) != 0);
+                    for event in events() {
+                        let Some(transition) = step(&before, index, event) else {
+                            continue;
+                        };
+                        let after = transition.meta_data;
+                        let state = state.expect("transition from a state that is not reachable");
+                        let next = SlotState::decode(&after, index).expect("unreachable state");
+                        for other in (0..SLOTS).filter(|&other| other != index) {
+                            assert_eq!(
+                                SlotState::decode(&before, other),
+                                SlotState::decode(&after, other)
+                            );
+                        }
+                        assert!(after.bucket_moved() || !before.bucket_moved());
+                        assert_eq!(
+                            matches!(event, SlotEvent::MoveBucket),
+                            before != after && next == state
+                        );
+                        match (state, event) {
+                            (SlotState::Empty, SlotEvent::Lock { h2 }) => {
+                                assert!(!before.bucket_moved());
+                                assert_eq!(
+                                    SlotState::Locked { h2: h2 & 0x3F, parked: false },
+                                    next
+                                );
+                            }
+                            (SlotState::Locked { h2, .. }, SlotEvent::Park) => {
+                                assert_eq!(SlotState::Locked { h2, parked: true }, next);
+                            }
+                            (
+                                SlotState::Locked { h2: locked, parked },
+                                SlotEvent::Publish { h2 },
+                            ) => {
+                                assert_eq!(locked, h2 & 0x3F);
+                                assert_eq!(SlotState::Valid { h2 }, next);
+                                // a parked thread is never left behind
+                                assert_eq!(parked, transition.unpark);
+                            }
+                            (SlotState::Locked { parked, .. }, SlotEvent::Poison) => {
+                                assert_eq!(SlotState::Poisoned, next);
+                                assert_eq!(parked, transition.unpark);
+                            }
+                            (_, SlotEvent::MoveBucket) => assert!(after.bucket_moved()),
+                            _ => panic!("{event:?} from {state:?} is not a transition"),
+                        }
+                        assert!(
+                            !transition.unpark
+                                || matches!(event, SlotEvent::Publish { .. } | SlotEvent::Poison)
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+// the state of the model and of the threads using it, for two slots of a bucket
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct World {
+    meta_data: MetaData,
+    // the value of the slot is written, only the insert that locked the slot writes it
+    written: [bool; 2],
+    // the insert that locked the slot gave up, the slot is never written
+    poisoned: [bool; 2],
+    // a thread is parked on the slot and waits to be unparked
+    parked: [bool; 2],
+}
+
+#[test]
+fn reachable_states_from_an_empty_bucket() {
+    const INDEXES: [usize; 2] = [0, 6];
+    let h2s = [0x00, 0x12, 0x52, 0xFF];
+    let start = World {
+        meta_data: MetaData::new(0),
+        written: [false; 2],
+        poisoned: [false; 2],
+        parked: [false; 2],
+    };
+    let mut seen = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(world) = queue.pop_front() {
+        let states = INDEXES.map(|index| SlotState::decode(&world.meta_data, index).unwrap());
+        let locked = states.iter().filter(|state| matches!(state, SlotState::Locked { .. }));
+        assert_eq!(locked.count() as isize, world.meta_data.count_locked_slots());
+        for (slot, state) in states.iter().enumerate() {
+            // no valid slot without the write of its value, and no thread parked on it
+            assert_eq!(matches!(state, SlotState::Valid { .. }), world.written[slot]);
+            assert_eq!(matches!(state, SlotState::Poisoned), world.poisoned[slot]);
+            assert!(!world.written[slot] || !world.poisoned[slot]);
+            assert!(!world.parked[slot] || matches!(state, SlotState::Locked { parked: true, .. }));
+        }
+
+        for (slot, index) in INDEXES.into_iter().enumerate() {
+            let slot_events = h2s
+                .into_iter()
+                .flat_map(|h2| [SlotEvent::Lock { h2 }, SlotEvent::Publish { h2 }])
+                .chain([SlotEvent::Park, SlotEvent::Poison, SlotEvent::MoveBucket]);
+            for event in slot_events {
+                let Some(transition) = step(&world.meta_data, index, event) else {
+                    continue;
+                };
+                let mut next = World { meta_data: transition.meta_data, ..world };
+                match event {
+                    SlotEvent::Lock { .. } => assert!(!world.meta_data.bucket_moved()),
+                    SlotEvent::Park => next.parked[slot] = true,
+                    SlotEvent::Publish { .. } => {
+                        next.written[slot] = true;
+                        if transition.unpark {
+                            next.parked[slot] = false;
+                        }
+                    }
+                    SlotEvent::Poison => {
+                        next.poisoned[slot] = true;
+                        if transition.unpark {
+                            next.parked[slot] = false;
+                        }
+                    }
+                    SlotEvent::MoveBucket => {}
+                }
+                if seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    // 12 states for each slot, empty, locked with one of the 3 different 6 bit h2s with and
+    // without the park bit, valid with one of the 4 h2s and poisoned, all combinations with and
+    // without the moved bit
+    let meta_data: HashSet<MetaData> = seen.iter().map(|world| world.meta_data).collect();
+    assert_eq!(2 * 12 * 12, meta_data.len());
+}
+
+#[cfg(any(debug_assertions, feature = "checked"))]
+#[test]
+#[should_panic(expected = "interner slot 0 of bucket 0 is valid but was not written")]
+fn read_of_unwritten_valid_slot_panics() {
+    use interner::interner::Cursor;
+    use interner::{internals, Interner};
+    let interner: Interner<u64> = Interner::with_capacity(6);
+    // SAFTY: the read of the slot is caught by the `checked` feature
+    unsafe { internals::publish_unwritten_slot(&interner, 0, 0, 0) };
+    interner.iter_chunk(Cursor::default(), 16);
+}
@@ -0,0 +1,32 @@
+//! Checks that a thread waiting on a short `make` finds the value while it spins. The park
+//! counters are shared by the whole process, so the test has its own test binary.
+
+use interner::stats::wait_stats;
+use interner::{Interner, InternerBuilder};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+#[test]
+fn short_make_is_waited_for_without_parking() {
+    let value = 42u64;
+    let interner: Interner<&u64> = InternerBuilder::new().spin_before_park(16).build();
+    let making = AtomicBool::new(false);
+    let before = wait_stats();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            interner.intern_ref(&value, || {
+                making.store(true, Ordering::Release);
+                let start = Instant::now();
+                while start.elapsed() < Duration::from_micros(20) {
+                    std::hint::spin_loop();
+                }
+                &value
+            });
+        });
+        while !making.load(Ordering::Acquire) {
+            std::hint::spin_loop();
+        }
+        assert_eq!(&value, interner.intern_ref(&value, || unreachable!()));
+    });
+    assert_eq!(before.total_parks(), wait_stats().total_parks());
+}